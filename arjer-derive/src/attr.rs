@@ -0,0 +1,63 @@
+use syn::{Attribute, ExprPath, LitStr, Token};
+
+/// What a missing member of a `default` field is replaced with.
+pub(crate) enum DefaultValue {
+    /// `#[json(default)]`
+    Trait,
+    /// `#[json(default = "path::to::function")]`
+    Function(ExprPath),
+}
+
+/// The `#[json(...)]` attributes of a field or variant.
+#[derive(Default)]
+pub(crate) struct Attrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) default: Option<DefaultValue>,
+    pub(crate) skip: bool,
+    pub(crate) flatten: bool,
+}
+
+impl Attrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Attrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("json")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    parsed.rename = Some(name.value());
+                } else if meta.path.is_ident("default") {
+                    parsed.default = Some(if meta.input.peek(Token![=]) {
+                        let path: LitStr = meta.value()?.parse()?;
+                        DefaultValue::Function(path.parse()?)
+                    } else {
+                        DefaultValue::Trait
+                    });
+                } else if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    parsed.flatten = true;
+                } else {
+                    return Err(meta.error("expected `rename`, `default`, `skip` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+
+    /// Whether anything but `rename` was given.
+    pub(crate) fn beyond_rename(&self) -> bool {
+        self.default.is_some() || self.skip || self.flatten
+    }
+}
+
+/// Fails on `#[json(...)]` attributes in places that don't take any.
+pub(crate) fn reject(attrs: &[Attribute], place: &str) -> syn::Result<()> {
+    match attrs.iter().find(|a| a.path().is_ident("json")) {
+        Some(attr) => Err(syn::Error::new_spanned(
+            attr,
+            format!("`json` attributes are not supported on {}", place),
+        )),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,233 @@
+use crate::attr::{self, Attrs, DefaultValue};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Fields, Generics, Ident, Index};
+
+/// A named field and the member it maps to.
+struct Field<'a> {
+    ident: &'a Ident,
+    name: String,
+    attrs: Attrs,
+}
+
+/// What the derived impls have to deal with.
+enum Shape<'a> {
+    /// Converts to and from an object.
+    Named(Vec<Field<'a>>),
+    /// A newtype converts like its field, other tuple structs to and from an array.
+    Tuple(usize),
+    /// Converts to and from `null`.
+    Unit,
+    /// A unit-only enum; converts to and from the name of the variant.
+    Enum(Vec<(&'a Ident, String)>),
+}
+
+fn shape(input: &DeriveInput) -> syn::Result<Shape<'_>> {
+    attr::reject(
+        &input.attrs,
+        "the type itself, only on its fields and variants",
+    )?;
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut named = vec![];
+                for field in &fields.named {
+                    let ident = field.ident.as_ref().expect("named fields have names");
+                    let attrs = Attrs::parse(&field.attrs)?;
+                    if attrs.flatten && (attrs.rename.is_some() || attrs.default.is_some()) {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            "a `flatten` field has no member of its own to rename or default",
+                        ));
+                    }
+                    let name = attrs
+                        .rename
+                        .clone()
+                        .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").into());
+                    named.push(Field { ident, name, attrs });
+                }
+                Ok(Shape::Named(named))
+            }
+            Fields::Unnamed(fields) => {
+                for field in &fields.unnamed {
+                    attr::reject(&field.attrs, "tuple struct fields")?;
+                }
+                Ok(Shape::Tuple(fields.unnamed.len()))
+            }
+            Fields::Unit => Ok(Shape::Unit),
+        },
+        Data::Enum(data) => {
+            let mut variants = vec![];
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "only enums whose variants are all units can be derived",
+                    ));
+                }
+                let attrs = Attrs::parse(&variant.attrs)?;
+                if attrs.beyond_rename() {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "variants only take `rename`",
+                    ));
+                }
+                let name = attrs.rename.unwrap_or_else(|| variant.ident.to_string());
+                variants.push((&variant.ident, name));
+            }
+            Ok(Shape::Enum(variants))
+        }
+        Data::Union(data) => Err(syn::Error::new_spanned(
+            data.union_token,
+            "unions can't be derived",
+        )),
+    }
+}
+
+/// `generics` with every type parameter bound by `bound`.
+fn bounded(generics: &Generics, bound: TokenStream) -> Generics {
+    let mut generics = generics.clone();
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+pub(crate) fn from_json(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let private = quote!(::arjer::convert::__private);
+    let body = match shape(input)? {
+        Shape::Named(fields) => {
+            let fields = fields.iter().map(|field| {
+                let Field { ident, name, attrs } = field;
+                let value = if attrs.skip {
+                    match &attrs.default {
+                        Some(DefaultValue::Function(function)) => quote!(#function()),
+                        _ => quote!(::core::default::Default::default()),
+                    }
+                } else if attrs.flatten {
+                    quote!(::arjer::convert::FromJson::from_json(value)?)
+                } else {
+                    match &attrs.default {
+                        Some(DefaultValue::Trait) => quote! {
+                            #private::field_or_else(members, #name, ::core::default::Default::default)?
+                        },
+                        Some(DefaultValue::Function(function)) => {
+                            quote!(#private::field_or_else(members, #name, #function)?)
+                        }
+                        None => quote!(#private::field(members, #name)?),
+                    }
+                };
+                quote!(#ident: #value)
+            });
+            quote! {
+                #[allow(unused_variables)]
+                let members = #private::object(value)?;
+                ::core::result::Result::Ok(Self { #(#fields),* })
+            }
+        }
+        Shape::Tuple(1) => {
+            quote!(::arjer::convert::FromJson::from_json(value).map(Self))
+        }
+        Shape::Tuple(len) => {
+            let elements = (0..len).map(|i| quote!(#private::element(items, #i)?));
+            quote! {
+                let items = #private::array(value, #len)?;
+                ::core::result::Result::Ok(Self(#(#elements),*))
+            }
+        }
+        Shape::Unit => quote! {
+            <() as ::arjer::convert::FromJson>::from_json(value)?;
+            ::core::result::Result::Ok(Self)
+        },
+        Shape::Enum(variants) => {
+            let arms = variants
+                .iter()
+                .map(|(ident, name)| quote!(#name => ::core::result::Result::Ok(Self::#ident)));
+            quote! {
+                match #private::variant(value)? {
+                    #(#arms,)*
+                    other => ::core::result::Result::Err(#private::unknown_variant(other)),
+                }
+            }
+        }
+    };
+    let generics = bounded(&input.generics, quote!(::arjer::convert::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident = &input.ident;
+    Ok(quote! {
+        impl #impl_generics ::arjer::convert::FromJson for #ident #ty_generics #where_clause {
+            fn from_json(
+                value: &::arjer::json::JsValue,
+            ) -> ::core::result::Result<Self, ::arjer::convert::FromJsonError> {
+                #body
+            }
+        }
+    })
+}
+
+pub(crate) fn to_json(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let private = quote!(::arjer::convert::__private);
+    let body = match shape(input)? {
+        Shape::Named(fields) => {
+            let members = fields.iter().filter(|field| !field.attrs.skip).map(
+                |Field { ident, name, attrs }| {
+                    if attrs.flatten {
+                        quote!(#private::flatten_into(&mut members, &self.#ident);)
+                    } else {
+                        quote! {
+                            members.insert(
+                                ::std::string::String::from(#name),
+                                ::arjer::convert::ToJson::to_json(&self.#ident),
+                            );
+                        }
+                    }
+                },
+            );
+            quote! {
+                #[allow(unused_mut)]
+                let mut members = ::arjer::json::Map::new();
+                #(#members)*
+                ::arjer::json::JsValue::JsObject(members)
+            }
+        }
+        Shape::Tuple(1) => quote!(::arjer::convert::ToJson::to_json(&self.0)),
+        Shape::Tuple(len) => {
+            let elements = (0..len).map(Index::from);
+            quote! {
+                ::arjer::json::JsValue::JsArray(::std::vec![
+                    #(::arjer::convert::ToJson::to_json(&self.#elements)),*
+                ])
+            }
+        }
+        Shape::Unit => quote!(::arjer::json::JsValue::JsNull),
+        Shape::Enum(variants) => {
+            let arms = variants.iter().map(|(ident, name)| {
+                quote! {
+                    Self::#ident => ::arjer::json::JsValue::JsString(
+                        ::std::string::String::from(#name),
+                    )
+                }
+            });
+            quote! {
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    };
+    let generics = bounded(&input.generics, quote!(::arjer::convert::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident = &input.ident;
+    Ok(quote! {
+        impl #impl_generics ::arjer::convert::ToJson for #ident #ty_generics #where_clause {
+            fn to_json(&self) -> ::arjer::json::JsValue {
+                #body
+            }
+        }
+    })
+}
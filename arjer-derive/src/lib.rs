@@ -0,0 +1,25 @@
+//! `#[derive(FromJson, ToJson)]` for `arjer`. Use it through arjer's `derive` feature,
+//! which re-exports the macros next to the traits; the attributes they take are described
+//! in `arjer::convert`.
+
+mod attr;
+mod expand;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::from_json(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::to_json(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -24,15 +24,85 @@ fn bench_parsers(c: &mut Criterion) {
             })
         });
 
+        group.bench_with_input(BenchmarkId::new("my_parser_borrowed", name), s, |b, s| {
+            b.iter(|| {
+                let _ = arjer::parse_borrowed(black_box(s)).unwrap();
+            })
+        });
+
         group.bench_with_input(BenchmarkId::new("serde_json", name), s, |b, s| {
             b.iter(|| {
                 let _: SerdeValue = serde_json::from_str(black_box(s)).unwrap();
             })
         });
+
+        #[cfg(feature = "serde")]
+        match *name {
+            "small" => typed::bench::<typed::Person>(&mut group, name, s),
+            "medium" => typed::bench::<Vec<typed::Entry>>(&mut group, name, s),
+            _ => typed::bench::<Vec<typed::Item>>(&mut group, name, s),
+        }
     }
 
     group.finish();
 }
 
+// typed deserialization into the shapes of the sample files; run with `--features serde`
+#[cfg(feature = "serde")]
+#[allow(dead_code)] // the fields are only ever written by `Deserialize`
+mod typed {
+    use criterion::measurement::WallTime;
+    use criterion::{black_box, BenchmarkGroup, BenchmarkId};
+    use serde::de::DeserializeOwned;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub struct Person {
+        name: String,
+        age: u32,
+        city: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Entry {
+        id: u64,
+        value: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Item {
+        id: u64,
+        name: String,
+        details: Details,
+        available: bool,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Details {
+        price: f64,
+        tags: Vec<String>,
+        nested: Nested,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Nested {
+        key: String,
+    }
+
+    pub fn bench<T: DeserializeOwned>(group: &mut BenchmarkGroup<WallTime>, name: &str, s: &str) {
+        group.bench_with_input(BenchmarkId::new("my_parser_typed", name), s, |b, s| {
+            b.iter(|| {
+                let _: T = arjer::from_str(black_box(s)).unwrap();
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("serde_json_typed", name), s, |b, s| {
+            b.iter(|| {
+                let _: T = serde_json::from_str(black_box(s)).unwrap();
+            })
+        });
+    }
+}
+
 criterion_group!(benches, bench_parsers);
 criterion_main!(benches);
@@ -0,0 +1,319 @@
+use crate::convert::__private::{array, element, object};
+use crate::convert::{FromJson, FromJsonError, ToJson};
+use crate::error::PathSegment;
+use crate::json::{JsValue, Map, Num};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+impl FromJson for JsValue {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsValue {
+    fn to_json(&self) -> JsValue {
+        self.clone()
+    }
+}
+
+impl FromJson for () {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsNull => Ok(()),
+            other => Err(FromJsonError::mismatch("null", other)),
+        }
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsNull
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsBool(b) => Ok(*b),
+            other => Err(FromJsonError::mismatch("boolean", other)),
+        }
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsBool(*self)
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsString(s) => Ok(s.clone()),
+            other => Err(FromJsonError::mismatch("string", other)),
+        }
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsString(self.clone())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsString(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        let mismatch = || FromJsonError::mismatch("a single character", value);
+        let JsValue::JsString(s) = value else {
+            return Err(mismatch());
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(mismatch()),
+        }
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsString(self.to_string())
+    }
+}
+
+/// Integers convert from any integer `Num` whose value fits, whichever variant holds it.
+/// Numbers with a fraction or exponent are `Num::F` and never convert to integers.
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl FromJson for $t {
+            fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+                let converted = match value {
+                    JsValue::JsNumber(Num::U32(n)) => <$t>::try_from(*n).ok(),
+                    JsValue::JsNumber(Num::U64(n)) => <$t>::try_from(*n).ok(),
+                    JsValue::JsNumber(Num::I64(n)) => <$t>::try_from(*n).ok(),
+                    other => return Err(FromJsonError::mismatch("integer", other)),
+                };
+                converted.ok_or(FromJsonError::OutOfRange {
+                    target: stringify!($t),
+                    path: vec![],
+                })
+            }
+        }
+
+        impl ToJson for $t {
+            fn to_json(&self) -> JsValue {
+                JsValue::JsNumber(integer_num(*self as i128))
+            }
+        }
+    )*};
+}
+
+integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// The variant `Num::parse` would give the integer.
+fn integer_num(n: i128) -> Num {
+    if let Ok(n) = u32::try_from(n) {
+        Num::U32(n)
+    } else if let Ok(n) = u64::try_from(n) {
+        Num::U64(n)
+    } else {
+        Num::I64(n as i64)
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsNumber(n) => Ok(n.as_f64()),
+            other => Err(FromJsonError::mismatch("number", other)),
+        }
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsNumber(Num::F(*self))
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        let f = f64::from_json(value)? as f32;
+        if f.is_finite() {
+            Ok(f)
+        } else {
+            Err(FromJsonError::OutOfRange {
+                target: "f32",
+                path: vec![],
+            })
+        }
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsNumber(Num::F(*self as f64))
+    }
+}
+
+/// `null` and a missing member are both `None`.
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsNull => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsValue {
+        match self {
+            Some(value) => value.to_json(),
+            None => JsValue::JsNull,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+
+    fn from_missing() -> Option<Self> {
+        T::from_missing().map(Box::new)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsValue::JsArray(items) => (0..items.len()).map(|i| element(items, i)).collect(),
+            other => Err(FromJsonError::mismatch("array", other)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsArray(self.iter().map(T::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> JsValue {
+        self.as_slice().to_json()
+    }
+}
+
+/// Converts the members of an object, in document order.
+fn members<T: FromJson, C: FromIterator<(String, T)>>(value: &JsValue) -> Result<C, FromJsonError> {
+    object(value)?
+        .iter()
+        .map(|(k, v)| match T::from_json(v) {
+            Ok(v) => Ok((k.clone(), v)),
+            Err(e) => Err(e.within(PathSegment::Key(k.clone()))),
+        })
+        .collect()
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        members(value)
+    }
+}
+
+/// Members are written in key order, since the map's own iteration order changes from one
+/// run to the next.
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> JsValue {
+        let mut members: Vec<_> = self.iter().collect();
+        members.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        JsValue::JsObject(
+            members
+                .into_iter()
+                .map(|(k, v)| (k.clone(), v.to_json()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        members(value)
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsObject(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Map<String, T> {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+        members(value)
+    }
+}
+
+impl<T: ToJson> ToJson for Map<String, T> {
+    fn to_json(&self) -> JsValue {
+        JsValue::JsObject(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+/// Tuples are arrays of exactly their length.
+macro_rules! tuple {
+    ($len:literal: $($t:ident $i:tt),+) => {
+        impl<$($t: FromJson),+> FromJson for ($($t,)+) {
+            fn from_json(value: &JsValue) -> Result<Self, FromJsonError> {
+                let items = array(value, $len)?;
+                Ok(($(element::<$t>(items, $i)?,)+))
+            }
+        }
+
+        impl<$($t: ToJson),+> ToJson for ($($t,)+) {
+            fn to_json(&self) -> JsValue {
+                JsValue::JsArray(vec![$(self.$i.to_json()),+])
+            }
+        }
+    };
+}
+
+tuple!(1: A 0);
+tuple!(2: A 0, B 1);
+tuple!(3: A 0, B 1, C 2);
+tuple!(4: A 0, B 1, C 2, D 3);
+tuple!(5: A 0, B 1, C 2, D 3, E 4);
+tuple!(6: A 0, B 1, C 2, D 3, E 4, F 5);
+tuple!(7: A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple!(8: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
@@ -0,0 +1,235 @@
+//! Conversions between `JsValue` and Rust types. `FromJson` reads a type out of a parsed
+//! value and `ToJson` builds one from it; both are implemented for the primitives and the
+//! standard containers, and with the `derive` feature `#[derive(FromJson, ToJson)]` writes
+//! them for structs and enums:
+//!
+//! ```ignore
+//! #[derive(FromJson, ToJson)]
+//! struct Job {
+//!     title: String,
+//!     #[json(rename = "self_employed", default)]
+//!     independent: bool,
+//!     #[json(skip)]
+//!     cached: Option<u64>,
+//!     #[json(flatten)]
+//!     address: Address,
+//! }
+//! ```
+//!
+//! Fields are read from the member of the same name, unless `rename` says otherwise. A
+//! missing member is an error, except for `Option` fields, which become `None`, and
+//! `default` fields, which get `Default::default()` (or the result of the function given
+//! as `default = "path"`). `skip` fields are neither read nor written and always start out
+//! as their default, which `default = "path"` can again replace. A `flatten` field is read
+//! from the whole object and has its members written into it. Enums convert only if all of
+//! their variants are units, to and from the variant's name.
+
+mod impls;
+
+use crate::error::{render_path, PathSegment};
+use crate::json::JsValue;
+use std::fmt::{self, Display, Formatter};
+
+pub trait FromJson: Sized {
+    fn from_json(value: &JsValue) -> Result<Self, FromJsonError>;
+
+    /// What a missing object member converts to, or `None` if it has to be present.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> JsValue;
+}
+
+/// Why a value didn't convert. `path` leads from the value `from_json` was called on to
+/// the part that didn't fit, outermost first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FromJsonError {
+    MissingField {
+        field: String,
+        path: Vec<PathSegment>,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        path: Vec<PathSegment>,
+    },
+    /// A number that the target type can't represent, like `-1` for a `u32`.
+    OutOfRange {
+        target: &'static str,
+        path: Vec<PathSegment>,
+    },
+    /// An array of the wrong length for a tuple.
+    WrongLength {
+        expected: usize,
+        found: usize,
+        path: Vec<PathSegment>,
+    },
+    /// A string that isn't the name of any variant of a unit-only enum.
+    UnknownVariant {
+        variant: String,
+        path: Vec<PathSegment>,
+    },
+}
+
+impl FromJsonError {
+    pub fn path(&self) -> &[PathSegment] {
+        match self {
+            FromJsonError::MissingField { path, .. }
+            | FromJsonError::TypeMismatch { path, .. }
+            | FromJsonError::OutOfRange { path, .. }
+            | FromJsonError::WrongLength { path, .. }
+            | FromJsonError::UnknownVariant { path, .. } => path,
+        }
+    }
+
+    fn path_mut(&mut self) -> &mut Vec<PathSegment> {
+        match self {
+            FromJsonError::MissingField { path, .. }
+            | FromJsonError::TypeMismatch { path, .. }
+            | FromJsonError::OutOfRange { path, .. }
+            | FromJsonError::WrongLength { path, .. }
+            | FromJsonError::UnknownVariant { path, .. } => path,
+        }
+    }
+
+    /// `path` rendered by [`render_path`].
+    pub fn json_path(&self) -> String {
+        render_path(self.path())
+    }
+
+    /// Places the error one level deeper, under `segment`.
+    pub(crate) fn within(mut self, segment: PathSegment) -> Self {
+        self.path_mut().insert(0, segment);
+        self
+    }
+
+    pub(crate) fn mismatch(expected: &'static str, found: &JsValue) -> Self {
+        FromJsonError::TypeMismatch {
+            expected,
+            found: type_name(found),
+            path: vec![],
+        }
+    }
+}
+
+impl Display for FromJsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FromJsonError::MissingField { field, .. } => write!(f, "missing field `{}`", field)?,
+            FromJsonError::TypeMismatch {
+                expected, found, ..
+            } => write!(f, "expected {}, found {}", expected, found)?,
+            FromJsonError::OutOfRange { target, .. } => {
+                write!(f, "number out of range for {}", target)?
+            }
+            FromJsonError::WrongLength {
+                expected, found, ..
+            } => write!(
+                f,
+                "expected an array of {} items, found {}",
+                expected, found
+            )?,
+            FromJsonError::UnknownVariant { variant, .. } => {
+                write!(f, "unknown variant `{}`", variant)?
+            }
+        }
+        write!(f, " at {}", self.json_path())
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// The JSON type of `value`, as named in error messages.
+pub(crate) fn type_name(value: &JsValue) -> &'static str {
+    match value {
+        JsValue::JsNull => "null",
+        JsValue::JsString(_) => "string",
+        JsValue::JsNumber(_) => "number",
+        JsValue::JsBool(_) => "boolean",
+        JsValue::JsObject(_) => "object",
+        JsValue::JsArray(_) => "array",
+    }
+}
+
+/// What the derive macros expand to. Not part of the API.
+#[doc(hidden)]
+pub mod __private {
+    use super::{FromJson, FromJsonError, ToJson};
+    use crate::error::PathSegment;
+    use crate::json::{JsValue, Map};
+
+    pub fn object(value: &JsValue) -> Result<&Map, FromJsonError> {
+        match value {
+            JsValue::JsObject(members) => Ok(members),
+            other => Err(FromJsonError::mismatch("object", other)),
+        }
+    }
+
+    pub fn field<T: FromJson>(members: &Map, key: &str) -> Result<T, FromJsonError> {
+        match members.get(key) {
+            Some(value) => T::from_json(value).map_err(|e| e.within(key_segment(key))),
+            None => T::from_missing().ok_or_else(|| FromJsonError::MissingField {
+                field: key.to_string(),
+                path: vec![],
+            }),
+        }
+    }
+
+    pub fn field_or_else<T: FromJson>(
+        members: &Map,
+        key: &str,
+        default: impl FnOnce() -> T,
+    ) -> Result<T, FromJsonError> {
+        match members.get(key) {
+            Some(value) => T::from_json(value).map_err(|e| e.within(key_segment(key))),
+            None => Ok(default()),
+        }
+    }
+
+    pub fn element<T: FromJson>(items: &[JsValue], index: usize) -> Result<T, FromJsonError> {
+        T::from_json(&items[index]).map_err(|e| e.within(PathSegment::Index(index)))
+    }
+
+    pub fn array(value: &JsValue, len: usize) -> Result<&[JsValue], FromJsonError> {
+        match value {
+            JsValue::JsArray(items) if items.len() == len => Ok(items),
+            JsValue::JsArray(items) => Err(FromJsonError::WrongLength {
+                expected: len,
+                found: items.len(),
+                path: vec![],
+            }),
+            other => Err(FromJsonError::mismatch("array", other)),
+        }
+    }
+
+    pub fn variant(value: &JsValue) -> Result<&str, FromJsonError> {
+        match value {
+            JsValue::JsString(name) => Ok(name),
+            other => Err(FromJsonError::mismatch("string", other)),
+        }
+    }
+
+    pub fn unknown_variant(name: &str) -> FromJsonError {
+        FromJsonError::UnknownVariant {
+            variant: name.to_string(),
+            path: vec![],
+        }
+    }
+
+    /// Copies the members of a flattened field into `members`; anything but an object (say,
+    /// the `null` of an empty `Option`) contributes nothing.
+    pub fn flatten_into<T: ToJson>(members: &mut Map, value: &T) {
+        if let JsValue::JsObject(inner) = value.to_json() {
+            for (k, v) in inner {
+                members.insert(k, v);
+            }
+        }
+    }
+
+    fn key_segment(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+}
@@ -0,0 +1,174 @@
+use crate::core::events::{Event, EventSource};
+use crate::core::options::{DuplicateKeys, ParseOptions};
+use crate::error::{Location, ParseError};
+use crate::json::{JsValue, JsValueRef, Map, Num};
+use std::borrow::{Borrow, Cow};
+use std::hash::Hash;
+
+/// Turns the pieces recognised by the parser into a value tree, so one grammar can produce
+/// both the owned `JsValue` and the borrowed `JsValueRef`.
+pub(crate) trait Builder<'a> {
+    type Key: Borrow<str> + Hash + Eq + Clone;
+    type Value;
+
+    fn key(s: Cow<'a, str>) -> Self::Key;
+    fn null() -> Self::Value;
+    fn bool(b: bool) -> Self::Value;
+    fn number(n: Num) -> Self::Value;
+    fn string(s: Cow<'a, str>) -> Self::Value;
+    fn array(values: Vec<Self::Value>) -> Self::Value;
+    fn object(key_values: Map<Self::Key, Self::Value>) -> Self::Value;
+}
+
+pub(crate) struct Owned;
+
+impl<'a> Builder<'a> for Owned {
+    type Key = String;
+    type Value = JsValue;
+
+    fn key(s: Cow<'a, str>) -> String {
+        s.into_owned()
+    }
+
+    fn null() -> JsValue {
+        JsValue::JsNull
+    }
+
+    fn bool(b: bool) -> JsValue {
+        JsValue::JsBool(b)
+    }
+
+    fn number(n: Num) -> JsValue {
+        JsValue::JsNumber(n)
+    }
+
+    fn string(s: Cow<'a, str>) -> JsValue {
+        JsValue::JsString(s.into_owned())
+    }
+
+    fn array(values: Vec<JsValue>) -> JsValue {
+        JsValue::JsArray(values)
+    }
+
+    fn object(key_values: Map) -> JsValue {
+        JsValue::JsObject(key_values)
+    }
+}
+
+pub(crate) struct Borrowed;
+
+impl<'a> Builder<'a> for Borrowed {
+    type Key = Cow<'a, str>;
+    type Value = JsValueRef<'a>;
+
+    fn key(s: Cow<'a, str>) -> Cow<'a, str> {
+        s
+    }
+
+    fn null() -> JsValueRef<'a> {
+        JsValueRef::JsNull
+    }
+
+    fn bool(b: bool) -> JsValueRef<'a> {
+        JsValueRef::JsBool(b)
+    }
+
+    fn number(n: Num) -> JsValueRef<'a> {
+        JsValueRef::JsNumber(n)
+    }
+
+    fn string(s: Cow<'a, str>) -> JsValueRef<'a> {
+        JsValueRef::JsString(s)
+    }
+
+    fn array(values: Vec<JsValueRef<'a>>) -> JsValueRef<'a> {
+        JsValueRef::JsArray(values)
+    }
+
+    fn object(key_values: Map<Cow<'a, str>, JsValueRef<'a>>) -> JsValueRef<'a> {
+        JsValueRef::JsObject(key_values)
+    }
+}
+
+enum Partial<K, V> {
+    Obj {
+        key_values: Map<K, V>,
+        key: Option<K>,
+    },
+    Arr(Vec<V>),
+}
+
+fn insert_entry<K, V>(key_values: &mut Map<K, V>, key: K, value: V, opts: &ParseOptions)
+where
+    K: Borrow<str> + Hash + Eq + Clone,
+{
+    match opts.duplicate_keys {
+        DuplicateKeys::KeepFirst if key_values.contains_key(key.borrow()) => {}
+        DuplicateKeys::CollectAll => key_values.append(key, value),
+        _ => {
+            key_values.insert(key, value);
+        }
+    }
+}
+
+/// Assembles the next complete value from `reader`'s events.
+pub(crate) fn build<'a, B: Builder<'a>, S: EventSource<'a>>(
+    reader: &mut S,
+    opts: &ParseOptions,
+) -> Result<B::Value, ParseError> {
+    let mut stack: Vec<Partial<B::Key, B::Value>> = vec![];
+    loop {
+        let event = reader.next_event()?.ok_or_else(|| {
+            reader.fail(ParseError::InvalidJsonStructure {
+                at: Location::at(reader.offset()),
+            })
+        })?;
+        let value = match event {
+            Event::StartObject => {
+                stack.push(Partial::Obj {
+                    key_values: Map::new(),
+                    key: None,
+                });
+                continue;
+            }
+            Event::StartArray => {
+                stack.push(Partial::Arr(vec![]));
+                continue;
+            }
+            Event::Key(k) => {
+                if let Some(Partial::Obj { key_values, key }) = stack.last_mut() {
+                    if opts.duplicate_keys == DuplicateKeys::Reject && key_values.contains_key(&k) {
+                        return Err(reader.fail(ParseError::DuplicateKey {
+                            key: k.into_owned(),
+                            at: Location::at(reader.offset()),
+                        }));
+                    }
+                    *key = Some(B::key(k));
+                }
+                continue;
+            }
+            Event::EndObject => match stack.pop() {
+                Some(Partial::Obj { key_values, .. }) => B::object(key_values),
+                _ => unreachable!("the reader balances containers"),
+            },
+            Event::EndArray => match stack.pop() {
+                Some(Partial::Arr(values)) => B::array(values),
+                _ => unreachable!("the reader balances containers"),
+            },
+            Event::String(s) => B::string(s),
+            Event::Number(n) => B::number(n),
+            Event::Bool(b) => B::bool(b),
+            Event::Null => B::null(),
+        };
+        match stack.last_mut() {
+            None => return Ok(value),
+            Some(Partial::Obj { key_values, key }) => {
+                let key = key
+                    .take()
+                    .expect("the reader emits a key before each member");
+                insert_entry(key_values, key, value, opts);
+            }
+            Some(Partial::Arr(values)) => values.push(value),
+        }
+    }
+}
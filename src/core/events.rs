@@ -0,0 +1,450 @@
+use crate::core::options::ParseOptions;
+use crate::core::parser::{parse_scalar, parse_str};
+use crate::core::strit::StrIt;
+use crate::error::{Location, ParseError, PathSegment};
+use crate::json::Num;
+use std::borrow::{Borrow, Cow};
+
+/// One step through a document, as produced by `EventReader`. Strings and keys borrow from
+/// the input unless they contained escape sequences.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'a, str>),
+    String(Cow<'a, str>),
+    Number(Num),
+    Bool(bool),
+    Null,
+}
+
+impl Event<'_> {
+    /// Detaches the event from the input by copying any borrowed string.
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Event::StartObject => Event::StartObject,
+            Event::EndObject => Event::EndObject,
+            Event::StartArray => Event::StartArray,
+            Event::EndArray => Event::EndArray,
+            Event::Key(k) => Event::Key(Cow::Owned(k.into_owned())),
+            Event::String(s) => Event::String(Cow::Owned(s.into_owned())),
+            Event::Number(n) => Event::Number(n),
+            Event::Bool(b) => Event::Bool(b),
+            Event::Null => Event::Null,
+        }
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
+enum ObjectParseState {
+    ExpectingKey,
+    ExpectingKeyOrEndOfObject,
+    ExpectingCommaOrEndOfObject,
+    ExpectingColon,
+    ExpectingValue,
+}
+
+#[allow(clippy::enum_variant_names)]
+enum ArrParseState {
+    ExpectingValue,
+    ExpectingValueOrEndOfArray,
+    ExpectingCommaOrEndOfArray,
+}
+
+enum Frame<K> {
+    Obj {
+        state: ObjectParseState,
+        key: Option<K>,
+    },
+    Arr {
+        state: ArrParseState,
+        index: usize,
+    },
+}
+
+/// How `ParserState` holds on to the key of each open object, which it needs for error
+/// paths. Readers over a `&str` keep borrowing it, readers over a buffer that gets reused
+/// have to copy.
+#[allow(clippy::ptr_arg)]
+pub(crate) trait FrameKey<'b>: Borrow<str> {
+    // takes the `Cow` itself so that a borrowed key can be kept without copying it
+    fn from_key(key: &Cow<'b, str>) -> Self;
+}
+
+impl<'a> FrameKey<'a> for Cow<'a, str> {
+    fn from_key(key: &Cow<'a, str>) -> Self {
+        key.clone()
+    }
+}
+
+impl FrameKey<'_> for String {
+    fn from_key(key: &Cow<'_, str>) -> Self {
+        key.to_string()
+    }
+}
+
+/// Why `ParserState::next` stopped without an event.
+pub(crate) enum Halt {
+    // boxed to keep the happy path's `Result` small
+    Error(Box<ParseError>),
+    /// The input ends in the middle of a token; nothing of it has been consumed.
+    NeedMoreInput,
+}
+
+impl From<ParseError> for Halt {
+    fn from(e: ParseError) -> Self {
+        Halt::Error(Box::new(e))
+    }
+}
+
+/// The grammar of a document as an explicit state machine, kept apart from the input so
+/// that it can be driven over a whole `&str` as well as over a buffer that is refilled
+/// between calls. Open containers live on `stack`, so nesting is bounded only by
+/// `ParseOptions::max_depth`.
+pub(crate) struct ParserState<K> {
+    stack: Vec<Frame<K>>,
+    max_depth: usize,
+    root_done: bool,
+    pub(crate) finished: bool,
+    // whether the token being read is a value belonging to the innermost container
+    in_value: bool,
+    pub(crate) token_start: usize,
+}
+
+impl<K> ParserState<K> {
+    pub(crate) fn new(opts: &ParseOptions) -> Self {
+        Self {
+            stack: vec![],
+            max_depth: opts.max_depth,
+            root_done: false,
+            finished: false,
+            in_value: false,
+            token_start: 0,
+        }
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Whether the top-level value has been read completely.
+    pub(crate) fn is_done(&self) -> bool {
+        self.root_done
+    }
+
+    /// Attaches the path to the current position to `e` and stops the parser.
+    pub(crate) fn fail(&mut self, e: ParseError) -> ParseError
+    where
+        K: Borrow<str>,
+    {
+        self.finished = true;
+        // the innermost container only owns the failure if it was reading one of its values
+        let owners = if self.in_value {
+            self.stack.len()
+        } else {
+            self.stack.len().saturating_sub(1)
+        };
+        let path = self.stack[..owners]
+            .iter()
+            .filter_map(|frame| match frame {
+                Frame::Obj { key: Some(key), .. } => {
+                    Some(PathSegment::Key(key.borrow().to_string()))
+                }
+                Frame::Obj { .. } => None,
+                Frame::Arr { index, .. } => Some(PathSegment::Index(*index)),
+            })
+            .collect();
+        e.within_path(path)
+    }
+
+    /// Reads the next event from `it`. On `Halt::NeedMoreInput` the position is left at the
+    /// start of the unfinished token, so the call can be repeated once `it` has more input.
+    pub(crate) fn next<'b>(&mut self, it: &mut StrIt<'b>) -> Result<Option<Event<'b>>, Halt>
+    where
+        K: FrameKey<'b>,
+    {
+        if self.finished {
+            return Ok(None);
+        }
+        match self.step(it) {
+            Ok(event) => Ok(event),
+            Err(e) if it.partial && it.cut_short(self.token_start, &e) => {
+                it.pos = self.token_start;
+                Err(Halt::NeedMoreInput)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn value_done(&mut self) {
+        match self.stack.last_mut() {
+            None => self.root_done = true,
+            Some(Frame::Obj { state, key }) => {
+                *state = ObjectParseState::ExpectingCommaOrEndOfObject;
+                *key = None;
+            }
+            Some(Frame::Arr { state, index }) => {
+                *state = ArrParseState::ExpectingCommaOrEndOfArray;
+                *index += 1;
+            }
+        }
+    }
+
+    fn open(&mut self, it: &mut StrIt, frame: Frame<K>) -> Result<(), ParseError> {
+        if self.stack.len() >= self.max_depth {
+            return Err(ParseError::DepthLimitExceeded {
+                depth: self.max_depth,
+                at: Location::at(it.pos),
+            });
+        }
+        _ = it.pop();
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    fn value<'b>(&mut self, it: &mut StrIt<'b>) -> Result<Event<'b>, ParseError> {
+        self.in_value = true;
+        match it.peek() {
+            Some(b'{') => {
+                self.open(
+                    it,
+                    Frame::Obj {
+                        state: ObjectParseState::ExpectingKeyOrEndOfObject,
+                        key: None,
+                    },
+                )?;
+                Ok(Event::StartObject)
+            }
+            Some(b'[') => {
+                self.open(
+                    it,
+                    Frame::Arr {
+                        state: ArrParseState::ExpectingValueOrEndOfArray,
+                        index: 0,
+                    },
+                )?;
+                Ok(Event::StartArray)
+            }
+            _ => {
+                let event = parse_scalar(it)?;
+                // a number running into the end of a partial input may have more digits, so
+                // treat it like any other token that was cut short
+                if it.partial && it.pos == it.s.len() && matches!(event, Event::Number(_)) {
+                    return Err(it.eof());
+                }
+                self.value_done();
+                Ok(event)
+            }
+        }
+    }
+
+    fn close<'b>(
+        &mut self,
+        it: &mut StrIt,
+        event: Event<'b>,
+    ) -> Result<Option<Event<'b>>, ParseError> {
+        _ = it.pop();
+        self.stack.pop();
+        self.value_done();
+        Ok(Some(event))
+    }
+
+    /// `next` for input that is known to be complete.
+    pub(crate) fn step<'b>(&mut self, it: &mut StrIt<'b>) -> Result<Option<Event<'b>>, ParseError>
+    where
+        K: FrameKey<'b>,
+    {
+        loop {
+            it.skip_whitespace();
+            self.token_start = it.pos;
+            self.in_value = false;
+            let Some(frame) = self.stack.last_mut() else {
+                if self.root_done {
+                    return Ok(None);
+                }
+                return self.value(it).map(Some);
+            };
+            let next = *it.peek().ok_or_else(|| it.eof())?;
+            match frame {
+                Frame::Obj { state, key } => match (&state, next) {
+                    (
+                        ObjectParseState::ExpectingKeyOrEndOfObject
+                        | ObjectParseState::ExpectingCommaOrEndOfObject,
+                        b'}',
+                    ) => return self.close(it, Event::EndObject),
+                    (
+                        ObjectParseState::ExpectingKey
+                        | ObjectParseState::ExpectingKeyOrEndOfObject,
+                        b'"',
+                    ) => {
+                        _ = it.pop(); // pop "
+                        let k = parse_str(it)?;
+                        *key = Some(K::from_key(&k));
+                        *state = ObjectParseState::ExpectingColon;
+                        return Ok(Some(Event::Key(k)));
+                    }
+                    (ObjectParseState::ExpectingKey, _) => {
+                        return Err(it.unexpected(&["\""]));
+                    }
+                    (ObjectParseState::ExpectingKeyOrEndOfObject, _) => {
+                        return Err(it.unexpected(&["\"", "}"]));
+                    }
+                    (ObjectParseState::ExpectingCommaOrEndOfObject, b',') => {
+                        _ = it.pop();
+                        *state = ObjectParseState::ExpectingKey;
+                    }
+                    (ObjectParseState::ExpectingCommaOrEndOfObject, _) => {
+                        return Err(it.unexpected(&[",", "}"]));
+                    }
+                    (ObjectParseState::ExpectingColon, b':') => {
+                        _ = it.pop();
+                        *state = ObjectParseState::ExpectingValue;
+                    }
+                    (ObjectParseState::ExpectingColon, _) => {
+                        return Err(it.unexpected(&[":"]));
+                    }
+                    (ObjectParseState::ExpectingValue, _) => return self.value(it).map(Some),
+                },
+                Frame::Arr { state, .. } => match (&state, next) {
+                    (
+                        ArrParseState::ExpectingValueOrEndOfArray
+                        | ArrParseState::ExpectingCommaOrEndOfArray,
+                        b']',
+                    ) => return self.close(it, Event::EndArray),
+                    (ArrParseState::ExpectingCommaOrEndOfArray, b',') => {
+                        _ = it.pop();
+                        *state = ArrParseState::ExpectingValue;
+                    }
+                    (ArrParseState::ExpectingCommaOrEndOfArray, _) => {
+                        return Err(it.unexpected(&[",", "]"]));
+                    }
+                    (_, _) => return self.value(it).map(Some),
+                },
+            }
+        }
+    }
+}
+
+/// Where `build` gets its events from.
+pub(crate) trait EventSource<'a> {
+    fn next_event(&mut self) -> Result<Option<Event<'a>>, ParseError>;
+
+    /// Byte offset at which the most recently returned event started.
+    fn offset(&self) -> usize;
+
+    /// Completes `e`, positioned at an absolute offset, with the current path and location,
+    /// and stops the source.
+    fn fail(&mut self, e: ParseError) -> ParseError;
+}
+
+/// A pull parser: hands out the document one `Event` at a time without building a tree.
+/// Open containers are tracked on an explicit stack, so nesting is bounded only by
+/// `ParseOptions::max_depth`.
+///
+/// The iterator yields the events of exactly one top-level value. Content after it other
+/// than whitespace is reported as `ParseError::TrailingCharacters`; after any error the
+/// iterator is exhausted.
+pub struct EventReader<'a> {
+    it: StrIt<'a>,
+    state: ParserState<Cow<'a, str>>,
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::with_options(s, &ParseOptions::default())
+    }
+
+    pub fn with_options(s: &'a str, opts: &ParseOptions) -> Self {
+        Self {
+            it: StrIt::new(s.as_bytes()),
+            state: ParserState::new(opts),
+        }
+    }
+
+    /// Byte offset at which the most recently returned event started.
+    pub fn offset(&self) -> usize {
+        self.state.token_start
+    }
+
+    /// Number of containers currently open.
+    pub fn depth(&self) -> usize {
+        self.state.depth()
+    }
+
+    /// Returns the next event, or `None` once the top-level value is complete.
+    pub fn next_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        if self.state.finished {
+            return Ok(None);
+        }
+        // the whole document is there, so nothing can be cut short by the end of the input
+        self.state.step(&mut self.it).map_err(|e| self.fail(e))
+    }
+
+    /// Attaches the current path and position to `e` and stops the reader.
+    pub(crate) fn fail(&mut self, e: ParseError) -> ParseError {
+        self.state.fail(e).resolve(self.it.s)
+    }
+
+    /// Fails unless only whitespace is left after the top-level value.
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        if self.state.finished {
+            return Ok(());
+        }
+        self.state.finished = true;
+        self.it.skip_whitespace();
+        if self.it.pos < self.it.s.len() {
+            return Err(ParseError::TrailingCharacters {
+                at: Location::at(self.it.pos),
+            }
+            .resolve(self.it.s));
+        }
+        Ok(())
+    }
+
+    /// A reader for the value starting at byte `offset` of `s`. Errors are still positioned
+    /// and resolved against all of `s`.
+    pub(crate) fn starting_at(s: &'a str, offset: usize, opts: &ParseOptions) -> Self {
+        let mut reader = Self::with_options(s, opts);
+        reader.it.pos = offset;
+        reader
+    }
+
+    /// Offset just past the last token read.
+    pub(crate) fn position(&self) -> usize {
+        self.it.pos
+    }
+
+    /// Offset of the first non-whitespace byte after what has been read so far.
+    pub(crate) fn rest_offset(&mut self) -> usize {
+        self.it.skip_whitespace();
+        self.it.pos
+    }
+}
+
+impl<'a> EventSource<'a> for EventReader<'a> {
+    fn next_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        EventReader::next_event(self)
+    }
+
+    fn offset(&self) -> usize {
+        EventReader::offset(self)
+    }
+
+    fn fail(&mut self, e: ParseError) -> ParseError {
+        EventReader::fail(self, e)
+    }
+}
+
+impl<'a> Iterator for EventReader<'a> {
+    type Item = Result<Event<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => self.finish().err().map(Err),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
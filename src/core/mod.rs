@@ -1,2 +1,8 @@
+pub(crate) mod builder;
+pub mod events;
+pub mod options;
 pub mod parser;
-mod strit;
+pub mod push;
+pub mod sequence;
+pub mod stream;
+pub(crate) mod strit;
@@ -0,0 +1,36 @@
+/// What to do when an object contains the same key more than once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with `ParseError::DuplicateKey` pointing at the repeated key.
+    Reject,
+    /// Keep the value that appeared first and ignore the later ones.
+    KeepFirst,
+    /// Let later values overwrite earlier ones.
+    #[default]
+    KeepLast,
+    /// Keep every entry; see `Map::get_all`.
+    CollectAll,
+}
+
+/// Default for `ParseOptions::max_depth`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Knobs accepted by `arjer::parse_with`. `ParseOptions::default()` matches `arjer::parse`.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    pub duplicate_keys: DuplicateKeys,
+    /// How many objects/arrays may be nested inside each other before parsing fails with
+    /// `ParseError::DepthLimitExceeded`. Parsing keeps open containers on the heap, so this
+    /// can be raised freely; note that dropping, cloning or printing an extremely deep
+    /// `JsValue` still recurses.
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            duplicate_keys: DuplicateKeys::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
@@ -1,278 +1,287 @@
+use crate::core::builder::{build, Builder, Owned};
+use crate::core::events::{Event, EventReader};
+use crate::core::options::ParseOptions;
+use crate::core::stream::IoEventReader;
 use crate::core::strit::StrIt;
-use crate::error::ParseError;
+use crate::error::{Location, ParseError};
 use crate::json::{JsValue, Num};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::io::Read;
 
-fn parse_str(i: &mut StrIt) -> Result<String, ParseError> {
+fn parse_hex4(i: &mut StrIt) -> Result<u16, ParseError> {
     let start_pos = i.pos;
-    while let Some(c) = i.pop() {
-        match c {
-            b'"' => break,
+    let mut code: u16 = 0;
+    for _ in 0..4 {
+        let h = i.pop().ok_or_else(|| i.eof())?;
+        let digit = (h as char)
+            .to_digit(16)
+            .ok_or_else(|| ParseError::InvalidEscape {
+                sequence: format!("\\u{}", String::from_utf8_lossy(&i.s[start_pos..i.pos])),
+                at: Location::at(start_pos - 2),
+            })?;
+        code = code * 16 + digit as u16;
+    }
+    Ok(code)
+}
+
+fn parse_unicode_escape(i: &mut StrIt) -> Result<char, ParseError> {
+    let high = parse_hex4(i)?;
+    let escape_pos = i.pos - 6;
+    let lone = |code: u16| ParseError::InvalidEscape {
+        sequence: format!("\\u{:04x}", code),
+        at: Location::at(escape_pos),
+    };
+    match high {
+        0xD800..=0xDBFF => {
+            let rest = &i.s[i.pos..];
+            if rest.len() < 2 && b"\\u".starts_with(rest) {
+                // the input ends before telling whether the low half follows
+                i.pos = i.s.len();
+                return Err(i.eof());
+            }
+            if !i.starts_with(b"\\u") {
+                return Err(lone(high));
+            }
+            i.shift(2);
+            let low = parse_hex4(i)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(lone(high));
+            }
+            let code = 0x10000 + (((high as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00);
+            char::from_u32(code).ok_or_else(|| lone(high))
+        }
+        0xDC00..=0xDFFF => Err(lone(high)),
+        _ => char::from_u32(high as u32).ok_or_else(|| lone(high)),
+    }
+}
+
+pub(crate) fn parse_str<'a>(i: &mut StrIt<'a>) -> Result<Cow<'a, str>, ParseError> {
+    let start_pos = i.pos;
+    let input: &'a [u8] = i.s;
+    // fast path: most strings contain no escapes and can be borrowed as they are
+    let special = input[start_pos..]
+        .iter()
+        .position(|&c| c == b'"' || c == b'\\' || c < 0x20);
+    match special {
+        Some(len) => i.pos += len,
+        None => {
+            i.pos = input.len();
+            return Err(i.eof());
+        }
+    }
+    match input[i.pos] {
+        b'"' => {
+            let raw = &input[start_pos..i.pos];
+            i.pos += 1;
+            // input came from a &str and we only stopped on an ASCII quote
+            return Ok(Cow::Borrowed(unsafe { std::str::from_utf8_unchecked(raw) }));
+        }
+        b'\\' => {}
+        c => {
+            return Err(ParseError::ControlCharacter {
+                value: c,
+                at: Location::at(i.pos),
+            })
+        }
+    }
+    let mut decoded = String::from_utf8_lossy(&i.s[start_pos..i.pos]).into_owned();
+    loop {
+        let chunk_start = i.pos;
+        while let Some(&c) = i.peek() {
+            if c == b'"' || c == b'\\' || c < 0x20 {
+                break;
+            }
+            _ = i.pop();
+        }
+        decoded.push_str(unsafe { std::str::from_utf8_unchecked(&i.s[chunk_start..i.pos]) });
+        match i.pop().ok_or_else(|| i.eof())? {
+            b'"' => return Ok(Cow::Owned(decoded)),
             b'\\' => {
-                _ = i.pop(); // pop next
+                let escaped = match i.pop().ok_or_else(|| i.eof())? {
+                    b'"' => '"',
+                    b'\\' => '\\',
+                    b'/' => '/',
+                    b'b' => '\u{08}',
+                    b'f' => '\u{0c}',
+                    b'n' => '\n',
+                    b'r' => '\r',
+                    b't' => '\t',
+                    b'u' => parse_unicode_escape(i)?,
+                    _ => {
+                        return Err(ParseError::InvalidEscape {
+                            sequence: format!(
+                                "\\{}",
+                                String::from_utf8_lossy(&i.s[i.pos - 1..i.pos])
+                            ),
+                            at: Location::at(i.pos - 2),
+                        })
+                    }
+                };
+                decoded.push(escaped);
+            }
+            c => {
+                return Err(ParseError::ControlCharacter {
+                    value: c,
+                    at: Location::at(i.pos - 1),
+                })
             }
-            _ => {} // just pop the head (which is already done
         }
     }
-    let end_pos = i.pos-1; // don't append ending double quote to the string
-    unsafe {
-        Ok(String::from_utf8_unchecked(i.s[start_pos..end_pos].to_vec()))
+}
+
+fn expect_digit(i: &StrIt) -> Result<(), ParseError> {
+    match i.peek() {
+        Some(d) if d.is_ascii_digit() => Ok(()),
+        Some(_) => Err(i.unexpected(&["digit"])),
+        None => Err(i.eof()),
+    }
+}
+
+fn skip_digits(i: &mut StrIt) {
+    while let Some(d) = i.peek() {
+        if d.is_ascii_digit() {
+            _ = i.pop();
+        } else {
+            break;
+        }
     }
 }
 
-fn parse_value(i: &mut StrIt) -> Result<JsValue, ParseError> {
-    let head = i.peek().ok_or(ParseError::EOF)?;
+// number = [ minus ] int [ frac ] [ exp ], as specified in RFC 8259 section 6
+fn parse_num(i: &mut StrIt) -> Result<Num, ParseError> {
+    let start_pos = i.pos;
+    let mut integer = true;
+    if i.peek() == Some(&b'-') {
+        _ = i.pop();
+    }
+    expect_digit(i)?;
+    if i.pop() == Some(b'0') {
+        if let Some(d) = i.peek().filter(|d| d.is_ascii_digit()) {
+            let mut value = String::from_utf8_lossy(&i.s[start_pos..i.pos]).into_owned();
+            value.push(*d as char);
+            return Err(ParseError::InvalidNumber {
+                tpe: "number".into(),
+                value,
+                at: Location::at(start_pos),
+            });
+        }
+    } else {
+        skip_digits(i);
+    }
+    if i.peek() == Some(&b'.') {
+        _ = i.pop();
+        expect_digit(i)?;
+        skip_digits(i);
+        integer = false;
+    }
+    if let Some(b'e' | b'E') = i.peek() {
+        _ = i.pop();
+        if let Some(b'+' | b'-') = i.peek() {
+            _ = i.pop();
+        }
+        expect_digit(i)?;
+        skip_digits(i);
+        integer = false;
+    }
+    // the grammar above only lets ASCII through
+    let num_str = unsafe { std::str::from_utf8_unchecked(&i.s[start_pos..i.pos]) };
+    match Num::parse(num_str, integer) {
+        Some(num) => Ok(num),
+        None => Err(ParseError::InvalidNumber {
+            tpe: "number".into(),
+            value: num_str.into(),
+            at: Location::at(start_pos),
+        }),
+    }
+}
+
+/// Parses the whole of `s` as one number in the grammar above, for `Num::try_from`.
+pub(crate) fn parse_number(s: &str) -> Result<Num, ParseError> {
+    let mut i = StrIt::new(s.as_bytes());
+    let parsed = parse_num(&mut i).and_then(|num| match i.peek() {
+        None => Ok(num),
+        Some(other) => Err(ParseError::UnexpectedToken {
+            expected: vec![String::from("end of number")],
+            got: String::from(*other as char),
+            at: Location::at(i.pos),
+        }),
+    });
+    parsed.map_err(|e| e.resolve(s.as_bytes()))
+}
+
+/// Reads any value that is not a container, returning it as the matching event.
+pub(crate) fn parse_scalar<'a>(i: &mut StrIt<'a>) -> Result<Event<'a>, ParseError> {
+    let head = i.peek().ok_or_else(|| i.eof())?;
     match head {
         b't' => {
-            if i.starts_with(&[b't', b'r', b'u', b'e']) {
+            if i.starts_with(b"true") {
                 i.shift(4);
-                Ok(JsValue::JsBool(true))
+                Ok(Event::Bool(true))
             } else {
                 Err(ParseError::UnexpectedToken {
                     expected: vec!["true".into()],
                     got: i.peek_n(4),
+                    at: Location::at(i.pos),
                 })
             }
         }
         b'f' => {
-            if i.starts_with(&[b'f', b'a', b'l', b's', b'e']) {
+            if i.starts_with(b"false") {
                 i.shift(5);
-                Ok(JsValue::JsBool(false))
+                Ok(Event::Bool(false))
             } else {
                 Err(ParseError::UnexpectedToken {
                     expected: vec!["false".into()],
                     got: i.peek_n(5),
+                    at: Location::at(i.pos),
                 })
             }
         }
         b'n' => {
-            if i.starts_with(&[b'n', b'u', b'l', b'l']) {
+            if i.starts_with(b"null") {
                 i.shift(4);
-                Ok(JsValue::JsNull)
+                Ok(Event::Null)
             } else {
                 Err(ParseError::UnexpectedToken {
                     expected: vec!["null".into()],
                     got: i.peek_n(4),
+                    at: Location::at(i.pos),
                 })
             }
         }
-        n if n.is_ascii_digit() => {
-            let start_pos = i.pos;
-            _ = i.pop();
-            while let Some(&next_n) = i.peek() {
-                if next_n.is_ascii_digit() || next_n == b'.' {
-                    i.pop();
-                } else {
-                    break;
-                }
-            }
-            let end_pos = i.pos;
-            unsafe {
-                let num_str = String::from_utf8_unchecked(i.s[start_pos..end_pos].to_vec());
-                let num = Num::try_from(num_str)?;
-                Ok(JsValue::JsNumber(num))
-            }
-        }
+        b'-' | b'0'..=b'9' => parse_num(i).map(Event::Number),
         b'"' => {
             _ = i.pop();
-            Ok(JsValue::JsString(parse_str(i)?))
-        }
-        b'{' => parse_obj(i),
-        b'[' => parse_arr(i),
-        b' ' | b'\t' | b'\n' => {
-            _ = i.pop();
-            parse_value(i)
-        }
-        _ => Err(ParseError::UnexpectedToken {
-            expected: vec![],
-            got: String::from(*head as char),
-        }),
-    }
-}
-
-enum ObjectParseState {
-    ExpectingKey,
-    ExpectingKeyOrEndOfObject,
-    ExpectingCommaOrEndOfObject,
-    ExpectingColon,
-    ExpectingValue,
-}
-
-fn parse_obj(i: &mut StrIt) -> Result<JsValue, ParseError> {
-    _ = i.pop(); // pop open curly brace
-    let mut state: ObjectParseState = ObjectParseState::ExpectingKeyOrEndOfObject;
-    let mut key_values: HashMap<String, JsValue> = HashMap::new();
-    let mut latest_key: Option<String> = None;
-
-    loop {
-        let next = i.peek().ok_or(ParseError::EOF)?;
-        match state {
-            ObjectParseState::ExpectingKey => {
-                match next {
-                    b'"' => {
-                        _ = i.pop(); // pop "
-                        latest_key = Some(parse_str(i)?);
-                        state = ObjectParseState::ExpectingColon;
-                    }
-                    b' ' | b'\t' | b'\n' => {
-                        _ = i.pop(); // ignore whitespaces here
-                    }
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: vec![String::from("\"")],
-                            got: String::from(*next as char),
-                        });
-                    }
-                }
-            }
-            ObjectParseState::ExpectingKeyOrEndOfObject => {
-                match next {
-                    b'}' => {
-                        _ = i.pop();
-                        break;
-                    }
-                    b'"' => {
-                        _ = i.pop(); // pop "
-                        latest_key = Some(parse_str(i)?);
-                        state = ObjectParseState::ExpectingColon;
-                    }
-                    b' ' | b'\t' | b'\n' => {
-                        _ = i.pop(); // ignore whitespaces here
-                    }
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: vec![String::from("\""), String::from("}")],
-                            got: String::from(*next as char),
-                        });
-                    }
-                }
-            }
-            ObjectParseState::ExpectingCommaOrEndOfObject => {
-                match next {
-                    b'}' => {
-                        _ = i.pop();
-                        break;
-                    }
-                    b',' => {
-                        _ = i.pop();
-                        state = ObjectParseState::ExpectingKey;
-                    }
-                    b' ' | b'\t' | b'\n' => {
-                        _ = i.pop(); // ignore whitespaces here
-                    }
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: vec![String::from(","), String::from("}")],
-                            got: String::from(*next as char),
-                        });
-                    }
-                }
-            }
-            ObjectParseState::ExpectingColon => {
-                match next {
-                    b':' => {
-                        _ = i.pop();
-                        state = ObjectParseState::ExpectingValue;
-                    }
-                    b' ' | b'\t' | b'\n' => {
-                        _ = i.pop(); // ignore whitespaces here
-                    }
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: vec![String::from(":")],
-                            got: String::from(*next as char),
-                        });
-                    }
-                }
-            }
-            ObjectParseState::ExpectingValue => {
-                match next {
-                    b' ' | b'\t' | b'\n' => {
-                        _ = i.pop(); // ignore whitespaces here
-                    }
-                    _ => {
-                        let value = parse_value(i)?;
-                        match &latest_key {
-                            Some(key) => {
-                                key_values.insert(key.clone(), value);
-                            }
-                            _ => {
-                                return Err(ParseError::InvalidJsonStructure);
-                            }
-                        }
-                        latest_key = None;
-                        state = ObjectParseState::ExpectingCommaOrEndOfObject;
-                    }
-                }
-            }
+            Ok(Event::String(parse_str(i)?))
         }
+        _ => Err(i.unexpected(&[])),
     }
-    Ok(JsValue::JsObject(key_values))
 }
 
-enum ArrParseState {
-    ExpectingValue,
-    ExpectingValueOrEndOfArray,
-    ExpectingCommaOrEndOfArray,
+pub(crate) fn parse_raw<'a, B: Builder<'a>>(
+    s: &'a str,
+    opts: &ParseOptions,
+) -> Result<B::Value, ParseError> {
+    let mut reader = EventReader::with_options(s, opts);
+    let value = build::<B, _>(&mut reader, opts)?;
+    reader.finish()?;
+    Ok(value)
 }
 
-fn parse_arr(i: &mut StrIt) -> Result<JsValue, ParseError> {
-    let mut values: Vec<JsValue> = vec![];
-    let mut state: ArrParseState = ArrParseState::ExpectingValueOrEndOfArray;
-    _ = i.pop(); // pop [
-    loop {
-        let head = i.peek().copied().ok_or(ParseError::EOF)?;
-        match state {
-            ArrParseState::ExpectingValueOrEndOfArray => match head {
-                b']' => {
-                    _ = i.pop();
-                    break;
-                }
-                b' ' | b'\t' | b'\n' => {
-                    _ = i.pop();
-                }
-                _ => {
-                    values.push(parse_value(i)?);
-                    state = ArrParseState::ExpectingCommaOrEndOfArray;
-                }
-            },
-            ArrParseState::ExpectingCommaOrEndOfArray => match head {
-                b']' => {
-                    _ = i.pop();
-                    break;
-                }
-                b' ' | b'\t' | b'\n' => {
-                    _ = i.pop();
-                }
-                b',' => {
-                    _ = i.pop();
-                    state = ArrParseState::ExpectingValue;
-                }
-                _ => {
-                    return Err(ParseError::UnexpectedToken {
-                        expected: vec![String::from(","), String::from("]")],
-                        got: String::from(head as char),
-                    });
-                }
-            },
-            ArrParseState::ExpectingValue => match head {
-                b' ' | b'\t' | b'\n' => {
-                    _ = i.pop();
-                }
-                _ => {
-                    values.push(parse_value(i)?);
-                    state = ArrParseState::ExpectingCommaOrEndOfArray;
-                }
-            },
-        }
-    }
-    Ok(JsValue::JsArray(values))
+/// Parses a single value off the front of `s`, returning it together with the offset of the
+/// first non-whitespace byte following it.
+pub(crate) fn parse_prefix_raw<'a, B: Builder<'a>>(
+    s: &'a str,
+    opts: &ParseOptions,
+) -> Result<(B::Value, usize), ParseError> {
+    let mut reader = EventReader::with_options(s, opts);
+    let value = build::<B, _>(&mut reader, opts)?;
+    Ok((value, reader.rest_offset()))
 }
 
-pub fn parse_raw(s: &str) -> Result<JsValue, ParseError> {
-    let mut it = StrIt {
-        s: s.as_bytes(),
-        pos: 0,
-    };
-    parse_value(&mut it)
+pub(crate) fn parse_reader<R: Read>(src: R, opts: &ParseOptions) -> Result<JsValue, ParseError> {
+    let mut reader = IoEventReader::with_options(src, opts);
+    let value = build::<Owned, _>(&mut reader, opts)?;
+    reader.finish()?;
+    Ok(value)
 }
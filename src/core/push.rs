@@ -0,0 +1,113 @@
+use crate::core::events::{Event, Halt, ParserState};
+use crate::core::options::ParseOptions;
+use crate::core::stream::Window;
+use crate::core::strit::StrIt;
+use crate::error::ParseError;
+
+/// What `PushParser::next_event` came up with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Feed<'a> {
+    Event(Event<'a>),
+    /// Everything fed so far has been used up, possibly in the middle of a token. Call
+    /// `feed` with the next chunk, or `close` if there is none, and ask again.
+    NeedMoreInput,
+    /// The document is complete and nothing but whitespace followed it.
+    Done,
+}
+
+/// A resumable parser for input that arrives in pieces, such as a request body read off the
+/// network: hand it chunks with `feed` as they come in and drain its events with
+/// `next_event` in between. Chunks may be split anywhere, including inside a token or a
+/// UTF-8 sequence; the parser suspends there and picks up once the rest is fed.
+///
+/// Only the part of the input that has not been turned into events yet is kept. Events
+/// borrow from the parser, so convert them with `Event::into_owned` to hold on to them
+/// across calls.
+pub struct PushParser {
+    window: Window,
+    state: ParserState<String>,
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushParser {
+    pub fn new() -> Self {
+        Self::with_options(&ParseOptions::default())
+    }
+
+    pub fn with_options(opts: &ParseOptions) -> Self {
+        Self {
+            window: Window::with_capacity(0),
+            state: ParserState::new(opts),
+        }
+    }
+
+    /// Appends the next chunk of input.
+    ///
+    /// # Panics
+    ///
+    /// If called after `close`.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        assert!(!self.window.complete, "PushParser::feed called after close");
+        self.window.compact(&mut self.state.token_start);
+        self.window.buf.extend_from_slice(chunk);
+        self.window.validate();
+    }
+
+    /// Marks the end of the input. Whatever is still unfinished at that point is an error.
+    pub fn close(&mut self) {
+        self.window.complete = true;
+    }
+
+    /// Byte offset into the whole input at which the most recently returned event started.
+    pub fn offset(&self) -> usize {
+        self.window.stream_offset(self.state.token_start)
+    }
+
+    /// Number of containers currently open.
+    pub fn depth(&self) -> usize {
+        self.state.depth()
+    }
+
+    /// Returns the next event if the input fed so far contains it. After an error the parser
+    /// only ever returns `Feed::Done`.
+    pub fn next_event(&mut self) -> Result<Feed<'_>, ParseError> {
+        if self.state.finished {
+            return Ok(Feed::Done);
+        }
+        if self.state.is_done() {
+            self.state.token_start = self.window.pos;
+            return match self.window.end_of_input() {
+                Ok(true) => {
+                    self.state.finished = true;
+                    Ok(Feed::Done)
+                }
+                Ok(false) => Ok(Feed::NeedMoreInput),
+                Err(e) => {
+                    self.state.finished = true;
+                    Err(e)
+                }
+            };
+        }
+        let mut it = StrIt::new(&self.window.buf[..self.window.valid]);
+        it.pos = self.window.pos;
+        it.partial = self.window.partial();
+        let result = self.state.next(&mut it);
+        self.window.pos = it.pos;
+        let e = match result {
+            Ok(Some(event)) => return Ok(Feed::Event(event)),
+            Ok(None) => return Ok(Feed::Done),
+            Err(Halt::Error(e)) => *e,
+            Err(Halt::NeedMoreInput) => match self.window.stuck() {
+                Some(e) => e,
+                None => return Ok(Feed::NeedMoreInput),
+            },
+        };
+        let e = e.offset_by(self.window.stream_offset(0));
+        Err(self.window.resolve(self.state.fail(e)))
+    }
+}
@@ -0,0 +1,99 @@
+use crate::core::builder::{build, Owned};
+use crate::core::events::EventReader;
+use crate::core::options::ParseOptions;
+use crate::error::{Location, ParseError};
+use crate::json::JsValue;
+
+/// Starts every record of a JSON text sequence (RFC 7464).
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Iterator over the documents in a text that holds several, created by `arjer::parse_many`.
+///
+/// Documents may simply follow each other, separated by whitespace where two would
+/// otherwise run together (`{"a":1}{"b":2} 3 4`), or be records of a JSON text sequence as
+/// defined by RFC 7464, each introduced by the record separator 0x1E. An error in a plain
+/// run of documents ends the iteration, since there is no telling where the next one
+/// starts. A record that fails to parse, including one cut short, is reported and skipped
+/// and the iteration resumes at the next separator.
+pub struct Documents<'a> {
+    s: &'a str,
+    pos: usize,
+    opts: ParseOptions,
+    done: bool,
+}
+
+impl<'a> Documents<'a> {
+    pub(crate) fn new(s: &'a str, opts: &ParseOptions) -> Self {
+        Self {
+            s,
+            pos: 0,
+            opts: opts.clone(),
+            done: false,
+        }
+    }
+
+    /// Parses the record that occupies `start..end`.
+    fn record(&self, start: usize, end: usize) -> Result<JsValue, ParseError> {
+        // a separator is ASCII, so `end` is on a char boundary
+        let text = &self.s[..end];
+        let mut reader = EventReader::starting_at(text, start, &self.opts);
+        let value = build::<Owned, _>(&mut reader, &self.opts)?;
+        // a number running up to the end of its record may have lost digits, so the RFC
+        // requires it to be followed by whitespace
+        if matches!(value, JsValue::JsNumber(_)) && reader.position() == end {
+            return Err(ParseError::EOF {
+                at: Location::at(end),
+            }
+            .resolve(text.as_bytes()));
+        }
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+impl Iterator for Documents<'_> {
+    type Item = Result<JsValue, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.s.as_bytes();
+        while !self.done {
+            while bytes.get(self.pos).is_some_and(|&b| is_whitespace(b)) {
+                self.pos += 1;
+            }
+            match bytes.get(self.pos) {
+                None => return None,
+                Some(&RECORD_SEPARATOR) => {
+                    let start = self.pos + 1;
+                    let end = bytes[start..]
+                        .iter()
+                        .position(|&b| b == RECORD_SEPARATOR)
+                        .map_or(bytes.len(), |len| start + len);
+                    self.pos = end;
+                    // consecutive separators don't delimit empty records
+                    if bytes[start..end].iter().all(|&b| is_whitespace(b)) {
+                        continue;
+                    }
+                    return Some(self.record(start, end));
+                }
+                Some(_) => {
+                    let mut reader = EventReader::starting_at(self.s, self.pos, &self.opts);
+                    return Some(match build::<Owned, _>(&mut reader, &self.opts) {
+                        Ok(value) => {
+                            self.pos = reader.rest_offset();
+                            Ok(value)
+                        }
+                        Err(e) => {
+                            self.done = true;
+                            Err(e)
+                        }
+                    });
+                }
+            }
+        }
+        None
+    }
+}
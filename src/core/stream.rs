@@ -0,0 +1,260 @@
+use crate::core::events::{Event, EventSource, Halt, ParserState};
+use crate::core::options::ParseOptions;
+use crate::core::strit::StrIt;
+use crate::error::{Location, Origin, ParseError};
+use std::io::{self, Read};
+
+/// How many bytes `IoEventReader` keeps in memory unless told otherwise.
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The part of a streamed document that is currently in memory, together with what is
+/// needed to report positions relative to the whole stream.
+pub(crate) struct Window {
+    pub(crate) buf: Vec<u8>,
+    // length of the prefix of `buf` known to be valid UTF-8
+    pub(crate) valid: usize,
+    // whether the bytes at `valid` are invalid no matter what follows them
+    invalid_utf8: bool,
+    pub(crate) pos: usize,
+    // set once nothing more will be appended to `buf`
+    pub(crate) complete: bool,
+    // position of `buf[0]` in the stream
+    origin: Origin,
+}
+
+impl Window {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+            valid: 0,
+            invalid_utf8: false,
+            pos: 0,
+            complete: false,
+            origin: Origin::default(),
+        }
+    }
+
+    /// Whether more of the document may still follow what is buffered.
+    pub(crate) fn partial(&self) -> bool {
+        !self.complete || self.valid < self.buf.len()
+    }
+
+    /// Offset into the stream of `pos` in the buffer.
+    pub(crate) fn stream_offset(&self, pos: usize) -> usize {
+        self.origin.offset + pos
+    }
+
+    /// Drops the bytes before `pos`, which the parser is done with. `token_start` is an
+    /// offset into the buffer and is moved along.
+    pub(crate) fn compact(&mut self, token_start: &mut usize) {
+        self.origin.advance(&self.buf[..self.pos]);
+        self.buf.drain(..self.pos);
+        self.valid -= self.pos;
+        *token_start = token_start.saturating_sub(self.pos);
+        self.pos = 0;
+    }
+
+    /// Checks the bytes added to `buf` since the last call.
+    pub(crate) fn validate(&mut self) {
+        match std::str::from_utf8(&self.buf[self.valid..]) {
+            Ok(_) => self.valid = self.buf.len(),
+            Err(e) => {
+                self.valid += e.valid_up_to();
+                // `error_len` is `None` when the input merely ends inside a character
+                self.invalid_utf8 = e.error_len().is_some();
+            }
+        }
+    }
+
+    /// The error to report when the parser wants more input, if no more can make a
+    /// difference. Its offset is relative to the buffer.
+    pub(crate) fn stuck(&self) -> Option<ParseError> {
+        if self.invalid_utf8 || (self.complete && self.valid < self.buf.len()) {
+            Some(ParseError::InvalidUtf8 {
+                at: Location::at(self.valid),
+            })
+        } else if self.complete {
+            Some(ParseError::EOF {
+                at: Location::at(self.valid),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Skips the whitespace after the top-level value. Returns whether the input is over, or
+    /// fails if something else follows.
+    pub(crate) fn end_of_input(&mut self) -> Result<bool, ParseError> {
+        let mut it = StrIt::new(&self.buf[..self.valid]);
+        it.pos = self.pos;
+        it.skip_whitespace();
+        self.pos = it.pos;
+        if self.pos < self.buf.len() {
+            return Err(self.resolve(ParseError::TrailingCharacters {
+                at: Location::at(self.stream_offset(self.pos)),
+            }));
+        }
+        Ok(self.complete)
+    }
+
+    /// Fills in line, column and excerpt for an error positioned in the stream.
+    pub(crate) fn resolve(&self, e: ParseError) -> ParseError {
+        e.resolve_from(&self.buf, &self.origin)
+    }
+}
+
+/// An `EventReader` over an `io::Read`: the input is pulled through a buffer of fixed size
+/// that is refilled as parsing goes on, so the whole document never has to be in memory.
+/// Tokens split between two reads are put back together before they are parsed; the buffer
+/// only grows past its size when a single token (say, a long string) doesn't fit in it.
+///
+/// Events own their strings, since the buffer they were read from gets reused. Errors carry
+/// offsets, lines and columns counted from the start of the stream, but their excerpt only
+/// covers what was still buffered.
+pub struct IoEventReader<R> {
+    src: R,
+    capacity: usize,
+    window: Window,
+    state: ParserState<String>,
+}
+
+impl<R: Read> IoEventReader<R> {
+    pub fn new(src: R) -> Self {
+        Self::with_options(src, &ParseOptions::default())
+    }
+
+    pub fn with_options(src: R, opts: &ParseOptions) -> Self {
+        Self::with_capacity(src, DEFAULT_BUFFER_SIZE, opts)
+    }
+
+    /// Reads `src` through a buffer of `capacity` bytes.
+    pub fn with_capacity(src: R, capacity: usize, opts: &ParseOptions) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            src,
+            capacity,
+            window: Window::with_capacity(capacity),
+            state: ParserState::new(opts),
+        }
+    }
+
+    /// Byte offset into the stream at which the most recently returned event started.
+    pub fn offset(&self) -> usize {
+        self.window.stream_offset(self.state.token_start)
+    }
+
+    /// Number of containers currently open.
+    pub fn depth(&self) -> usize {
+        self.state.depth()
+    }
+
+    /// Returns the next event, or `None` once the top-level value is complete.
+    pub fn next_event(&mut self) -> Result<Option<Event<'static>>, ParseError> {
+        loop {
+            let mut it = StrIt::new(&self.window.buf[..self.window.valid]);
+            it.pos = self.window.pos;
+            it.partial = self.window.partial();
+            let result = self.state.next(&mut it);
+            self.window.pos = it.pos;
+            match result {
+                Ok(event) => return Ok(event.map(Event::into_owned)),
+                Err(Halt::Error(e)) => return Err(self.fail_in_buffer(*e)),
+                Err(Halt::NeedMoreInput) => {
+                    if let Err(e) = self.refill() {
+                        return Err(self.fail_in_buffer(e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fails unless only whitespace is left in the stream after the top-level value.
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        while !self.state.finished {
+            self.state.token_start = self.window.pos;
+            match self.window.end_of_input() {
+                Ok(true) => self.state.finished = true,
+                Ok(false) => {
+                    if let Err(e) = self.refill() {
+                        return Err(self.fail_in_buffer(e));
+                    }
+                }
+                Err(e) => {
+                    self.state.finished = true;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `fail` for errors whose offset is relative to the buffer.
+    fn fail_in_buffer(&mut self, e: ParseError) -> ParseError {
+        let e = e.offset_by(self.window.stream_offset(0));
+        self.fail(e)
+    }
+
+    fn fail(&mut self, e: ParseError) -> ParseError {
+        self.window.resolve(self.state.fail(e))
+    }
+
+    /// Drops everything before the current position and reads more input behind the rest.
+    fn refill(&mut self) -> Result<(), ParseError> {
+        if let Some(e) = self.window.stuck() {
+            return Err(e);
+        }
+        self.window.compact(&mut self.state.token_start);
+        let buf = &mut self.window.buf;
+        if buf.len() < self.capacity && buf.capacity() > 2 * self.capacity {
+            // the long token that made the buffer grow is gone
+            buf.shrink_to(self.capacity);
+        }
+
+        // a token that takes up most of the buffer needs a bigger one
+        let filled = buf.len();
+        buf.resize(self.capacity.max(2 * filled), 0);
+        let read = loop {
+            match self.src.read(&mut buf[filled..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    buf.truncate(filled);
+                    return Err(ParseError::Io {
+                        error,
+                        at: Location::at(self.window.pos),
+                    });
+                }
+            }
+        };
+        buf.truncate(filled + read);
+        self.window.complete = read == 0;
+        self.window.validate();
+        Ok(())
+    }
+}
+
+impl<R: Read> EventSource<'static> for IoEventReader<R> {
+    fn next_event(&mut self) -> Result<Option<Event<'static>>, ParseError> {
+        IoEventReader::next_event(self)
+    }
+
+    fn offset(&self) -> usize {
+        IoEventReader::offset(self)
+    }
+
+    fn fail(&mut self, e: ParseError) -> ParseError {
+        IoEventReader::fail(self, e)
+    }
+}
+
+impl<R: Read> Iterator for IoEventReader<R> {
+    type Item = Result<Event<'static>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => self.finish().err().map(Err),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -1,9 +1,36 @@
-pub(super) struct StrIt<'a> {
-    pub(super) s: &'a [u8],
-    pub(super) pos: usize,
+use crate::error::{Location, ParseError};
+
+pub(crate) struct StrIt<'a> {
+    pub(crate) s: &'a [u8],
+    pub(crate) pos: usize,
+    /// Set when `s` is only the part of the document that is available so far.
+    pub(crate) partial: bool,
 }
 
 impl<'a> StrIt<'a> {
+    pub fn new(s: &'a [u8]) -> Self {
+        Self {
+            s,
+            pos: 0,
+            partial: false,
+        }
+    }
+
+    /// Whether `e`, raised while reading the token that starts at `start`, only means that
+    /// the token was cut short: it ran out of input, or all that is left of the input is the
+    /// beginning of `true`, `false` or `null`. For a partial input the token then has to be
+    /// read again once more of it is available; anything else is an error whatever follows.
+    pub fn cut_short(&self, start: usize, e: &ParseError) -> bool {
+        if matches!(e, ParseError::EOF { .. }) {
+            return true;
+        }
+        let rest = &self.s[start..];
+        !rest.is_empty()
+            && [&b"true"[..], b"false", b"null"]
+                .iter()
+                .any(|literal| literal.len() > rest.len() && literal.starts_with(rest))
+    }
+
     #[inline(always)]
     pub fn peek(&self) -> Option<&u8> {
         self.s.get(self.pos)
@@ -26,10 +53,38 @@ impl<'a> StrIt<'a> {
     }
 
     pub fn peek_n(&self, n: usize) -> String {
-        let chars = self.s[self.pos..self.pos + n]
-            .iter()
-            .copied()
-            .collect::<Vec<_>>();
-        String::from_utf8(chars).unwrap()
+        let end = (self.pos + n).min(self.s.len());
+        String::from_utf8_lossy(&self.s[self.pos..end]).into_owned()
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// The (possibly multi-byte) character at the current position.
+    pub fn peek_char(&self) -> String {
+        let len = match self.peek() {
+            Some(&b) if b >= 0xF0 => 4,
+            Some(&b) if b >= 0xE0 => 3,
+            Some(&b) if b >= 0xC0 => 2,
+            _ => 1,
+        };
+        self.peek_n(len)
+    }
+
+    pub fn eof(&self) -> ParseError {
+        ParseError::EOF {
+            at: Location::at(self.pos),
+        }
+    }
+
+    pub fn unexpected(&self, expected: &[&str]) -> ParseError {
+        ParseError::UnexpectedToken {
+            expected: expected.iter().map(|e| String::from(*e)).collect(),
+            got: self.peek_char(),
+            at: Location::at(self.pos),
+        }
     }
 }
@@ -0,0 +1,222 @@
+//! Structural comparison of two values: which nodes were added, removed or changed, and
+//! where. Meant for explaining a mismatch to a person, e.g. when a golden-file test fails;
+//! use [`crate::patch::diff`] to get something a program can apply.
+
+mod render;
+
+use crate::error::{render_path, PathSegment};
+use crate::json::{JsValue, Num};
+use crate::patch::diff::{align, Edit};
+use std::cmp::Ordering;
+
+/// How lenient the comparison is.
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    /// Compare arrays element by element in order. Otherwise they are compared as
+    /// multisets: every element just needs an equal counterpart somewhere in the other.
+    pub ordered_arrays: bool,
+    /// Two numbers at least one of which is a `Num::F` count as equal if they are no
+    /// further apart than this. Integers are always compared exactly.
+    pub float_tolerance: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            ordered_arrays: true,
+            float_tolerance: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<'a> {
+    /// Only on the right.
+    Added(&'a JsValue),
+    /// Only on the left.
+    Removed(&'a JsValue),
+    /// On both sides, but with a different value. Objects and arrays are only reported as
+    /// changed when the other side is of a different type; otherwise the differences are
+    /// reported for their members.
+    Changed {
+        left: &'a JsValue,
+        right: &'a JsValue,
+    },
+}
+
+/// One node that differs. Paths of removed nodes index into the left value, all others
+/// into the right one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference<'a> {
+    pub path: Vec<PathSegment>,
+    pub change: Change<'a>,
+}
+
+impl Difference<'_> {
+    /// `path` rendered by [`render_path`].
+    pub fn json_path(&self) -> String {
+        render_path(&self.path)
+    }
+}
+
+/// Every difference between two values, in document order. `Display` renders it as
+/// uncolored text; see [`Report::render`].
+#[derive(Clone, Debug, Default)]
+pub struct Report<'a> {
+    differences: Vec<Difference<'a>>,
+}
+
+impl<'a> Report<'a> {
+    /// Whether the values are equal under the options they were compared with.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    pub fn differences(&self) -> &[Difference<'a>] {
+        &self.differences
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Difference<'a>> {
+        self.differences.iter()
+    }
+}
+
+impl<'a> IntoIterator for Report<'a> {
+    type Item = Difference<'a>;
+    type IntoIter = std::vec::IntoIter<Difference<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.differences.into_iter()
+    }
+}
+
+pub fn diff<'a>(left: &'a JsValue, right: &'a JsValue) -> Report<'a> {
+    diff_with(left, right, &DiffOptions::default())
+}
+
+pub fn diff_with<'a>(left: &'a JsValue, right: &'a JsValue, opts: &DiffOptions) -> Report<'a> {
+    let mut differences = vec![];
+    walk(&mut vec![], left, right, opts, &mut differences);
+    Report { differences }
+}
+
+fn walk<'a>(
+    path: &mut Vec<PathSegment>,
+    left: &'a JsValue,
+    right: &'a JsValue,
+    opts: &DiffOptions,
+    out: &mut Vec<Difference<'a>>,
+) {
+    match (left, right) {
+        (JsValue::JsObject(l), JsValue::JsObject(r)) => {
+            for (k, v) in l.iter() {
+                match r.get(k) {
+                    Some(w) => {
+                        path.push(PathSegment::Key(k.clone()));
+                        walk(path, v, w, opts, out);
+                        path.pop();
+                    }
+                    None => report(out, path, PathSegment::Key(k.clone()), Change::Removed(v)),
+                }
+            }
+            for (k, w) in r.iter().filter(|(k, _)| !l.contains_key(k)) {
+                report(out, path, PathSegment::Key(k.clone()), Change::Added(w));
+            }
+        }
+        (JsValue::JsArray(l), JsValue::JsArray(r)) if opts.ordered_arrays => {
+            let mut edits = align(l, r, |a, b| same(a, b, opts)).into_iter().peekable();
+            while let Some(edit) = edits.next() {
+                match edit {
+                    Edit::Keep => {}
+                    Edit::Delete(i) => match edits.peek() {
+                        Some(Edit::Insert(j)) => {
+                            let j = *j;
+                            edits.next();
+                            path.push(PathSegment::Index(j));
+                            walk(path, &l[i], &r[j], opts, out);
+                            path.pop();
+                        }
+                        _ => report(out, path, PathSegment::Index(i), Change::Removed(&l[i])),
+                    },
+                    Edit::Insert(j) => {
+                        report(out, path, PathSegment::Index(j), Change::Added(&r[j]))
+                    }
+                }
+            }
+        }
+        (JsValue::JsArray(l), JsValue::JsArray(r)) => {
+            let (removed, added) = unmatched(l, r, opts);
+            for i in removed {
+                report(out, path, PathSegment::Index(i), Change::Removed(&l[i]));
+            }
+            for j in added {
+                report(out, path, PathSegment::Index(j), Change::Added(&r[j]));
+            }
+        }
+        _ if same(left, right, opts) => {}
+        _ => out.push(Difference {
+            path: path.clone(),
+            change: Change::Changed { left, right },
+        }),
+    }
+}
+
+fn report<'a>(
+    out: &mut Vec<Difference<'a>>,
+    path: &mut Vec<PathSegment>,
+    segment: PathSegment,
+    change: Change<'a>,
+) {
+    path.push(segment);
+    out.push(Difference {
+        path: path.clone(),
+        change,
+    });
+    path.pop();
+}
+
+/// Equality under `opts`.
+fn same(a: &JsValue, b: &JsValue, opts: &DiffOptions) -> bool {
+    match (a, b) {
+        (JsValue::JsNumber(x), JsValue::JsNumber(y)) => {
+            if matches!(x, Num::F(_)) || matches!(y, Num::F(_)) {
+                (x.as_f64() - y.as_f64()).abs() <= opts.float_tolerance
+            } else {
+                x.compare(y) == Some(Ordering::Equal)
+            }
+        }
+        (JsValue::JsArray(l), JsValue::JsArray(r)) if opts.ordered_arrays => {
+            l.len() == r.len() && l.iter().zip(r).all(|(v, w)| same(v, w, opts))
+        }
+        (JsValue::JsArray(l), JsValue::JsArray(r)) => {
+            let (removed, added) = unmatched(l, r, opts);
+            removed.is_empty() && added.is_empty()
+        }
+        (JsValue::JsObject(l), JsValue::JsObject(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .all(|(k, v)| r.get(k).is_some_and(|w| same(v, w, opts)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Pairs the elements of `l` and `r` up with equal ones on the other side, and returns the
+/// indices of those left over on each side.
+fn unmatched(l: &[JsValue], r: &[JsValue], opts: &DiffOptions) -> (Vec<usize>, Vec<usize>) {
+    let mut taken = vec![false; r.len()];
+    let mut removed = vec![];
+    for (i, v) in l.iter().enumerate() {
+        let counterpart = (0..r.len()).find(|&j| !taken[j] && same(v, &r[j], opts));
+        match counterpart {
+            Some(j) => taken[j] = true,
+            None => removed.push(i),
+        }
+    }
+    let added = (0..r.len()).filter(|&j| !taken[j]).collect();
+    (removed, added)
+}
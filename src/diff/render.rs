@@ -0,0 +1,69 @@
+use crate::diff::{Change, Report};
+use crate::json::JsValue;
+use std::fmt::{self, Display, Formatter, Write};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Writes `text` with `color` if there is one.
+fn paint(out: &mut String, color: Option<&str>, text: &str) {
+    match color {
+        Some(color) => {
+            out.push_str(color);
+            out.push_str(text);
+            out.push_str(RESET);
+        }
+        None => out.push_str(text),
+    }
+    out.push('\n');
+}
+
+/// One `-` or `+` line per line of the pretty-printed `value`.
+fn lines(out: &mut String, sign: char, color: Option<&str>, value: &JsValue) {
+    let mut line = String::new();
+    for text in format!("{:#}", value).lines() {
+        line.clear();
+        line.push(sign);
+        line.push_str(text);
+        paint(out, color, &line);
+    }
+}
+
+impl Report<'_> {
+    /// Renders the report like a unified diff: a `@@ path @@` header per difference,
+    /// followed by the left value on `-` lines and the right one on `+` lines. With `colored`
+    /// set, ANSI escapes highlight it the way `git diff` does in a terminal. Equal values
+    /// render as an empty string.
+    pub fn render(&self, colored: bool) -> String {
+        let color = |code| colored.then_some(code);
+        let mut out = String::new();
+        if self.is_empty() {
+            return out;
+        }
+        paint(&mut out, color(BOLD), "--- left");
+        paint(&mut out, color(BOLD), "+++ right");
+        for difference in self.iter() {
+            let mut header = String::new();
+            _ = write!(header, "@@ {} @@", difference.json_path());
+            paint(&mut out, color(CYAN), &header);
+            match &difference.change {
+                Change::Added(value) => lines(&mut out, '+', color(GREEN), value),
+                Change::Removed(value) => lines(&mut out, '-', color(RED), value),
+                Change::Changed { left, right } => {
+                    lines(&mut out, '-', color(RED), left);
+                    lines(&mut out, '+', color(GREEN), right);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(false))
+    }
+}
@@ -1,7 +1,313 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+/// One step of the JSON path leading to the value an error occurred in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Where in the input a [`ParseError`] happened.
+///
+/// The parser only records `offset` and `path` while it runs; `line`, `column` and
+/// `excerpt` are filled in from the input once the error reaches the public entry point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Location {
+    /// Byte offset into the input.
+    pub offset: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// The offending line (shortened if long) followed by a line holding a caret.
+    pub excerpt: String,
+    /// Path of the value containing the error, outermost first.
+    pub path: Vec<PathSegment>,
+}
+
+const EXCERPT_RADIUS: usize = 32;
+
+/// Where a window of the input starts, for readers that only keep part of the document in
+/// memory and have to resolve errors against that part.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Origin {
+    pub(crate) offset: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Default for Origin {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Origin {
+    /// Moves the origin past `consumed`, the bytes that used to start the window.
+    pub(crate) fn advance(&mut self, consumed: &[u8]) {
+        // counting the bytes that start a UTF-8 sequence counts characters
+        let chars = |bytes: &[u8]| bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+        self.offset += consumed.len();
+        match consumed.iter().rposition(|&b| b == b'\n') {
+            Some(last) => {
+                self.line += consumed.iter().filter(|&&b| b == b'\n').count();
+                self.column = 1 + chars(&consumed[last + 1..]);
+            }
+            None => self.column += chars(consumed),
+        }
+    }
+}
+
+impl Location {
+    pub(crate) fn at(offset: usize) -> Self {
+        Self {
+            offset,
+            ..Self::default()
+        }
+    }
+
+    /// Fills in line, column and excerpt from `input`, the part of the document that begins
+    /// at `origin`.
+    fn resolve(&mut self, input: &[u8], origin: &Origin) {
+        let offset = self.offset.saturating_sub(origin.offset).min(input.len());
+        let line_start = input[..offset]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |p| p + 1);
+        let line_end = input[offset..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+            .map_or(input.len(), |p| offset + p);
+        self.line = origin.line + input[..line_start].iter().filter(|&&b| b == b'\n').count();
+
+        let before = String::from_utf8_lossy(&input[line_start..offset]);
+        let after = String::from_utf8_lossy(&input[offset..line_end]);
+        let line_origin = if line_start == 0 { origin.column } else { 1 };
+        self.column = line_origin + before.chars().count();
+
+        let skipped = before.chars().count().saturating_sub(EXCERPT_RADIUS);
+        let before = before.chars().skip(skipped).collect::<String>();
+        let after = after.chars().take(EXCERPT_RADIUS).collect::<String>();
+        let caret = " ".repeat(before.chars().count());
+        self.excerpt = format!("{}{}\n{}^", before, after, caret);
+    }
+
+    /// `path` rendered by [`render_path`].
+    pub fn json_path(&self) -> String {
+        render_path(&self.path)
+    }
+}
+
+/// Renders a path the way JSONPath does, e.g. `$.hobbies[3]`: keys that are identifiers
+/// follow a dot, other keys are quoted in brackets, and indices are bracketed.
+pub fn render_path(path: &[PathSegment]) -> String {
+    let mut rendered = String::from("$");
+    for segment in path {
+        match segment {
+            PathSegment::Key(k) if is_identifier(k) => {
+                rendered.push('.');
+                rendered.push_str(k);
+            }
+            PathSegment::Key(k) => {
+                rendered.push_str("['");
+                rendered.push_str(&k.replace('\\', "\\\\").replace('\'', "\\'"));
+                rendered.push_str("']");
+            }
+            PathSegment::Index(n) => rendered.push_str(&format!("[{}]", n)),
+        }
+    }
+    rendered
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
 #[derive(Debug)]
 pub enum ParseError {
-    EOF,
-    UnexpectedToken { expected: Vec<String>, got: String },
-    InvalidNumber { tpe: String, value: String },
-    InvalidJsonStructure,
+    EOF {
+        at: Location,
+    },
+    UnexpectedToken {
+        expected: Vec<String>,
+        got: String,
+        at: Location,
+    },
+    InvalidNumber {
+        tpe: String,
+        value: String,
+        at: Location,
+    },
+    InvalidJsonStructure {
+        at: Location,
+    },
+    InvalidEscape {
+        sequence: String,
+        at: Location,
+    },
+    ControlCharacter {
+        value: u8,
+        at: Location,
+    },
+    TrailingCharacters {
+        at: Location,
+    },
+    DuplicateKey {
+        key: String,
+        at: Location,
+    },
+    /// `depth` is the configured `ParseOptions::max_depth` that the input went past.
+    DepthLimitExceeded {
+        depth: usize,
+        at: Location,
+    },
+    /// The input is not valid UTF-8 at `at`.
+    InvalidUtf8 {
+        at: Location,
+    },
+    /// Reading the input failed; `at` is where the parser had got to.
+    Io {
+        error: io::Error,
+        at: Location,
+    },
+}
+
+impl ParseError {
+    pub fn location(&self) -> &Location {
+        match self {
+            ParseError::EOF { at }
+            | ParseError::UnexpectedToken { at, .. }
+            | ParseError::InvalidNumber { at, .. }
+            | ParseError::InvalidJsonStructure { at }
+            | ParseError::InvalidEscape { at, .. }
+            | ParseError::ControlCharacter { at, .. }
+            | ParseError::TrailingCharacters { at }
+            | ParseError::DuplicateKey { at, .. }
+            | ParseError::DepthLimitExceeded { at, .. }
+            | ParseError::InvalidUtf8 { at }
+            | ParseError::Io { at, .. } => at,
+        }
+    }
+
+    fn location_mut(&mut self) -> &mut Location {
+        match self {
+            ParseError::EOF { at }
+            | ParseError::UnexpectedToken { at, .. }
+            | ParseError::InvalidNumber { at, .. }
+            | ParseError::InvalidJsonStructure { at }
+            | ParseError::InvalidEscape { at, .. }
+            | ParseError::ControlCharacter { at, .. }
+            | ParseError::TrailingCharacters { at }
+            | ParseError::DuplicateKey { at, .. }
+            | ParseError::DepthLimitExceeded { at, .. }
+            | ParseError::InvalidUtf8 { at }
+            | ParseError::Io { at, .. } => at,
+        }
+    }
+
+    /// Byte offset into the input at which the error was detected.
+    pub fn offset(&self) -> usize {
+        self.location().offset
+    }
+
+    /// Prefixes the error's path with `path` (outermost first), which leads to the container
+    /// the error was detected in.
+    pub(crate) fn within_path(mut self, mut path: Vec<PathSegment>) -> Self {
+        let at = self.location_mut();
+        path.append(&mut at.path);
+        at.path = path;
+        self
+    }
+
+    pub(crate) fn resolve(self, input: &[u8]) -> Self {
+        self.resolve_from(input, &Origin::default())
+    }
+
+    /// Like `resolve`, for an `input` that is only the part of the document from `origin` on.
+    pub(crate) fn resolve_from(mut self, input: &[u8], origin: &Origin) -> Self {
+        self.location_mut().resolve(input, origin);
+        self
+    }
+
+    /// Moves an error resolved against a single line of some larger input onto `line`, which
+    /// starts at byte `line_offset` of it.
+    pub(crate) fn on_line(mut self, line: usize, line_offset: usize) -> Self {
+        let at = self.location_mut();
+        at.offset += line_offset;
+        at.line += line.saturating_sub(1);
+        self
+    }
+
+    /// Moves an error positioned relative to some window of the input by `base` bytes.
+    pub(crate) fn offset_by(mut self, base: usize) -> Self {
+        self.location_mut().offset += base;
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::EOF { .. } => write!(f, "unexpected end of input")?,
+            ParseError::UnexpectedToken { expected, got, .. } if expected.is_empty() => {
+                write!(f, "unexpected token `{}`", got)?
+            }
+            ParseError::UnexpectedToken { expected, got, .. } => write!(
+                f,
+                "unexpected token `{}`, expected {}",
+                got,
+                expected
+                    .iter()
+                    .map(|e| format!("`{}`", e))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )?,
+            ParseError::InvalidNumber { tpe, value, .. } => {
+                write!(f, "invalid {}: `{}`", tpe, value)?
+            }
+            ParseError::InvalidJsonStructure { .. } => write!(f, "invalid json structure")?,
+            ParseError::InvalidEscape { sequence, .. } => {
+                write!(f, "invalid escape sequence `{}`", sequence)?
+            }
+            ParseError::ControlCharacter { value, .. } => {
+                write!(f, "unescaped control character 0x{:02x} in string", value)?
+            }
+            ParseError::TrailingCharacters { .. } => {
+                write!(f, "trailing characters after the top-level value")?
+            }
+            ParseError::DuplicateKey { key, .. } => write!(f, "duplicate key `{}`", key)?,
+            ParseError::DepthLimitExceeded { depth, .. } => {
+                write!(f, "nesting deeper than {} levels", depth)?
+            }
+            ParseError::InvalidUtf8 { .. } => write!(f, "invalid UTF-8")?,
+            ParseError::Io { error, .. } => write!(f, "failed to read input: {}", error)?,
+        }
+        let at = self.location();
+        if at.line > 0 {
+            write!(f, " at line {}, column {}", at.line, at.column)?;
+        }
+        write!(f, " (byte {}), in {}", at.offset, at.json_path())?;
+        if !at.excerpt.is_empty() {
+            for line in at.excerpt.lines() {
+                write!(f, "\n    {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
 }
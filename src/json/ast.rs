@@ -1,42 +1,118 @@
 use crate::error::ParseError;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::hash::Hash;
 
 #[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
 pub enum Num {
     U32(u32),
     U64(u64),
+    I64(i64),
     F(f64),
 }
 
+impl Num {
+    /// Converts a lexeme that already matched the JSON number grammar. Integers pick the
+    /// narrowest of `U32`, `U64` and (for negatives) `I64`, falling back to `F` when they
+    /// don't fit; anything with a fraction or exponent becomes `F`, and so does `-0`, which
+    /// only a float can keep the sign of. Returns `None` for values that overflow `f64`.
+    pub(crate) fn parse(value: &str, integer: bool) -> Option<Self> {
+        if integer {
+            let parsed = if value.starts_with('-') {
+                value.parse::<i64>().ok().filter(|&i| i != 0).map(Self::I64)
+            } else {
+                value
+                    .parse::<u32>()
+                    .map(Self::U32)
+                    .or_else(|_| value.parse::<u64>().map(Self::U64))
+                    .ok()
+            };
+            if parsed.is_some() {
+                return parsed;
+            }
+        }
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(Self::F)
+    }
+}
+
+impl Num {
+    /// Compares by value regardless of representation, so `U32(1)` equals `F(1.0)`.
+    /// Comparisons are exact, also between an integer and a float: integers are never
+    /// rounded to `f64`, which would blur those above 2^53. `None` only comes up for NaN.
+    pub fn compare(&self, other: &Num) -> Option<Ordering> {
+        match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            (Some(a), None) => compare_with_float(a, other.as_f64()),
+            (None, Some(b)) => compare_with_float(b, self.as_f64()).map(Ordering::reverse),
+            (None, None) => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+
+    pub(crate) fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Num::U32(u) => Some(u as i128),
+            Num::U64(u) => Some(u as i128),
+            Num::I64(i) => Some(i as i128),
+            Num::F(_) => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> f64 {
+        match *self {
+            Num::U32(u) => u as f64,
+            Num::U64(u) => u as f64,
+            Num::I64(i) => i as f64,
+            Num::F(f) => f,
+        }
+    }
+}
+
+/// Compares the integer `i` with `f`. Integer `Num`s lie within ±2^64, where every whole
+/// `f64` converts to `i128` exactly, so the integral parts are compared as integers and the
+/// fraction breaks ties.
+fn compare_with_float(i: i128, f: f64) -> Option<Ordering> {
+    const LIMIT: f64 = 18446744073709551616.0; // 2^64
+    if f.is_nan() {
+        return None;
+    }
+    if f >= LIMIT {
+        return Some(Ordering::Less);
+    }
+    if f <= -LIMIT {
+        return Some(Ordering::Greater);
+    }
+    let whole = f.trunc();
+    let fraction = if f > whole {
+        Ordering::Less
+    } else if f < whole {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    };
+    Some(i.cmp(&(whole as i128)).then(fraction))
+}
+
+/// Equality by value, as `compare` decides it: `U32(1) == F(1.0)`, and NaN equals nothing.
+/// Match on the variant to tell representations apart.
+impl PartialEq for Num {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Parses `value` with the JSON number grammar `arjer::parse` uses, so `"01"`, `"+1"` or
+/// `"1."` are rejected rather than handed to Rust's more lenient number parsing.
 impl TryFrom<String> for Num {
     type Error = ParseError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.contains(".") {
-            value
-                .parse::<f64>()
-                .map(|f| Self::F(f))
-                .map_err(|_| ParseError::InvalidNumber {
-                    tpe: "f64".into(),
-                    value: value.clone(),
-                })
-        } else {
-            value
-                .parse::<u32>()
-                .map(|u| Self::U32(u))
-                .map_err(|_| ParseError::InvalidNumber {
-                    tpe: "u32".into(),
-                    value: value.clone(),
-                })
-                .or(value.parse::<u64>().map(|u| Self::U64(u)).map_err(|_| {
-                    ParseError::InvalidNumber {
-                        tpe: "u64".into(),
-                        value: value.clone(),
-                    }
-                }))
-        }
+        crate::core::parser::parse_number(&value)
     }
 }
 
@@ -47,6 +123,241 @@ pub enum JsValue {
     JsString(String),
     JsNumber(Num),
     JsBool(bool),
-    JsObject(HashMap<String, JsValue>),
+    JsObject(Map),
     JsArray(Vec<JsValue>),
 }
+
+/// Equality in the JSON sense: numbers are equal when their values are, whatever their
+/// representation, and objects when they have the same members in any order. A key that
+/// was collected more than once (`DuplicateKeys::CollectAll`) has to hold the same values
+/// in the same order on both sides.
+impl PartialEq for JsValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (JsValue::JsNull, JsValue::JsNull) => true,
+            (JsValue::JsString(a), JsValue::JsString(b)) => a == b,
+            (JsValue::JsNumber(a), JsValue::JsNumber(b)) => a == b,
+            (JsValue::JsBool(a), JsValue::JsBool(b)) => a == b,
+            (JsValue::JsArray(a), JsValue::JsArray(b)) => a == b,
+            (JsValue::JsObject(a), JsValue::JsObject(b)) => a.same_entries(b),
+            _ => false,
+        }
+    }
+}
+
+/// Objects below this size are searched linearly; larger ones get a hash index.
+const INDEX_THRESHOLD: usize = 8;
+
+/// The object representation behind `JsValue::JsObject`: a map that remembers the order
+/// its keys were inserted in, so that parse -> print round trips are stable. It is generic
+/// so that `JsValueRef` can key it by borrowed strings.
+#[derive(Clone)]
+pub struct Map<K = String, V = JsValue> {
+    entries: Vec<(K, V)>,
+    index: Option<HashMap<K, usize>>,
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: None,
+        }
+    }
+}
+
+impl<K, V> Map<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.entries.iter_mut().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> Map<K, V>
+where
+    K: Borrow<str> + Hash + Eq + Clone,
+{
+    fn position(&self, key: &str) -> Option<usize> {
+        match &self.index {
+            Some(index) => index.get(key).copied(),
+            None => self.entries.iter().position(|(k, _)| k.borrow() == key),
+        }
+    }
+
+    fn reindex(&mut self) {
+        self.index = (self.entries.len() > INDEX_THRESHOLD).then(|| {
+            let mut index = HashMap::with_capacity(self.entries.len());
+            for (pos, (k, _)) in self.entries.iter().enumerate() {
+                index.entry(k.clone()).or_insert(pos);
+            }
+            index
+        });
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.position(key).map(|pos| &self.entries[pos].1)
+    }
+
+    /// Like `get`, but also returns the stored key.
+    pub fn get_key_value(&self, key: &str) -> Option<(&K, &V)> {
+        self.position(key).map(|pos| {
+            let (k, v) = &self.entries[pos];
+            (k, v)
+        })
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.position(key).map(|pos| &mut self.entries[pos].1)
+    }
+
+    /// Inserts `value` under `key`. An existing key keeps its position and has its value
+    /// replaced, which is then returned; a new key is appended at the end.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.position(key.borrow()) {
+            Some(pos) => Some(std::mem::replace(&mut self.entries[pos].1, value)),
+            None => {
+                let pos = self.entries.len();
+                match &mut self.index {
+                    Some(index) => {
+                        index.insert(key.clone(), pos);
+                    }
+                    None if pos == INDEX_THRESHOLD => {
+                        self.entries.push((key, value));
+                        self.reindex();
+                        return None;
+                    }
+                    None => {}
+                }
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Appends an entry even if `key` is already present, as `DuplicateKeys::CollectAll`
+    /// does. Lookups through `get` keep returning the first entry for the key.
+    pub fn append(&mut self, key: K, value: V) {
+        if !self.contains_key(key.borrow()) {
+            self.insert(key, value);
+        } else {
+            self.entries.push((key, value));
+            if self.index.is_none() {
+                self.reindex();
+            }
+        }
+    }
+
+    /// Every value stored under `key`, in input order.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a V> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Removes `key` (every entry of it, if duplicates were collected) while keeping the
+    /// order of the remaining entries, and returns the first value that was stored. The
+    /// index is updated for just the entries that moved.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let pos = self.position(key)?;
+        let (_, value) = self.entries.remove(pos);
+        self.entries.retain(|(k, _)| k.borrow() != key);
+        if let Some(index) = &mut self.index {
+            index.remove(key);
+            // entries only move towards the front, so a recorded position past where the
+            // key is now first found is stale
+            for (p, (k, _)) in self.entries.iter().enumerate().skip(pos) {
+                if let Some(first) = index.get_mut(k.borrow()) {
+                    *first = (*first).min(p);
+                }
+            }
+        }
+        Some(value)
+    }
+
+    /// The object equality behind `JsValue`'s `PartialEq`.
+    pub(crate) fn same_entries(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        if self.len() != other.len() {
+            return false;
+        }
+        // an entry is the first of its key if a lookup of the key lands on it
+        let mut firsts = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(pos, (k, _))| self.position(k.borrow()) == Some(*pos));
+        if firsts.clone().count() == self.len() {
+            return self.iter().all(|(k, v)| other.get(k.borrow()) == Some(v));
+        }
+        firsts.all(|(_, (k, _))| self.get_all(k.borrow()).eq(other.get_all(k.borrow())))
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for Map<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Map<K, V>
+where
+    K: Borrow<str> + Hash + Eq + Clone,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Map::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+impl<K, V> IntoIterator for Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::json::{JsValue, Map, Num};
+use std::borrow::Cow;
+
+/// A parsed document that borrows from its input: strings and object keys point straight
+/// into the source text and are only allocated when escape sequences had to be decoded.
+/// Produced by `arjer::parse_borrowed`; call `into_owned` to detach it from the input.
+#[derive(Clone, Debug)]
+pub enum JsValueRef<'a> {
+    JsNull,
+    JsString(Cow<'a, str>),
+    JsNumber(Num),
+    JsBool(bool),
+    JsObject(Map<Cow<'a, str>, JsValueRef<'a>>),
+    JsArray(Vec<JsValueRef<'a>>),
+}
+
+impl JsValueRef<'_> {
+    pub fn into_owned(self) -> JsValue {
+        match self {
+            JsValueRef::JsNull => JsValue::JsNull,
+            JsValueRef::JsString(s) => JsValue::JsString(s.into_owned()),
+            JsValueRef::JsNumber(n) => JsValue::JsNumber(n),
+            JsValueRef::JsBool(b) => JsValue::JsBool(b),
+            JsValueRef::JsObject(key_values) => {
+                let mut owned = Map::with_capacity(key_values.len());
+                for (k, v) in key_values {
+                    // `append` keeps entries collected under `DuplicateKeys::CollectAll`
+                    owned.append(k.into_owned(), v.into_owned());
+                }
+                JsValue::JsObject(owned)
+            }
+            JsValueRef::JsArray(values) => {
+                JsValue::JsArray(values.into_iter().map(JsValueRef::into_owned).collect())
+            }
+        }
+    }
+}
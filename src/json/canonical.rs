@@ -0,0 +1,160 @@
+use crate::json::ser::write_escaped;
+use crate::json::{JsValue, Num};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+
+/// RFC 8785 requires NaN and the infinities to abort canonicalization rather than be
+/// written in some substitute form.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NonFiniteNumber(pub f64);
+
+impl Display for NonFiniteNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} cannot be represented in canonical JSON", self.0)
+    }
+}
+
+impl std::error::Error for NonFiniteNumber {}
+
+/// Formats `x` the way ECMAScript's `Number.prototype.toString` does (ECMA-262, section
+/// "Number::toString"), which is what RFC 8785 mandates for every number.
+fn write_es_number(out: &mut String, x: f64) -> Result<(), NonFiniteNumber> {
+    if !x.is_finite() {
+        return Err(NonFiniteNumber(x));
+    }
+    if x == 0.0 {
+        // covers -0 as well
+        out.push('0');
+        return Ok(());
+    }
+    if x < 0.0 {
+        out.push('-');
+    }
+    let (digits, exp) = shortest_digits(x.abs());
+    let k = digits.len() as i32;
+    // the value is 0.<digits> * 10^n
+    let n = exp + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        out.push_str(int);
+        out.push('.');
+        out.push_str(frac);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', -n as usize));
+        out.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            out.push('.');
+            out.push_str(rest);
+        }
+        let e = n - 1;
+        out.push('e');
+        out.push(if e < 0 { '-' } else { '+' });
+        out.push_str(&e.abs().to_string());
+    }
+    Ok(())
+}
+
+/// Splits `x` into the shortest digit string that round-trips and the decimal exponent of its
+/// first digit.
+fn shortest_digits(x: f64) -> (String, i32) {
+    fn split(sci: &str) -> (String, i32) {
+        let (mantissa, exp) = sci.split_once('e').unwrap_or((sci, "0"));
+        (mantissa.replace('.', ""), exp.parse().unwrap_or(0))
+    }
+    // `{:e}` yields the shortest round-tripping digits, but resolves an exact tie between two
+    // of them upwards where ECMAScript wants the even one
+    let (digits, exp) = split(&format!("{:e}", x));
+    let k = digits.len();
+    if digits.as_bytes()[k - 1] % 2 == 0 {
+        return (digits, exp);
+    }
+    // every double has a finite decimal expansion of at most 767 significant digits
+    let (exact, exact_exp) = split(&format!("{:.767e}", x));
+    if exact_exp != exp || !exact[k..].starts_with('5') || exact[k + 1..].bytes().any(|b| b != b'0')
+    {
+        return (digits, exp);
+    }
+    // `digits` is odd, so the even neighbour is the truncated expansion or the one above it
+    let mut candidate = exact.as_bytes()[..k].to_vec();
+    if candidate[k - 1] % 2 == 1 {
+        // round the truncated digits up, giving up if that would add a digit
+        let Some(last) = candidate.iter().rposition(|&d| d != b'9') else {
+            return (digits, exp);
+        };
+        candidate[last] += 1;
+        candidate[last + 1..].fill(b'0');
+    }
+    let candidate = String::from_utf8(candidate).unwrap_or_default();
+    match format!("{}e{}", candidate, exp as i64 - (k as i64 - 1)).parse::<f64>() {
+        Ok(parsed) if parsed == x => (candidate, exp),
+        _ => (digits, exp),
+    }
+}
+
+fn cmp_utf16(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+fn write_canonical(out: &mut String, value: &JsValue) -> Result<(), NonFiniteNumber> {
+    match value {
+        JsValue::JsNumber(Num::U32(u)) => write_es_number(out, *u as f64),
+        JsValue::JsNumber(Num::U64(u)) => write_es_number(out, *u as f64),
+        JsValue::JsNumber(Num::I64(i)) => write_es_number(out, *i as f64),
+        JsValue::JsNumber(Num::F(f)) => write_es_number(out, *f),
+        JsValue::JsObject(obj) => {
+            let mut members = obj.iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| cmp_utf16(a, b));
+            out.push('{');
+            for (pos, (k, v)) in members.into_iter().enumerate() {
+                if pos > 0 {
+                    out.push(',');
+                }
+                // writing into a String cannot fail
+                _ = write_escaped(out, k);
+                out.push(':');
+                write_canonical(out, v)?;
+            }
+            out.push('}');
+            Ok(())
+        }
+        JsValue::JsArray(arr) => {
+            out.push('[');
+            for (pos, v) in arr.iter().enumerate() {
+                if pos > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, v)?;
+            }
+            out.push(']');
+            Ok(())
+        }
+        // strings, booleans and null are already minimal in the compact form
+        other => {
+            _ = other.write_compact(out);
+            Ok(())
+        }
+    }
+}
+
+impl JsValue {
+    /// Serializes the value with the JSON Canonicalization Scheme (RFC 8785): no whitespace,
+    /// members sorted by their UTF-16 code units and numbers formatted as ECMAScript does.
+    /// The result is stable byte-for-byte and suitable for hashing and signing.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, NonFiniteNumber> {
+        self.canonical_string().map(String::into_bytes)
+    }
+
+    pub fn canonical_string(&self) -> Result<String, NonFiniteNumber> {
+        let mut out = String::new();
+        write_canonical(&mut out, self)?;
+        Ok(out)
+    }
+}
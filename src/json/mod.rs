@@ -1,4 +1,13 @@
 pub mod ast;
+mod borrowed;
+mod canonical;
+mod pointer;
+pub(crate) mod ser;
 mod syntax;
 
-pub use ast::{JsValue, Num};
+pub use ast::{JsValue, Map, Num};
+pub use borrowed::JsValueRef;
+pub use canonical::NonFiniteNumber;
+pub use pointer::{JsonPointer, PointerError};
+pub use ser::PrettyConfig;
+pub use syntax::{Cursor, CursorError};
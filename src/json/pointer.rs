@@ -0,0 +1,373 @@
+use crate::json::JsValue;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A JSON Pointer (RFC 6901), such as `/job/details/0`: a sequence of reference tokens,
+/// each naming an object member or an array index.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+/// Why a pointer could not be parsed or followed. Every variant that concerns a specific
+/// step carries `pointer`, the given pointer cut after the token that failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PointerError {
+    /// The text is not a pointer: it doesn't start with `/`, or has a `~` that isn't part
+    /// of `~0` or `~1`. `offset` is the byte at which it went wrong.
+    Syntax { pointer: String, offset: usize },
+    /// The object has no member with the token as its key.
+    NoSuchKey { pointer: String },
+    /// The token is an index past the end of the array (`-` names the position right
+    /// after its last element).
+    IndexOutOfBounds {
+        pointer: String,
+        index: usize,
+        len: usize,
+    },
+    /// The token is used on an array but isn't `-` or a number without leading zeros.
+    InvalidIndex { pointer: String },
+    /// The pointer continues past a value that is neither an object nor an array.
+    NotAContainer { pointer: String },
+    /// The operation needs a parent, so it can't apply to the whole document.
+    Root,
+}
+
+impl Display for PointerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PointerError::Syntax { pointer, offset } => {
+                write!(
+                    f,
+                    "`{}` is not a JSON pointer (at byte {})",
+                    pointer, offset
+                )
+            }
+            PointerError::NoSuchKey { pointer } => write!(f, "no value at `{}`", pointer),
+            PointerError::IndexOutOfBounds {
+                pointer,
+                index,
+                len,
+            } => write!(
+                f,
+                "index {} at `{}` is out of bounds for an array of length {}",
+                index, pointer, len
+            ),
+            PointerError::InvalidIndex { pointer } => {
+                write!(f, "`{}` does not end in a valid array index", pointer)
+            }
+            PointerError::NotAContainer { pointer } => write!(
+                f,
+                "the value before the last token of `{}` is neither an object nor an array",
+                pointer
+            ),
+            PointerError::Root => write!(f, "the operation does not apply to the root"),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+/// Where a token leads in an array.
+enum Index {
+    At(usize),
+    // `-`, one past the last element
+    End,
+}
+
+impl JsonPointer {
+    /// The empty pointer, which refers to the whole document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(s: &str) -> Result<Self, PointerError> {
+        if s.is_empty() {
+            return Ok(Self::root());
+        }
+        if !s.starts_with('/') {
+            return Err(PointerError::Syntax {
+                pointer: s.into(),
+                offset: 0,
+            });
+        }
+        let mut tokens = vec![];
+        let mut offset = 1;
+        for raw in s[1..].split('/') {
+            let mut token = String::with_capacity(raw.len());
+            let mut chars = raw.char_indices();
+            while let Some((pos, c)) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some((_, '0')) => token.push('~'),
+                        Some((_, '1')) => token.push('/'),
+                        _ => {
+                            return Err(PointerError::Syntax {
+                                pointer: s.into(),
+                                offset: offset + pos,
+                            })
+                        }
+                    },
+                    c => token.push(c),
+                }
+            }
+            offset += raw.len() + 1;
+            tokens.push(token);
+        }
+        Ok(Self { tokens })
+    }
+
+    /// The unescaped reference tokens, outermost first.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Appends a token; `~` and `/` need no escaping here.
+    pub fn push<S: Into<String>>(&mut self, token: S) {
+        self.tokens.push(token.into());
+    }
+
+    /// Removes the last token and returns it.
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// The pointer to the value containing the one this points to, together with the last
+    /// token. `None` for the root.
+    pub fn split_last(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        Some((
+            JsonPointer {
+                tokens: parent.to_vec(),
+            },
+            last,
+        ))
+    }
+
+    /// Whether this pointer is `other` or points into it.
+    pub fn starts_with(&self, other: &JsonPointer) -> bool {
+        self.tokens.starts_with(&other.tokens)
+    }
+
+    /// The first `n` tokens rendered as a pointer, for errors.
+    fn prefix(&self, n: usize) -> String {
+        JsonPointer {
+            tokens: self.tokens[..n].to_vec(),
+        }
+        .to_string()
+    }
+
+    fn index(&self, n: usize, len: usize) -> Result<Index, PointerError> {
+        let token = &self.tokens[n];
+        if token == "-" {
+            return Ok(Index::End);
+        }
+        let valid = token.bytes().all(|b| b.is_ascii_digit())
+            && !token.is_empty()
+            && (token == "0" || !token.starts_with('0'));
+        match token.parse::<usize>() {
+            Ok(index) if valid && index < len => Ok(Index::At(index)),
+            Ok(index) if valid => Err(PointerError::IndexOutOfBounds {
+                pointer: self.prefix(n + 1),
+                index,
+                len,
+            }),
+            _ => Err(PointerError::InvalidIndex {
+                pointer: self.prefix(n + 1),
+            }),
+        }
+    }
+
+    /// The value one step below `value`, following token `n`.
+    fn step<'v>(&self, value: &'v JsValue, n: usize) -> Result<&'v JsValue, PointerError> {
+        match value {
+            JsValue::JsObject(key_values) => {
+                key_values
+                    .get(&self.tokens[n])
+                    .ok_or_else(|| PointerError::NoSuchKey {
+                        pointer: self.prefix(n + 1),
+                    })
+            }
+            JsValue::JsArray(values) => match self.index(n, values.len())? {
+                Index::At(index) => Ok(&values[index]),
+                Index::End => Err(PointerError::IndexOutOfBounds {
+                    pointer: self.prefix(n + 1),
+                    index: values.len(),
+                    len: values.len(),
+                }),
+            },
+            _ => Err(PointerError::NotAContainer {
+                pointer: self.prefix(n + 1),
+            }),
+        }
+    }
+
+    fn step_mut<'v>(
+        &self,
+        value: &'v mut JsValue,
+        n: usize,
+    ) -> Result<&'v mut JsValue, PointerError> {
+        match value {
+            JsValue::JsObject(key_values) => {
+                key_values
+                    .get_mut(&self.tokens[n])
+                    .ok_or_else(|| PointerError::NoSuchKey {
+                        pointer: self.prefix(n + 1),
+                    })
+            }
+            JsValue::JsArray(values) => match self.index(n, values.len())? {
+                Index::At(index) => Ok(&mut values[index]),
+                Index::End => Err(PointerError::IndexOutOfBounds {
+                    pointer: self.prefix(n + 1),
+                    index: values.len(),
+                    len: values.len(),
+                }),
+            },
+            _ => Err(PointerError::NotAContainer {
+                pointer: self.prefix(n + 1),
+            }),
+        }
+    }
+
+    /// Follows the pointer from `root`.
+    pub fn get<'v>(&self, root: &'v JsValue) -> Result<&'v JsValue, PointerError> {
+        (0..self.tokens.len()).try_fold(root, |value, n| self.step(value, n))
+    }
+
+    pub fn get_mut<'v>(&self, root: &'v mut JsValue) -> Result<&'v mut JsValue, PointerError> {
+        (0..self.tokens.len()).try_fold(root, |value, n| self.step_mut(value, n))
+    }
+
+    /// The container holding the target, and the position of the last token.
+    fn parent_mut<'v>(
+        &self,
+        root: &'v mut JsValue,
+    ) -> Result<(&'v mut JsValue, usize), PointerError> {
+        let last = self.tokens.len().checked_sub(1).ok_or(PointerError::Root)?;
+        let parent = (0..last).try_fold(root, |value, n| self.step_mut(value, n))?;
+        Ok((parent, last))
+    }
+
+    /// Adds `value` at the pointer the way a JSON Patch `add` does: an object member is
+    /// created or replaced, an array element is inserted before the one currently at the
+    /// index (`-` appends), and the root is replaced. Returns the value that was replaced.
+    pub fn insert(
+        &self,
+        root: &mut JsValue,
+        value: JsValue,
+    ) -> Result<Option<JsValue>, PointerError> {
+        if self.is_root() {
+            return Ok(Some(std::mem::replace(root, value)));
+        }
+        let (parent, last) = self.parent_mut(root)?;
+        match parent {
+            JsValue::JsObject(key_values) => {
+                Ok(key_values.insert(self.tokens[last].clone(), value))
+            }
+            JsValue::JsArray(values) => {
+                // inserting right after the last element is allowed
+                let index = match self.index(last, values.len() + 1) {
+                    Ok(Index::At(index)) => index,
+                    Ok(Index::End) => values.len(),
+                    Err(PointerError::IndexOutOfBounds { pointer, index, .. }) => {
+                        return Err(PointerError::IndexOutOfBounds {
+                            pointer,
+                            index,
+                            len: values.len(),
+                        })
+                    }
+                    Err(e) => return Err(e),
+                };
+                values.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(PointerError::NotAContainer {
+                pointer: self.prefix(last + 1),
+            }),
+        }
+    }
+
+    /// Removes the value at the pointer and returns it. Array elements after it shift down.
+    pub fn remove(&self, root: &mut JsValue) -> Result<JsValue, PointerError> {
+        let (parent, last) = self.parent_mut(root)?;
+        match parent {
+            JsValue::JsObject(key_values) => {
+                key_values
+                    .remove(&self.tokens[last])
+                    .ok_or_else(|| PointerError::NoSuchKey {
+                        pointer: self.prefix(last + 1),
+                    })
+            }
+            JsValue::JsArray(values) => match self.index(last, values.len())? {
+                Index::At(index) => Ok(values.remove(index)),
+                Index::End => Err(PointerError::IndexOutOfBounds {
+                    pointer: self.prefix(last + 1),
+                    index: values.len(),
+                    len: values.len(),
+                }),
+            },
+            _ => Err(PointerError::NotAContainer {
+                pointer: self.prefix(last + 1),
+            }),
+        }
+    }
+
+    /// Replaces the value at the pointer, which must exist, and returns the old one.
+    pub fn replace(&self, root: &mut JsValue, value: JsValue) -> Result<JsValue, PointerError> {
+        self.get_mut(root)
+            .map(|target| std::mem::replace(target, value))
+    }
+}
+
+/// Renders the pointer with `~` and `/` in tokens escaped as `~0` and `~1`.
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            f.write_str("/")?;
+            f.write_str(&token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = PointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl JsValue {
+    /// Looks up a value by JSON Pointer, e.g. `value.pointer("/job/details/0")`.
+    pub fn pointer(&self, pointer: &str) -> Result<&JsValue, PointerError> {
+        JsonPointer::parse(pointer)?.get(self)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut JsValue, PointerError> {
+        JsonPointer::parse(pointer)?.get_mut(self)
+    }
+
+    /// See `JsonPointer::insert`.
+    pub fn insert_at(
+        &mut self,
+        pointer: &str,
+        value: JsValue,
+    ) -> Result<Option<JsValue>, PointerError> {
+        JsonPointer::parse(pointer)?.insert(self, value)
+    }
+
+    /// See `JsonPointer::remove`.
+    pub fn remove_at(&mut self, pointer: &str) -> Result<JsValue, PointerError> {
+        JsonPointer::parse(pointer)?.remove(self)
+    }
+
+    /// See `JsonPointer::replace`.
+    pub fn replace_at(&mut self, pointer: &str, value: JsValue) -> Result<JsValue, PointerError> {
+        JsonPointer::parse(pointer)?.replace(self, value)
+    }
+}
@@ -0,0 +1,225 @@
+use crate::json::{JsValue, Num};
+use std::fmt::{self, Display, Formatter, Write};
+use std::io;
+
+/// Layout used by `JsValue::write_pretty` and friends.
+#[derive(Clone, Debug)]
+pub struct PrettyConfig {
+    /// Written once per nesting level in front of every member and element.
+    pub indent: String,
+    /// Emit object members ordered by key instead of insertion order.
+    pub sort_keys: bool,
+    /// End the output with a `\n`.
+    pub trailing_newline: bool,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        Self {
+            indent: String::from("  "),
+            sort_keys: false,
+            trailing_newline: false,
+        }
+    }
+}
+
+pub(crate) fn write_escaped<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    w.write_char('"')?;
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (pos, &b) in bytes.iter().enumerate() {
+        let escaped = match b {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0c => "\\f",
+            0x00..=0x1f => "",
+            _ => continue,
+        };
+        // only ASCII bytes get here, so `pos` is always on a char boundary
+        w.write_str(&s[start..pos])?;
+        if escaped.is_empty() {
+            write!(w, "\\u{:04x}", b)?;
+        } else {
+            w.write_str(escaped)?;
+        }
+        start = pos + 1;
+    }
+    w.write_str(&s[start..])?;
+    w.write_char('"')
+}
+
+pub(crate) fn write_num<W: Write>(w: &mut W, n: &Num) -> fmt::Result {
+    match n {
+        Num::U32(u) => write!(w, "{}", u),
+        Num::U64(u) => write!(w, "{}", u),
+        Num::I64(i) => write!(w, "{}", i),
+        // JSON has no spelling for NaN or the infinities
+        Num::F(f) if !f.is_finite() => w.write_str("null"),
+        // `{:?}` keeps a fractional part (`2.0`) and switches to exponents for very large or
+        // small magnitudes, both of which are valid JSON
+        Num::F(f) => write!(w, "{:?}", f),
+    }
+}
+
+fn write_compact<W: Write>(w: &mut W, value: &JsValue) -> fmt::Result {
+    match value {
+        JsValue::JsNull => w.write_str("null"),
+        JsValue::JsString(s) => write_escaped(w, s),
+        JsValue::JsNumber(n) => write_num(w, n),
+        JsValue::JsBool(b) => w.write_str(if *b { "true" } else { "false" }),
+        JsValue::JsObject(obj) => {
+            w.write_char('{')?;
+            for (pos, (k, v)) in obj.iter().enumerate() {
+                if pos > 0 {
+                    w.write_char(',')?;
+                }
+                write_escaped(w, k)?;
+                w.write_char(':')?;
+                write_compact(w, v)?;
+            }
+            w.write_char('}')
+        }
+        JsValue::JsArray(arr) => {
+            w.write_char('[')?;
+            for (pos, v) in arr.iter().enumerate() {
+                if pos > 0 {
+                    w.write_char(',')?;
+                }
+                write_compact(w, v)?;
+            }
+            w.write_char(']')
+        }
+    }
+}
+
+fn write_indent<W: Write>(w: &mut W, config: &PrettyConfig, level: usize) -> fmt::Result {
+    w.write_char('\n')?;
+    for _ in 0..level {
+        w.write_str(&config.indent)?;
+    }
+    Ok(())
+}
+
+fn write_pretty<W: Write>(
+    w: &mut W,
+    value: &JsValue,
+    config: &PrettyConfig,
+    level: usize,
+) -> fmt::Result {
+    match value {
+        JsValue::JsObject(obj) if !obj.is_empty() => {
+            w.write_char('{')?;
+            let mut write_member = |pos: usize, k: &str, v: &JsValue| {
+                if pos > 0 {
+                    w.write_char(',')?;
+                }
+                write_indent(w, config, level + 1)?;
+                write_escaped(w, k)?;
+                w.write_str(": ")?;
+                write_pretty(w, v, config, level + 1)
+            };
+            if config.sort_keys {
+                let mut members = obj.iter().collect::<Vec<_>>();
+                members.sort_by_key(|(k, _)| *k);
+                for (pos, (k, v)) in members.into_iter().enumerate() {
+                    write_member(pos, k, v)?;
+                }
+            } else {
+                for (pos, (k, v)) in obj.iter().enumerate() {
+                    write_member(pos, k, v)?;
+                }
+            }
+            write_indent(w, config, level)?;
+            w.write_char('}')
+        }
+        JsValue::JsArray(arr) if !arr.is_empty() => {
+            w.write_char('[')?;
+            for (pos, v) in arr.iter().enumerate() {
+                if pos > 0 {
+                    w.write_char(',')?;
+                }
+                write_indent(w, config, level + 1)?;
+                write_pretty(w, v, config, level + 1)?;
+            }
+            write_indent(w, config, level)?;
+            w.write_char(']')
+        }
+        scalar_or_empty => write_compact(w, scalar_or_empty),
+    }
+}
+
+/// Lets the `fmt::Write` based serializer target an `io::Write`, keeping the real error.
+struct IoAdapter<W: io::Write> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+impl<W: io::Write> IoAdapter<W> {
+    fn run<F>(inner: W, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> fmt::Result,
+    {
+        let mut adapter = Self { inner, error: None };
+        match f(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+}
+
+impl JsValue {
+    /// Writes the value as JSON without any insignificant whitespace.
+    pub fn write_compact<W: Write>(&self, w: &mut W) -> fmt::Result {
+        write_compact(w, self)
+    }
+
+    pub fn write_pretty<W: Write>(&self, w: &mut W, config: &PrettyConfig) -> fmt::Result {
+        write_pretty(w, self, config, 0)?;
+        if config.trailing_newline {
+            w.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Compact output to an `io::Write`. Every token is a separate write, so wrap unbuffered
+    /// sinks such as files or sockets in a `BufWriter`.
+    pub fn to_writer<W: io::Write>(&self, w: W) -> io::Result<()> {
+        IoAdapter::run(w, |a| self.write_compact(a))
+    }
+
+    pub fn to_writer_pretty<W: io::Write>(&self, w: W, config: &PrettyConfig) -> io::Result<()> {
+        IoAdapter::run(w, |a| self.write_pretty(a, config))
+    }
+
+    pub fn to_string_pretty(&self, config: &PrettyConfig) -> String {
+        let mut out = String::new();
+        _ = self.write_pretty(&mut out, config);
+        out
+    }
+}
+
+/// Compact JSON; the alternate flag (`{:#}`) switches to the default pretty layout.
+impl Display for JsValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.write_pretty(f, &PrettyConfig::default())
+        } else {
+            self.write_compact(f)
+        }
+    }
+}
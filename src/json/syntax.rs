@@ -1,210 +1,292 @@
-use crate::json::{JsValue, Num};
+use crate::convert::{type_name, FromJson, FromJsonError};
+use crate::error::{render_path, PathSegment};
+use crate::json::{JsValue, Num, PrettyConfig};
+use std::fmt::{self, Display, Formatter};
 
-#[allow(dead_code)]
+/// Walks into a `JsValue` one member or element at a time, then converts what it finds
+/// with `FromJson`:
+///
+/// ```ignore
+/// let title: String = json.cursor().obj("job").field("title")?;
+/// let retries = json.cursor().obj("config").or_default::<u32>("retries")?;
+/// ```
+///
+/// The first step that fails is remembered and every later one skipped, so a chain of calls
+/// reports the step where it went wrong.
 pub struct Cursor<'a> {
-    underlying: Option<&'a JsValue>,
-    error: Option<String>,
+    current: Result<&'a JsValue, CursorError>,
+    path: Vec<PathSegment>,
 }
 
-impl<'a> Cursor<'a> {
-    fn if_matches<P, EF>(
-        opt: Option<&JsValue>,
-        predicate: P,
-        if_doesnt_match: EF,
-        if_empty: String,
-    ) -> (Option<&JsValue>, Option<String>)
-    where
-        EF: FnOnce(&JsValue) -> String,
-        P: FnOnce(&JsValue) -> bool,
-    {
-        match &opt {
-            Some(t) if predicate(t) => (opt, None),
-            Some(t) => (None, Some(if_doesnt_match(t))),
-            _ => (None, Some(if_empty)),
+/// Why a `Cursor` couldn't go on. `path` leads from the value the cursor started at to
+/// the one it failed on, outermost first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CursorError {
+    /// The object at `path` has no member `key`.
+    MissingKey { key: String, path: Vec<PathSegment> },
+    /// The value at `path` isn't what the step asked for.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        path: Vec<PathSegment>,
+    },
+    /// The array at `path` has only `len` elements.
+    IndexOutOfBounds {
+        index: usize,
+        len: usize,
+        path: Vec<PathSegment>,
+    },
+    /// The value was there but didn't convert to the requested type, say a number out of
+    /// range for it. The error's path is the full one, from where the cursor started.
+    Conversion(FromJsonError),
+}
+
+impl CursorError {
+    pub fn path(&self) -> &[PathSegment] {
+        match self {
+            CursorError::MissingKey { path, .. }
+            | CursorError::TypeMismatch { path, .. }
+            | CursorError::IndexOutOfBounds { path, .. } => path,
+            CursorError::Conversion(e) => e.path(),
+        }
+    }
+
+    /// `path` rendered by [`render_path`].
+    pub fn json_path(&self) -> String {
+        render_path(self.path())
+    }
+
+    /// A `from_json` failure of the value at `path`. Type mismatches stay mismatches, with the
+    /// paths joined; anything else is wrapped.
+    fn converting(e: FromJsonError, path: &[PathSegment]) -> Self {
+        let e = path
+            .iter()
+            .rev()
+            .fold(e, |e, segment| e.within(segment.clone()));
+        match e {
+            FromJsonError::TypeMismatch {
+                expected,
+                found,
+                path,
+            } => CursorError::TypeMismatch {
+                expected,
+                found,
+                path,
+            },
+            other => CursorError::Conversion(other),
+        }
+    }
+
+    fn mismatch(expected: &'static str, found: &JsValue, path: &[PathSegment]) -> Self {
+        CursorError::TypeMismatch {
+            expected,
+            found: type_name(found),
+            path: path.to_vec(),
+        }
+    }
+}
+
+impl Display for CursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorError::MissingKey { key, .. } => write!(f, "missing key `{}`", key)?,
+            CursorError::TypeMismatch {
+                expected, found, ..
+            } => write!(f, "expected {}, found {}", expected, found)?,
+            CursorError::IndexOutOfBounds { index, len, .. } => write!(
+                f,
+                "index {} out of bounds for an array of {} elements",
+                index, len
+            )?,
+            CursorError::Conversion(e) => return e.fmt(f),
         }
+        write!(f, " at {}", self.json_path())
     }
+}
 
-    fn if_exists(opt: Option<&JsValue>, if_empty: String) -> (Option<&JsValue>, Option<String>) {
-        match &opt {
-            Some(_) => (opt, None),
-            _ => (None, Some(if_empty)),
+impl std::error::Error for CursorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CursorError::Conversion(e) => Some(e),
+            _ => None,
         }
     }
+}
+
+fn convert<T: FromJson>(value: &JsValue, path: &[PathSegment]) -> Result<T, CursorError> {
+    T::from_json(value).map_err(|e| CursorError::converting(e, path))
+}
 
-    fn map<F>(self, f: F) -> Self
+impl<'a> Cursor<'a> {
+    /// Takes one step from the current value, unless an earlier step already failed.
+    fn then<F>(self, f: F) -> Self
     where
-        F: FnOnce(&'a JsValue) -> (Option<&'a JsValue>, Option<String>),
+        F: FnOnce(&'a JsValue, &mut Vec<PathSegment>) -> Result<&'a JsValue, CursorError>,
     {
-        match self {
-            Self { error: Some(_), .. } => self,
-            Self {
-                underlying: Some(j),
-                ..
-            } => {
-                let (new_j, e) = f(j);
-                Self {
-                    underlying: new_j,
-                    error: e,
+        let Self { current, mut path } = self;
+        let current = current.and_then(|j| f(j, &mut path));
+        Self { current, path }
+    }
+
+    fn member(self, key: String) -> Self {
+        self.then(|j, path| match j {
+            JsValue::JsObject(key_values) => match key_values.get(&key) {
+                Some(value) => {
+                    path.push(PathSegment::Key(key));
+                    Ok(value)
                 }
-            }
-            _ => Self {
-                underlying: None,
-                error: Some("illegal state".into()),
+                None => Err(CursorError::MissingKey {
+                    key,
+                    path: path.clone(),
+                }),
             },
+            other => Err(CursorError::mismatch("object", other, path)),
+        })
+    }
+
+    fn expect(self, expected: &'static str, predicate: fn(&JsValue) -> bool) -> Self {
+        self.then(|j, path| match predicate(j) {
+            true => Ok(j),
+            false => Err(CursorError::mismatch(expected, j, path)),
+        })
+    }
+
+    /// The member `key` of the current object, if it has one, and the path of the object.
+    fn lookup(self, key: &str) -> Result<(Option<&'a JsValue>, Vec<PathSegment>), CursorError> {
+        let Self { current, path } = self;
+        match current? {
+            JsValue::JsObject(key_values) => Ok((key_values.get(key), path)),
+            other => Err(CursorError::mismatch("object", other, &path)),
         }
     }
 
+    /// Reads the member `key` and converts it with `f`, which returns `None` if the member
+    /// isn't an `expected`.
+    fn extract<T, F>(self, key: String, expected: &'static str, f: F) -> Result<T, CursorError>
+    where
+        F: FnOnce(&'a JsValue) -> Option<T>,
+    {
+        let Self { current, path } = self.member(key);
+        let value = current?;
+        f(value).ok_or_else(|| CursorError::mismatch(expected, value, &path))
+    }
+
     pub fn obj<S: Into<String>>(self, path: S) -> Self {
-        let p = path.into();
-        self.map(|j| match j {
-            JsValue::JsObject(key_values) => Self::if_matches(
-                key_values.get(&p),
-                JsValue::is_obj,
-                |inner| format!("{:?} is not an object", inner),
-                format!("no such element: {}", p),
-            ),
-            other => (None, Some(format!("{:?} is not an object", other))),
-        })
+        self.member(path.into()).expect("object", JsValue::is_obj)
     }
 
     pub fn arr(self, path: String) -> Self {
-        self.map(|j| match j {
-            JsValue::JsObject(key_values) => Self::if_matches(
-                key_values.get(&path),
-                JsValue::is_array,
-                |inner| format!("{:?} is not an array", inner),
-                format!("no such element: {}", path),
-            ),
-            other => (None, Some(format!("{:?} is not an object", other))),
-        })
+        self.member(path).expect("array", JsValue::is_array)
     }
 
     pub fn nth(self, n: usize) -> Self {
-        self.map(|j| match j {
-            JsValue::JsArray(elems) => {
-                Self::if_exists(elems.get(n), format!("index {} out of bounds", n))
-            }
-            other => (None, Some(format!("{:?} is not an object", other))),
+        self.then(|j, path| match j {
+            JsValue::JsArray(elems) => match elems.get(n) {
+                Some(elem) => {
+                    path.push(PathSegment::Index(n));
+                    Ok(elem)
+                }
+                None => Err(CursorError::IndexOutOfBounds {
+                    index: n,
+                    len: elems.len(),
+                    path: path.clone(),
+                }),
+            },
+            other => Err(CursorError::mismatch("array", other, path)),
         })
     }
 
-    pub fn string<S: Into<String>>(self, path: S) -> Result<String, String> {
-        let p = path.into();
-        let result = self
-            .map(|j| match j {
-                JsValue::JsObject(key_values) => Self::if_matches(
-                    key_values.get(&p),
-                    JsValue::is_str,
-                    |inner| format!("{:?} is not a string", inner),
-                    format!("no such element: {}", p),
-                ),
-                other => (None, Some(format!("{:?} is not an object", other))),
-            })
-            .get();
-        match result {
-            Ok(JsValue::JsString(s)) => Ok(s.clone()),
-            Ok(other) => Err(format!("{:?} is not a string", other)),
-            Err(e) => Err(e),
+    /// Converts the member `key` of the current object. A missing member is an error unless
+    /// `T` has a value for that, as `Option` does.
+    pub fn field<T: FromJson>(self, key: impl Into<String>) -> Result<T, CursorError> {
+        let key = key.into();
+        let (value, mut path) = self.lookup(&key)?;
+        match value {
+            Some(value) => {
+                path.push(PathSegment::Key(key));
+                convert(value, &path)
+            }
+            None => T::from_missing().ok_or(CursorError::MissingKey { key, path }),
         }
     }
 
-    pub fn boolean<S: Into<String>>(self, path: S) -> Result<bool, String> {
-        let p = path.into();
-        let result = self
-            .map(|j| match j {
-                JsValue::JsObject(key_values) => Self::if_matches(
-                    key_values.get(&p),
-                    JsValue::is_bool,
-                    |inner| format!("{:?} is not a bool", inner),
-                    format!("no such element: {}", p),
-                ),
-                other => (None, Some(format!("{:?} is not an object", other))),
-            })
-            .get();
-        match result {
-            Ok(JsValue::JsBool(b)) => Ok(b.clone()),
-            Ok(other) => Err(format!("{:?} is not a bool", other)),
-            Err(e) => Err(e),
-        }
+    /// Converts the element `index` of the current array.
+    pub fn at<T: FromJson>(self, index: usize) -> Result<T, CursorError> {
+        let Self { current, path } = self.nth(index);
+        convert(current?, &path)
     }
 
-    pub fn num_u32<S: Into<String>>(self, path: S) -> Result<u32, String> {
-        let p = path.into();
-        let result = self
-            .map(|j| match j {
-                JsValue::JsObject(key_values) => Self::if_matches(
-                    key_values.get(&p),
-                    JsValue::is_num_u32,
-                    |inner| format!("{:?} is not a u32", inner),
-                    format!("no such element: {}", p),
-                ),
-                other => (None, Some(format!("{:?} is not an object", other))),
-            })
-            .get();
-        match result {
-            Ok(JsValue::JsNumber(Num::U32(n))) => Ok(n.clone()),
-            Ok(other) => Err(format!("{:?} is not a u32", other)),
-            Err(e) => Err(e),
-        }
+    /// Like `field`, but a member that is missing or `null` gives `Ok(None)`.
+    pub fn opt<T: FromJson>(self, key: impl Into<String>) -> Result<Option<T>, CursorError> {
+        self.field::<Option<T>>(key)
     }
 
-    pub fn num_u64<S: Into<String>>(self, path: S) -> Result<u64, String> {
-        let p = path.into();
-        let result = self
-            .map(|j| match j {
-                JsValue::JsObject(key_values) => Self::if_matches(
-                    key_values.get(&p),
-                    JsValue::is_num_u64,
-                    |inner| format!("{:?} is not a u64", inner),
-                    format!("no such element: {}", p),
-                ),
-                other => (None, Some(format!("{:?} is not an object", other))),
-            })
-            .get();
-        match result {
-            Ok(JsValue::JsNumber(Num::U64(n))) => Ok(n.clone()),
-            Ok(other) => Err(format!("{:?} is not a u64", other)),
-            Err(e) => Err(e),
-        }
+    /// Like `opt`, with `T::default()` in place of `None`.
+    pub fn or_default<T: FromJson + Default>(
+        self,
+        key: impl Into<String>,
+    ) -> Result<T, CursorError> {
+        self.opt(key).map(Option::unwrap_or_default)
     }
 
-    pub fn num_f64<S: Into<String>>(self, path: S) -> Result<f64, String> {
-        let p = path.into();
-        let result = self
-            .map(|j| match j {
-                JsValue::JsObject(key_values) => Self::if_matches(
-                    key_values.get(&p),
-                    JsValue::is_num_f64,
-                    |inner| format!("{:?} is not a f64", inner),
-                    format!("no such element: {}", p),
-                ),
-                other => (None, Some(format!("{:?} is not an object", other))),
-            })
-            .get();
-        match result {
-            Ok(JsValue::JsNumber(Num::F(n))) => Ok(n.clone()),
-            Ok(other) => Err(format!("{:?} is not a f64", other)),
-            Err(e) => Err(e),
-        }
+    /// The member `path`, if it is a string.
+    #[deprecated(note = "use `field::<String>`")]
+    pub fn string<S: Into<String>>(self, path: S) -> Result<String, CursorError> {
+        self.extract(path.into(), "string", |j| match j {
+            JsValue::JsString(s) => Some(s.clone()),
+            _ => None,
+        })
     }
 
-    pub fn get(self) -> Result<&'a JsValue, String> {
-        match self {
-            Self {
-                underlying: Some(j),
-                ..
-            } => Ok(j),
-            Self { error: Some(e), .. } => Err(e),
-            _ => Err("illegal state of cursor".into()),
-        }
+    /// The member `path`, if it is a boolean.
+    #[deprecated(note = "use `field::<bool>`")]
+    pub fn boolean<S: Into<String>>(self, path: S) -> Result<bool, CursorError> {
+        self.extract(path.into(), "boolean", |j| match j {
+            JsValue::JsBool(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    /// The member `path`, if it was parsed as a `Num::U32`. Unlike `field::<u32>`, a number
+    /// stored any other way is a mismatch even if its value would fit.
+    #[deprecated(note = "use `field::<u32>`, which accepts any number that fits")]
+    pub fn num_u32<S: Into<String>>(self, path: S) -> Result<u32, CursorError> {
+        self.extract(path.into(), "u32", |j| match j {
+            JsValue::JsNumber(Num::U32(n)) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// The member `path`, if it was parsed as a `Num::U64`, so only integers above
+    /// `u32::MAX`. `field::<u64>` takes any non-negative integer.
+    #[deprecated(note = "use `field::<u64>`, which accepts any number that fits")]
+    pub fn num_u64<S: Into<String>>(self, path: S) -> Result<u64, CursorError> {
+        self.extract(path.into(), "u64", |j| match j {
+            JsValue::JsNumber(Num::U64(n)) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// The member `path`, if it was parsed as a `Num::F`, so not an integer. `field::<f64>`
+    /// takes integers as well.
+    #[deprecated(note = "use `field::<f64>`, which accepts any number")]
+    pub fn num_f64<S: Into<String>>(self, path: S) -> Result<f64, CursorError> {
+        self.extract(path.into(), "f64", |j| match j {
+            JsValue::JsNumber(Num::F(n)) => Some(*n),
+            _ => None,
+        })
+    }
+
+    pub fn get(self) -> Result<&'a JsValue, CursorError> {
+        self.current
     }
 }
 
 impl JsValue {
-    pub fn cursor(&self) -> Cursor {
+    pub fn cursor(&self) -> Cursor<'_> {
         Cursor {
-            underlying: Some(self),
-            error: None,
+            current: Ok(self),
+            path: vec![],
         }
     }
 
@@ -232,59 +314,22 @@ impl JsValue {
         matches!(self, JsValue::JsNumber(Num::U64(_)))
     }
 
-    pub fn is_num_f64(&self) -> bool {
-        matches!(self, JsValue::JsNumber(Num::F(_)))
+    pub fn is_num_i64(&self) -> bool {
+        matches!(self, JsValue::JsNumber(Num::I64(_)))
     }
 
-    fn indent_impl(&self, s: &str, level: usize) -> String {
-        let space_inner = s.repeat(level);
-        let space_outer = s.repeat(level - 1);
-        match self {
-            JsValue::JsNull => String::from("null"),
-            JsValue::JsString(s) => format!("\"{}\"", s),
-            JsValue::JsNumber(Num::U32(u)) => u.to_string(),
-            JsValue::JsNumber(Num::U64(u)) => u.to_string(),
-            JsValue::JsNumber(Num::F(f)) => f.to_string(),
-            JsValue::JsBool(b) => b.to_string(),
-            JsValue::JsObject(obj) => {
-                if obj.is_empty() {
-                    String::from("{}")
-                } else {
-                    let inner = obj
-                        .iter()
-                        .map(|(k, v)| {
-                            format!(
-                                "\n{}\"{}\": {}",
-                                space_inner,
-                                k,
-                                v.indent_impl(s, level + 1)
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .join(",");
-                    format!("{}{}\n{}{}", "{", inner, space_outer, "}")
-                }
-            }
-            JsValue::JsArray(arr) => {
-                if arr.is_empty() {
-                    String::from("[]")
-                } else {
-                    let inner = arr
-                        .iter()
-                        .map(|v| format!("\n{}{}", space_inner, v.indent_impl(s, level + 1)))
-                        .collect::<Vec<_>>()
-                        .join(",");
-                    format!("[{}\n{}]", inner, space_outer)
-                }
-            }
-        }
+    pub fn is_num_f64(&self) -> bool {
+        matches!(self, JsValue::JsNumber(Num::F(_)))
     }
 
     pub fn pretty_print(&self) -> String {
-        self.indent_impl("  ", 1)
+        self.to_string_pretty(&PrettyConfig::default())
     }
 
     pub fn indent(&self, space: &str) -> String {
-        self.indent_impl(space, 1)
+        self.to_string_pretty(&PrettyConfig {
+            indent: space.into(),
+            ..PrettyConfig::default()
+        })
     }
 }
@@ -0,0 +1,93 @@
+use crate::json::JsValue;
+use crate::jsonpath::functions::Function;
+use regex::Regex;
+
+/// `$` or `@` followed by segments.
+#[derive(Clone, Debug)]
+pub(crate) struct Query {
+    // `@` rather than `$`
+    pub(crate) relative: bool,
+    pub(crate) segments: Vec<Segment>,
+}
+
+impl Query {
+    /// Whether the query can produce at most one node: it only uses name and index
+    /// selectors, one per segment, and no descendant segments.
+    pub(crate) fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors[..],
+                    [Selector::Name(_)] | [Selector::Index(_)]
+                )
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Segment {
+    // `..` rather than `.` or a bare bracket
+    pub(crate) descendant: bool,
+    pub(crate) selectors: Vec<Selector>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Expr),
+}
+
+/// A logical expression, as found in a filter.
+#[derive(Clone, Debug)]
+pub(crate) enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparable, CompareOp, Comparable),
+    // a query as a test: true if it selects anything
+    Exists(Query),
+    // a call to a function of logical type
+    Test(Call),
+}
+
+/// One side of a comparison.
+#[derive(Clone, Debug)]
+pub(crate) enum Comparable {
+    Literal(JsValue),
+    // always singular
+    Query(Query),
+    // always of value type
+    Call(Call),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Call {
+    pub(crate) function: Function,
+    pub(crate) args: Vec<Arg>,
+    // for `match` and `search` with a literal pattern, compiled up front; `None` inside
+    // means the pattern is not a valid I-Regexp
+    pub(crate) regex: Option<Option<Regex>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Arg {
+    Value(Comparable),
+    Nodes(Query),
+}
@@ -0,0 +1,241 @@
+use crate::json::{JsValue, Num};
+use crate::jsonpath::ast::{Arg, Call, Comparable, CompareOp, Expr, Query, Selector};
+use crate::jsonpath::functions::{compile_iregexp, Function};
+use crate::jsonpath::{Match, NormalizedPath, PathStep};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// What a function call evaluates to. A value of `None` is the RFC's "Nothing".
+enum Output<'a> {
+    Value(Option<Cow<'a, JsValue>>),
+    Logical(bool),
+}
+
+/// Runs `query` with `root` as `$` and `current` as `@`, returning the selected nodes in
+/// order. Paths are relative to wherever the query started.
+pub(crate) fn select<'v>(query: &Query, root: &'v JsValue, current: &'v JsValue) -> Vec<Match<'v>> {
+    let start = if query.relative { current } else { root };
+    let mut nodes = vec![Match {
+        path: NormalizedPath::default(),
+        value: start,
+    }];
+    for segment in &query.segments {
+        let mut selected = vec![];
+        for node in &nodes {
+            if segment.descendant {
+                descend(&node.path, node.value, &mut |path, value| {
+                    for selector in &segment.selectors {
+                        apply(selector, root, path, value, &mut selected);
+                    }
+                });
+            } else {
+                for selector in &segment.selectors {
+                    apply(selector, root, &node.path, node.value, &mut selected);
+                }
+            }
+        }
+        nodes = selected;
+    }
+    nodes
+}
+
+/// Visits `value` and everything below it, parents before children and in document order.
+fn descend<'v, F>(path: &NormalizedPath<'v>, value: &'v JsValue, visit: &mut F)
+where
+    F: FnMut(&NormalizedPath<'v>, &'v JsValue),
+{
+    visit(path, value);
+    for (step, child) in children(value) {
+        descend(&path.child(step), child, visit);
+    }
+}
+
+fn children(value: &JsValue) -> Box<dyn Iterator<Item = (PathStep<'_>, &JsValue)> + '_> {
+    match value {
+        JsValue::JsObject(key_values) => Box::new(
+            key_values
+                .iter()
+                .map(|(k, v)| (PathStep::Key(k.as_str()), v)),
+        ),
+        JsValue::JsArray(values) => Box::new(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (PathStep::Index(i), v)),
+        ),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// Appends what `selector` picks out of `value` to `out`.
+fn apply<'v>(
+    selector: &Selector,
+    root: &'v JsValue,
+    path: &NormalizedPath<'v>,
+    value: &'v JsValue,
+    out: &mut Vec<Match<'v>>,
+) {
+    let mut push = |step: PathStep<'v>, value: &'v JsValue| {
+        out.push(Match {
+            path: path.child(step),
+            value,
+        })
+    };
+    match (selector, value) {
+        (Selector::Name(name), JsValue::JsObject(key_values)) => {
+            if let Some((k, v)) = key_values.get_key_value(name) {
+                push(PathStep::Key(k.as_str()), v);
+            }
+        }
+        (Selector::Wildcard, _) => {
+            for (step, child) in children(value) {
+                push(step, child);
+            }
+        }
+        (Selector::Index(index), JsValue::JsArray(values)) => {
+            let len = values.len() as i64;
+            let index = if *index < 0 { len + index } else { *index };
+            if (0..len).contains(&index) {
+                push(PathStep::Index(index as usize), &values[index as usize]);
+            }
+        }
+        (Selector::Slice { start, end, step }, JsValue::JsArray(values)) => {
+            for index in slice(values.len(), *start, *end, step.unwrap_or(1)) {
+                push(PathStep::Index(index), &values[index]);
+            }
+        }
+        (Selector::Filter(expr), _) => {
+            for (step, child) in children(value) {
+                if test(expr, root, child) {
+                    push(step, child);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The indices a slice selects from an array of `len` elements (RFC 9535, section 2.3.4.2).
+fn slice(len: usize, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    let mut indices = vec![];
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = start.map_or(len - 1, normalize).clamp(-1, len - 1);
+        let lower = end.map_or(-1, normalize).clamp(-1, len - 1);
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+fn test(expr: &Expr, root: &JsValue, current: &JsValue) -> bool {
+    match expr {
+        Expr::Or(any) => any.iter().any(|expr| test(expr, root, current)),
+        Expr::And(all) => all.iter().all(|expr| test(expr, root, current)),
+        Expr::Not(expr) => !test(expr, root, current),
+        Expr::Compare(lhs, op, rhs) => {
+            let lhs = evaluate(lhs, root, current);
+            let rhs = evaluate(rhs, root, current);
+            compare(lhs.as_deref(), *op, rhs.as_deref())
+        }
+        Expr::Exists(query) => !select(query, root, current).is_empty(),
+        Expr::Test(call) => matches!(invoke(call, root, current), Output::Logical(true)),
+    }
+}
+
+fn evaluate<'a>(
+    comparable: &'a Comparable,
+    root: &'a JsValue,
+    current: &'a JsValue,
+) -> Option<Cow<'a, JsValue>> {
+    match comparable {
+        Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+        Comparable::Query(query) => select(query, root, current)
+            .first()
+            .map(|node| Cow::Borrowed(node.value)),
+        Comparable::Call(call) => match invoke(call, root, current) {
+            Output::Value(value) => value,
+            Output::Logical(_) => None,
+        },
+    }
+}
+
+fn compare(lhs: Option<&JsValue>, op: CompareOp, rhs: Option<&JsValue>) -> bool {
+    match op {
+        CompareOp::Eq => lhs == rhs,
+        CompareOp::Ne => lhs != rhs,
+        CompareOp::Lt => less(lhs, rhs),
+        CompareOp::Le => less(lhs, rhs) || lhs == rhs,
+        CompareOp::Gt => less(rhs, lhs),
+        CompareOp::Ge => less(rhs, lhs) || lhs == rhs,
+    }
+}
+
+/// Only numbers and strings are ordered; strings by code point, which is the order of their
+/// UTF-8 bytes.
+fn less(lhs: Option<&JsValue>, rhs: Option<&JsValue>) -> bool {
+    match (lhs, rhs) {
+        (Some(JsValue::JsNumber(a)), Some(JsValue::JsNumber(b))) => {
+            a.compare(b) == Some(Ordering::Less)
+        }
+        (Some(JsValue::JsString(a)), Some(JsValue::JsString(b))) => a < b,
+        _ => false,
+    }
+}
+
+fn invoke<'a>(call: &'a Call, root: &'a JsValue, current: &'a JsValue) -> Output<'a> {
+    let value = |n: usize| match &call.args[n] {
+        Arg::Value(comparable) => evaluate(comparable, root, current),
+        Arg::Nodes(_) => None,
+    };
+    let nodes = |n: usize| match &call.args[n] {
+        Arg::Nodes(query) => select(query, root, current),
+        Arg::Value(_) => vec![],
+    };
+    match call.function {
+        Function::Length => Output::Value(value(0).and_then(|value| match &*value {
+            JsValue::JsString(s) => Some(count(s.chars().count())),
+            JsValue::JsArray(values) => Some(count(values.len())),
+            JsValue::JsObject(key_values) => Some(count(key_values.len())),
+            _ => None,
+        })),
+        Function::Count => Output::Value(Some(count(nodes(0).len()))),
+        Function::Match | Function::Search => {
+            let matched = match (value(0).as_deref(), value(1).as_deref()) {
+                (Some(JsValue::JsString(s)), Some(JsValue::JsString(pattern))) => {
+                    match &call.regex {
+                        Some(regex) => regex.as_ref().is_some_and(|regex| regex.is_match(s)),
+                        None => compile_iregexp(pattern, call.function == Function::Match)
+                            .is_some_and(|regex| regex.is_match(s)),
+                    }
+                }
+                _ => false,
+            };
+            Output::Logical(matched)
+        }
+        Function::Value => {
+            let nodes = nodes(0);
+            Output::Value(match nodes[..] {
+                [ref node] => Some(Cow::Borrowed(node.value)),
+                _ => None,
+            })
+        }
+    }
+}
+
+fn count<'a>(n: usize) -> Cow<'a, JsValue> {
+    let n = u32::try_from(n).map_or(Num::U64(n as u64), Num::U32);
+    Cow::Owned(JsValue::JsNumber(n))
+}
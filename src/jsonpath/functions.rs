@@ -0,0 +1,131 @@
+use regex::Regex;
+
+/// The function extensions defined by RFC 9535.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+/// The declared types of function parameters and results (RFC 9535, section 2.4.1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Type {
+    Value,
+    Logical,
+    Nodes,
+}
+
+impl Function {
+    pub(crate) fn named(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Function::Length),
+            "count" => Some(Function::Count),
+            "match" => Some(Function::Match),
+            "search" => Some(Function::Search),
+            "value" => Some(Function::Value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Function::Length => "length",
+            Function::Count => "count",
+            Function::Match => "match",
+            Function::Search => "search",
+            Function::Value => "value",
+        }
+    }
+
+    pub(crate) fn params(self) -> &'static [Type] {
+        match self {
+            Function::Length => &[Type::Value],
+            Function::Count | Function::Value => &[Type::Nodes],
+            Function::Match | Function::Search => &[Type::Value, Type::Value],
+        }
+    }
+
+    pub(crate) fn result(self) -> Type {
+        match self {
+            Function::Length | Function::Count | Function::Value => Type::Value,
+            Function::Match | Function::Search => Type::Logical,
+        }
+    }
+}
+
+/// Compiles an I-Regexp (RFC 9485) by rewriting it into `regex` syntax. `match` needs the
+/// whole string to match, `search` any substring. Returns `None` for patterns that are not
+/// valid I-Regexps, which `match` and `search` treat as matching nothing.
+pub(crate) fn compile_iregexp(pattern: &str, whole: bool) -> Option<Regex> {
+    let mut translated = String::with_capacity(pattern.len() + 8);
+    translated.push_str(if whole { r"\A(?:" } else { "(?:" });
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next()?;
+                match escaped {
+                    '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{'
+                    | '|' | '}' | 'n' | 'r' | 't' => {
+                        translated.push('\\');
+                        translated.push(escaped);
+                    }
+                    'p' | 'P' => {
+                        translated.push('\\');
+                        translated.push(escaped);
+                        if chars.next()? != '{' {
+                            return None;
+                        }
+                        translated.push('{');
+                        loop {
+                            let c = chars.next()?;
+                            if !(c.is_ascii_alphanumeric() || c == '}') {
+                                return None;
+                            }
+                            translated.push(c);
+                            if c == '}' {
+                                break;
+                            }
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+            '[' if in_class => return None,
+            '[' => {
+                in_class = true;
+                translated.push('[');
+                if chars.peek() == Some(&'^') {
+                    translated.push(chars.next()?);
+                }
+                // an empty class, or `]` as its first member, isn't allowed
+                if chars.peek() == Some(&']') {
+                    return None;
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                translated.push(']');
+            }
+            // `regex` reads these as set operations when doubled inside a class
+            '&' | '~' if in_class => {
+                translated.push('\\');
+                translated.push(c);
+            }
+            // I-Regexp's dot excludes only the line terminators
+            '.' if !in_class => translated.push_str(r"[^\n\r]"),
+            '^' | '$' if !in_class => return None,
+            '(' if !in_class && chars.peek() == Some(&'?') => return None,
+            c => translated.push(c),
+        }
+    }
+    if in_class {
+        return None;
+    }
+    translated.push_str(if whole { r")\z" } else { ")" });
+    Regex::new(&translated).ok()
+}
@@ -0,0 +1,184 @@
+//! JSONPath (RFC 9535): queries such as `$.hobbies[?@.title]` or `$..info` that select
+//! nodes out of a document. A query is compiled once with [`JsonPath::compile`] and can then
+//! be run against any number of values.
+
+mod ast;
+mod eval;
+mod functions;
+mod parser;
+
+use crate::json::{JsValue, JsonPointer};
+use std::fmt::{self, Display, Formatter, Write};
+use std::str::FromStr;
+
+/// A compiled JSONPath query.
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    source: String,
+    query: ast::Query,
+}
+
+/// Why a query didn't compile. Offsets are byte offsets into the query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonPathError {
+    /// The query doesn't follow the grammar.
+    Syntax {
+        offset: usize,
+        expected: &'static str,
+    },
+    /// An index or slice bound is outside the I-JSON range of ±(2^53 - 1).
+    IntegerOutOfRange {
+        offset: usize,
+    },
+    UnknownFunction {
+        offset: usize,
+        name: String,
+    },
+    /// The query is well-formed but not well-typed (RFC 9535, section 2.4.3), e.g. a
+    /// comparison against a query that can select more than one node.
+    Type {
+        offset: usize,
+        reason: String,
+    },
+}
+
+impl Display for JsonPathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonPathError::Syntax { offset, expected } => {
+                write!(f, "expected {} at byte {}", expected, offset)
+            }
+            JsonPathError::IntegerOutOfRange { offset } => {
+                write!(f, "integer at byte {} is out of range", offset)
+            }
+            JsonPathError::UnknownFunction { offset, name } => {
+                write!(f, "unknown function `{}` at byte {}", name, offset)
+            }
+            JsonPathError::Type { offset, reason } => write!(f, "{} (at byte {})", reason, offset),
+        }
+    }
+}
+
+impl std::error::Error for JsonPathError {}
+
+/// One step of a [`NormalizedPath`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathStep<'v> {
+    Key(&'v str),
+    Index(usize),
+}
+
+/// The unique path to a node, rendered the way RFC 9535 normalizes it: `$['a'][0]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NormalizedPath<'v> {
+    steps: Vec<PathStep<'v>>,
+}
+
+impl<'v> NormalizedPath<'v> {
+    pub fn steps(&self) -> &[PathStep<'v>] {
+        &self.steps
+    }
+
+    /// The same location as a JSON Pointer, e.g. for `JsValue::pointer_mut`.
+    pub fn to_pointer(&self) -> JsonPointer {
+        let mut pointer = JsonPointer::root();
+        for step in &self.steps {
+            match step {
+                PathStep::Key(k) => pointer.push(*k),
+                PathStep::Index(i) => pointer.push(i.to_string()),
+            }
+        }
+        pointer
+    }
+
+    fn child(&self, step: PathStep<'v>) -> Self {
+        let mut steps = Vec::with_capacity(self.steps.len() + 1);
+        steps.extend_from_slice(&self.steps);
+        steps.push(step);
+        Self { steps }
+    }
+}
+
+impl Display for NormalizedPath<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('$')?;
+        for step in &self.steps {
+            match step {
+                PathStep::Index(i) => write!(f, "[{}]", i)?,
+                PathStep::Key(k) => {
+                    f.write_str("['")?;
+                    for c in k.chars() {
+                        match c {
+                            '\u{8}' => f.write_str("\\b")?,
+                            '\u{c}' => f.write_str("\\f")?,
+                            '\n' => f.write_str("\\n")?,
+                            '\r' => f.write_str("\\r")?,
+                            '\t' => f.write_str("\\t")?,
+                            '\'' => f.write_str("\\'")?,
+                            '\\' => f.write_str("\\\\")?,
+                            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                            c => f.write_char(c)?,
+                        }
+                    }
+                    f.write_str("']")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A node selected by a query: a value in the document together with where it is.
+#[derive(Clone, Debug)]
+pub struct Match<'v> {
+    pub path: NormalizedPath<'v>,
+    pub value: &'v JsValue,
+}
+
+impl JsonPath {
+    pub fn compile(query: &str) -> Result<Self, JsonPathError> {
+        Ok(Self {
+            source: query.to_string(),
+            query: parser::parse_query(query)?,
+        })
+    }
+
+    /// The nodes the query selects from `value`, in the order the RFC prescribes. The same
+    /// node may come up more than once, e.g. for `$[0, 0]`.
+    pub fn query<'v>(&self, value: &'v JsValue) -> Vec<Match<'v>> {
+        eval::select(&self.query, value, value)
+    }
+
+    /// Just the values of the selected nodes.
+    pub fn values<'v>(&self, value: &'v JsValue) -> Vec<&'v JsValue> {
+        self.query(value)
+            .into_iter()
+            .map(|node| node.value)
+            .collect()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = JsonPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::compile(s)
+    }
+}
+
+impl JsValue {
+    /// Compiles `query` and runs it against this value; see [`JsonPath`] to reuse a query.
+    pub fn query(&self, query: &str) -> Result<Vec<Match<'_>>, JsonPathError> {
+        Ok(JsonPath::compile(query)?.query(self))
+    }
+}
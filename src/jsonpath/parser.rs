@@ -0,0 +1,561 @@
+use crate::json::{JsValue, Num};
+use crate::jsonpath::ast::{Arg, Call, Comparable, CompareOp, Expr, Query, Segment, Selector};
+use crate::jsonpath::functions::{compile_iregexp, Function, Type};
+use crate::jsonpath::JsonPathError;
+
+/// Indices and slice bounds have to be I-JSON integers.
+const MAX_INT: i64 = (1 << 53) - 1;
+
+/// What a filter operand turned out to be, before it is known whether it is compared,
+/// tested, or passed to a function.
+enum Operand {
+    Literal(JsValue),
+    Query(Query),
+    Call(Call),
+    Logical(Expr),
+}
+
+pub(crate) fn parse_query(s: &str) -> Result<Query, JsonPathError> {
+    let mut p = Parser { s, pos: 0 };
+    if !p.eat("$") {
+        return Err(p.expected("`$`"));
+    }
+    let query = p.query(false)?;
+    if p.pos < s.len() {
+        return Err(p.expected("a segment"));
+    }
+    Ok(query)
+}
+
+struct Parser<'q> {
+    s: &'q str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.s[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str, what: &'static str) -> Result<(), JsonPathError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.expected(what))
+        }
+    }
+
+    fn expected(&self, what: &'static str) -> JsonPathError {
+        JsonPathError::Syntax {
+            offset: self.pos,
+            expected: what,
+        }
+    }
+
+    fn type_error(offset: usize, reason: String) -> JsonPathError {
+        JsonPathError::Type { offset, reason }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.s[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    /// The segments after `$` or `@`.
+    fn query(&mut self, relative: bool) -> Result<Query, JsonPathError> {
+        let mut segments = vec![];
+        loop {
+            // whitespace only belongs to the query if a segment follows it
+            let before = self.pos;
+            self.skip_whitespace();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.segment()?),
+                _ => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+        Ok(Query { relative, segments })
+    }
+
+    fn segment(&mut self) -> Result<Segment, JsonPathError> {
+        let descendant = self.eat("..");
+        if !descendant && self.eat("[") {
+            return self.bracketed(false);
+        }
+        if !descendant {
+            self.expect(".", "`.` or `[`")?;
+        }
+        let selector = if self.eat("*") {
+            Selector::Wildcard
+        } else if descendant && self.eat("[") {
+            return self.bracketed(true);
+        } else {
+            Selector::Name(self.member_name()?)
+        };
+        Ok(Segment {
+            descendant,
+            selectors: vec![selector],
+        })
+    }
+
+    /// The shorthand after `.`, as in `$.name`.
+    fn member_name(&mut self) -> Result<String, JsonPathError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || !c.is_ascii() => {}
+            _ => return Err(self.expected("a member name or `*`")),
+        }
+        let rest = &self.s[start..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    /// The selectors of a segment, after its `[`.
+    fn bracketed(&mut self, descendant: bool) -> Result<Segment, JsonPathError> {
+        let mut selectors = vec![];
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(Segment {
+                    descendant,
+                    selectors,
+                });
+            }
+            self.expect(",", "`,` or `]`")?;
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(self.logical()?))
+            }
+            _ => {
+                let start = self.int()?;
+                self.skip_whitespace();
+                if !self.eat(":") {
+                    return match start {
+                        Some(index) => Ok(Selector::Index(index)),
+                        None => Err(self.expected("a selector")),
+                    };
+                }
+                self.skip_whitespace();
+                let end = self.int()?;
+                self.skip_whitespace();
+                let mut step = None;
+                if self.eat(":") {
+                    self.skip_whitespace();
+                    step = self.int()?;
+                }
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    /// An index or slice bound, if there is one.
+    fn int(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let start = self.pos;
+        let negative = self.eat("-");
+        let digits = self.s[self.pos..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.s.len() - self.pos);
+        if digits == 0 {
+            self.pos = start;
+            return if negative {
+                Err(self.expected("an integer"))
+            } else {
+                Ok(None)
+            };
+        }
+        let lexeme = &self.s[start..self.pos + digits];
+        let leading_zero = self.s[self.pos..].starts_with('0') && (digits > 1 || negative);
+        if leading_zero {
+            return Err(self.expected("an integer without leading zeros"));
+        }
+        self.pos += digits;
+        match lexeme.parse::<i64>() {
+            Ok(int) if (-MAX_INT..=MAX_INT).contains(&int) => Ok(Some(int)),
+            _ => Err(JsonPathError::IntegerOutOfRange { offset: start }),
+        }
+    }
+
+    /// A string literal in single or double quotes.
+    fn string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.peek().unwrap_or('"');
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.expected("a closing quote"))?;
+            match c {
+                c if c == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    out.push(self.escape(quote)?);
+                }
+                '\u{0}'..='\u{1f}' => return Err(self.expected("an escape sequence")),
+                c => {
+                    self.pos += c.len_utf8();
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    /// The character escaped after a `\` in a string in `quote`s.
+    fn escape(&mut self, quote: char) -> Result<char, JsonPathError> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.expected("an escape sequence"))?;
+        self.pos += c.len_utf8();
+        match c {
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            '/' | '\\' => Ok(c),
+            c if c == quote => Ok(c),
+            'u' => {
+                let high = self.hex4()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.expected("a surrogate pair"));
+                }
+                if !self.eat("\\u") {
+                    return Err(self.expected("a low surrogate"));
+                }
+                let low = self.hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.expected("a low surrogate"));
+                }
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                char::from_u32(code).ok_or_else(|| self.expected("a surrogate pair"))
+            }
+            _ => {
+                self.pos -= c.len_utf8();
+                Err(self.expected("an escape sequence"))
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonPathError> {
+        let digits = self
+            .s
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.expected("four hex digits"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap_or_default())
+    }
+
+    /// `a || b`, the loosest-binding logical expression.
+    fn logical(&mut self) -> Result<Expr, JsonPathError> {
+        let mut any = vec![self.conjunction()?];
+        loop {
+            let before = self.pos;
+            self.skip_whitespace();
+            if !self.eat("||") {
+                self.pos = before;
+                break;
+            }
+            self.skip_whitespace();
+            any.push(self.conjunction()?);
+        }
+        Ok(if any.len() == 1 {
+            any.remove(0)
+        } else {
+            Expr::Or(any)
+        })
+    }
+
+    fn conjunction(&mut self) -> Result<Expr, JsonPathError> {
+        let mut all = vec![self.basic()?];
+        loop {
+            let before = self.pos;
+            self.skip_whitespace();
+            if !self.eat("&&") {
+                self.pos = before;
+                break;
+            }
+            self.skip_whitespace();
+            all.push(self.basic()?);
+        }
+        Ok(if all.len() == 1 {
+            all.remove(0)
+        } else {
+            Expr::And(all)
+        })
+    }
+
+    /// A comparison, a test, or a parenthesized expression.
+    fn basic(&mut self) -> Result<Expr, JsonPathError> {
+        let start = self.pos;
+        let lhs = self.operand()?;
+        let before = self.pos;
+        self.skip_whitespace();
+        let Some(op) = self.compare_op() else {
+            self.pos = before;
+            return match lhs {
+                Operand::Query(query) => Ok(Expr::Exists(query)),
+                Operand::Call(call) => Self::test(call, start),
+                Operand::Logical(expr) => Ok(expr),
+                Operand::Literal(_) => Err(self.expected("a comparison operator")),
+            };
+        };
+        self.skip_whitespace();
+        let rhs_start = self.pos;
+        let rhs = self.operand()?;
+        Ok(Expr::Compare(
+            Self::comparable(lhs, start)?,
+            op,
+            Self::comparable(rhs, rhs_start)?,
+        ))
+    }
+
+    fn compare_op(&mut self) -> Option<CompareOp> {
+        let ops = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ];
+        ops.into_iter()
+            .find(|(token, _)| self.eat(token))
+            .map(|(_, op)| op)
+    }
+
+    /// A function call used as a test, which needs a logical or nodes result.
+    fn test(call: Call, offset: usize) -> Result<Expr, JsonPathError> {
+        match call.function.result() {
+            Type::Logical | Type::Nodes => Ok(Expr::Test(call)),
+            Type::Value => Err(Self::type_error(
+                offset,
+                format!(
+                    "`{}` returns a value, which has to be compared rather than tested",
+                    call.function.name()
+                ),
+            )),
+        }
+    }
+
+    fn comparable(operand: Operand, offset: usize) -> Result<Comparable, JsonPathError> {
+        match operand {
+            Operand::Literal(value) => Ok(Comparable::Literal(value)),
+            Operand::Query(query) if query.is_singular() => Ok(Comparable::Query(query)),
+            Operand::Query(_) => Err(Self::type_error(
+                offset,
+                "only queries selecting at most one node can be compared".into(),
+            )),
+            Operand::Call(call) if call.function.result() == Type::Value => {
+                Ok(Comparable::Call(call))
+            }
+            Operand::Call(call) => Err(Self::type_error(
+                offset,
+                format!(
+                    "`{}` returns a logical result, which can't be compared",
+                    call.function.name()
+                ),
+            )),
+            Operand::Logical(_) => Err(Self::type_error(
+                offset,
+                "a logical expression can't be compared".into(),
+            )),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, JsonPathError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('!') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                let negated = match self.peek() {
+                    Some('(' | '@' | '$' | 'a'..='z') => self.operand()?,
+                    _ => return Err(self.expected("`(`, a query or a function call")),
+                };
+                let expr = match negated {
+                    Operand::Query(query) => Expr::Exists(query),
+                    Operand::Call(call) => Self::test(call, start)?,
+                    Operand::Logical(expr) => expr,
+                    Operand::Literal(_) => {
+                        return Err(self.expected("`(`, a query or a function call"))
+                    }
+                };
+                Ok(Operand::Logical(Expr::Not(Box::new(expr))))
+            }
+            Some('(') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                let expr = self.logical()?;
+                self.skip_whitespace();
+                self.expect(")", "`)`")?;
+                Ok(Operand::Logical(expr))
+            }
+            Some(c @ ('@' | '$')) => {
+                self.pos += 1;
+                Ok(Operand::Query(self.query(c == '@')?))
+            }
+            Some('\'' | '"') => Ok(Operand::Literal(JsValue::JsString(self.string()?))),
+            Some('-' | '0'..='9') => self.number().map(Operand::Literal),
+            Some('a'..='z') => {
+                let rest = &self.s[start..];
+                let len = rest
+                    .find(|c: char| !matches!(c, 'a'..='z' | '0'..='9' | '_'))
+                    .unwrap_or(rest.len());
+                self.pos += len;
+                let name = &rest[..len];
+                if self.peek() == Some('(') {
+                    return self.call(name, start).map(Operand::Call);
+                }
+                match name {
+                    "true" => Ok(Operand::Literal(JsValue::JsBool(true))),
+                    "false" => Ok(Operand::Literal(JsValue::JsBool(false))),
+                    "null" => Ok(Operand::Literal(JsValue::JsNull)),
+                    _ => {
+                        self.pos = start;
+                        Err(self.expected("a literal, a query or a function call"))
+                    }
+                }
+            }
+            _ => Err(self.expected("a literal, a query or a function call")),
+        }
+    }
+
+    /// A number literal, which unlike JSON also allows `-0` with a fraction or exponent.
+    fn number(&mut self) -> Result<JsValue, JsonPathError> {
+        let start = self.pos;
+        self.eat("-");
+        let digits = |p: &Self| {
+            p.s[p.pos..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(p.s.len() - p.pos)
+        };
+        let int = digits(self);
+        if int == 0 || (int > 1 && self.s[self.pos..].starts_with('0')) {
+            return Err(self.expected("a number"));
+        }
+        self.pos += int;
+        let mut integer = true;
+        if self.eat(".") {
+            integer = false;
+            let frac = digits(self);
+            if frac == 0 {
+                return Err(self.expected("a digit"));
+            }
+            self.pos += frac;
+        }
+        if self.eat("e") || self.eat("E") {
+            integer = false;
+            let _ = self.eat("+") || self.eat("-");
+            let exp = digits(self);
+            if exp == 0 {
+                return Err(self.expected("a digit"));
+            }
+            self.pos += exp;
+        }
+        let lexeme = &self.s[start..self.pos];
+        Num::parse(lexeme, integer)
+            .map(JsValue::JsNumber)
+            .ok_or(JsonPathError::Syntax {
+                offset: start,
+                expected: "a number that fits in an f64",
+            })
+    }
+
+    /// A function call, from the `(` after its name.
+    fn call(&mut self, name: &str, start: usize) -> Result<Call, JsonPathError> {
+        let function = Function::named(name).ok_or_else(|| JsonPathError::UnknownFunction {
+            offset: start,
+            name: name.to_string(),
+        })?;
+        self.pos += 1;
+        self.skip_whitespace();
+        let mut args = vec![];
+        if !self.eat(")") {
+            loop {
+                let arg_start = self.pos;
+                let operand = self.operand()?;
+                args.push((operand, arg_start));
+                self.skip_whitespace();
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",", "`,` or `)`")?;
+                self.skip_whitespace();
+            }
+        }
+        let params = function.params();
+        if args.len() != params.len() {
+            return Err(Self::type_error(
+                start,
+                format!(
+                    "`{}` takes {} argument(s) but was given {}",
+                    name,
+                    params.len(),
+                    args.len()
+                ),
+            ));
+        }
+        let args = args
+            .into_iter()
+            .zip(params)
+            .map(|((operand, offset), param)| Self::argument(function, operand, *param, offset))
+            .collect::<Result<Vec<_>, _>>()?;
+        let regex = match (function, args.get(1)) {
+            (
+                Function::Match | Function::Search,
+                Some(Arg::Value(Comparable::Literal(JsValue::JsString(pattern)))),
+            ) => Some(compile_iregexp(pattern, function == Function::Match)),
+            _ => None,
+        };
+        Ok(Call {
+            function,
+            args,
+            regex,
+        })
+    }
+
+    fn argument(
+        function: Function,
+        operand: Operand,
+        param: Type,
+        offset: usize,
+    ) -> Result<Arg, JsonPathError> {
+        match (param, operand) {
+            (Type::Nodes, Operand::Query(query)) => Ok(Arg::Nodes(query)),
+            (Type::Value, operand) => Self::comparable(operand, offset).map(Arg::Value),
+            _ => Err(Self::type_error(
+                offset,
+                format!("`{}` expects a query as its argument", function.name()),
+            )),
+        }
+    }
+}
@@ -1,12 +1,88 @@
 mod core;
 
+pub mod convert;
+pub mod diff;
 pub mod error;
 pub mod json;
+pub mod jsonpath;
+pub mod ndjson;
+pub mod patch;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde;
 
-use crate::core::parser::parse_raw;
+use crate::core::builder::{Borrowed, Owned};
+use crate::core::parser::{parse_prefix_raw, parse_raw, parse_reader};
 use crate::error::ParseError;
-use crate::json::JsValue;
+use crate::json::{JsValue, JsValueRef};
+use std::io::Read;
+
+pub use crate::convert::{FromJson, FromJsonError, ToJson};
+pub use crate::core::events::{Event, EventReader};
+pub use crate::core::options::{DuplicateKeys, ParseOptions};
+pub use crate::core::push::{Feed, PushParser};
+pub use crate::core::sequence::Documents;
+pub use crate::core::stream::{IoEventReader, DEFAULT_BUFFER_SIZE};
+#[cfg(feature = "serde")]
+pub use crate::serde::{from_str, to_string};
+#[cfg(feature = "derive")]
+pub use arjer_derive::{FromJson, ToJson};
 
 pub fn parse(t: &str) -> Result<JsValue, ParseError> {
-    parse_raw(t)
+    parse_raw::<Owned>(t, &ParseOptions::default())
+}
+
+pub fn parse_with(t: &str, opts: &ParseOptions) -> Result<JsValue, ParseError> {
+    parse_raw::<Owned>(t, opts)
+}
+
+/// Parses without copying: strings and keys in the result borrow from `t` unless they
+/// contained escape sequences.
+pub fn parse_borrowed(t: &str) -> Result<JsValueRef<'_>, ParseError> {
+    parse_raw::<Borrowed>(t, &ParseOptions::default())
+}
+
+pub fn parse_borrowed_with<'a>(
+    t: &'a str,
+    opts: &ParseOptions,
+) -> Result<JsValueRef<'a>, ParseError> {
+    parse_raw::<Borrowed>(t, opts)
+}
+
+/// Parses one value from the start of `t` and returns it with whatever follows it, for
+/// inputs that intentionally hold several documents back to back. Whitespace after the
+/// value is skipped; the remainder begins at byte `t.len() - rest.len()` of the input.
+pub fn parse_prefix(t: &str) -> Result<(JsValue, &str), ParseError> {
+    let (value, offset) = parse_prefix_raw::<Owned>(t, &ParseOptions::default())?;
+    Ok((value, &t[offset..]))
+}
+
+/// Parses every document in `t`, for inputs holding several back to back or a JSON text
+/// sequence (RFC 7464); see `Documents`.
+pub fn parse_many(t: &str) -> Documents<'_> {
+    Documents::new(t, &ParseOptions::default())
+}
+
+pub fn parse_many_with<'a>(t: &'a str, opts: &ParseOptions) -> Documents<'a> {
+    Documents::new(t, opts)
+}
+
+/// Reads `t` as a stream of events instead of building a tree; see `EventReader`.
+pub fn events(t: &str) -> EventReader<'_> {
+    EventReader::new(t)
+}
+
+/// Parses a document read from `src` through a bounded buffer, without first loading all of
+/// it into a `String`. Fails if anything other than whitespace follows the value.
+pub fn from_reader<R: Read>(src: R) -> Result<JsValue, ParseError> {
+    parse_reader(src, &ParseOptions::default())
+}
+
+pub fn from_reader_with<R: Read>(src: R, opts: &ParseOptions) -> Result<JsValue, ParseError> {
+    parse_reader(src, opts)
+}
+
+/// Reads the document in `src` as a stream of events; see `IoEventReader`.
+pub fn events_from_reader<R: Read>(src: R) -> IoEventReader<R> {
+    IoEventReader::new(src)
 }
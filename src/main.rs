@@ -1,19 +1,19 @@
-use std::fs;
+use std::fs::File;
 use std::time::Instant;
 
 fn main() {
-    let input = fs::read_to_string("input.json").unwrap();
+    let input = File::open("input.json").unwrap();
     let start = Instant::now();
-    let json = arjer::parse(input.as_str()).unwrap();
+    let json = arjer::from_reader(input).unwrap();
     let end = Instant::now();
     let dur = end.duration_since(start);
     println!("{}", json.pretty_print());
     println!("It took {:?} to parse json file", dur);
     // println!("----------");
-    // let job_title = json.cursor().obj("job").string("title").unwrap();
+    // let job_title = json.cursor().obj("job").field::<String>("title").unwrap();
     // println!("job title is {}", job_title);
-    // let self_employed = json.cursor().obj("job").boolean("self_employed").unwrap();
+    // let self_employed = json.cursor().obj("job").field::<bool>("self_employed").unwrap();
     // println!("is self employed: {}", self_employed);
-    // let no_such_boolean = json.cursor().obj("job").boolean("self_employed_non");
+    // let no_such_boolean = json.cursor().obj("job").field::<bool>("self_employed_non");
     // println!("no such boolean error: {}", no_such_boolean.err().unwrap())
 }
@@ -0,0 +1,224 @@
+//! Newline-delimited JSON (NDJSON, also known as JSON Lines): a stream of documents, one
+//! per line, as commonly used for logs and bulk exports.
+
+use crate::core::builder::Owned;
+use crate::core::options::ParseOptions;
+use crate::core::parser::parse_raw;
+use crate::error::{Location, ParseError};
+use crate::json::JsValue;
+use std::io::{self, BufRead, Write};
+
+/// What the readers do with lines that don't hold a document.
+#[derive(Clone, Debug)]
+pub struct NdjsonOptions {
+    /// How each line is parsed.
+    pub parse: ParseOptions,
+    /// Pass over lines that are empty or only hold whitespace instead of reporting them as
+    /// errors.
+    pub skip_blank_lines: bool,
+    /// Keep going after a line fails to parse. Otherwise the failing line is the last item.
+    pub continue_on_error: bool,
+}
+
+impl Default for NdjsonOptions {
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            skip_blank_lines: true,
+            continue_on_error: false,
+        }
+    }
+}
+
+/// Line bookkeeping shared by both readers.
+struct Records {
+    opts: NdjsonOptions,
+    // number of the last line read, 1-based
+    line: usize,
+    // offset of the next line in the input
+    offset: usize,
+    done: bool,
+}
+
+impl Records {
+    fn new(opts: NdjsonOptions) -> Self {
+        Self {
+            opts,
+            line: 0,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Moves past a line of `len` bytes, including its terminator, and returns its number
+    /// and offset.
+    fn advance(&mut self, len: usize) -> (usize, usize) {
+        let offset = self.offset;
+        self.line += 1;
+        self.offset += len;
+        (self.line, offset)
+    }
+
+    fn fail(&mut self, line: usize, e: ParseError) -> Option<(usize, Result<JsValue, ParseError>)> {
+        if !self.opts.continue_on_error {
+            self.done = true;
+        }
+        Some((line, Err(e)))
+    }
+
+    /// Parses the line `raw` (without its `\n`), or returns `None` if it is to be skipped.
+    fn record(
+        &mut self,
+        raw: &str,
+        line: usize,
+        offset: usize,
+    ) -> Option<(usize, Result<JsValue, ParseError>)> {
+        let text = raw.strip_suffix('\r').unwrap_or(raw);
+        if self.opts.skip_blank_lines && text.trim_matches([' ', '\t', '\r']).is_empty() {
+            return None;
+        }
+        match parse_raw::<Owned>(text, &self.opts.parse) {
+            Ok(value) => Some((line, Ok(value))),
+            Err(e) => self.fail(line, e.on_line(line, offset)),
+        }
+    }
+}
+
+/// Iterator over the documents in a `&str`, created by [`from_str`].
+pub struct StrRecords<'a> {
+    rest: &'a str,
+    records: Records,
+}
+
+impl Iterator for StrRecords<'_> {
+    /// The 1-based number of the line the document was on, and the document.
+    type Item = (usize, Result<JsValue, ParseError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.records.done && !self.rest.is_empty() {
+            let (raw, len) = match self.rest.find('\n') {
+                Some(end) => (&self.rest[..end], end + 1),
+                None => (self.rest, self.rest.len()),
+            };
+            self.rest = &self.rest[len..];
+            let (line, offset) = self.records.advance(len);
+            if let Some(item) = self.records.record(raw, line, offset) {
+                return Some(item);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the documents read from an `io::BufRead`, created by [`from_reader`].
+pub struct ReadRecords<R> {
+    src: R,
+    buf: Vec<u8>,
+    records: Records,
+}
+
+impl<R: BufRead> Iterator for ReadRecords<R> {
+    /// The 1-based number of the line the document was on, and the document.
+    type Item = (usize, Result<JsValue, ParseError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.records.done {
+            self.buf.clear();
+            let len = match self.src.read_until(b'\n', &mut self.buf) {
+                Ok(0) => return None,
+                Ok(len) => len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    // there is no telling where the next line starts
+                    self.records.done = true;
+                    let e = ParseError::Io {
+                        error,
+                        at: Location::at(self.records.offset),
+                    };
+                    return Some((self.records.line + 1, Err(e)));
+                }
+            };
+            let (line, offset) = self.records.advance(len);
+            let raw = self.buf.strip_suffix(b"\n").unwrap_or(&self.buf);
+            let item = match std::str::from_utf8(raw) {
+                Ok(raw) => self.records.record(raw, line, offset),
+                Err(e) => {
+                    let at = Location::at(e.valid_up_to());
+                    let e = ParseError::InvalidUtf8 { at }.resolve(raw);
+                    self.records.fail(line, e.on_line(line, offset))
+                }
+            };
+            if item.is_some() {
+                return item;
+            }
+        }
+        None
+    }
+}
+
+/// Reads the documents in `s`, one per line.
+pub fn from_str(s: &str) -> StrRecords<'_> {
+    from_str_with(s, NdjsonOptions::default())
+}
+
+pub fn from_str_with(s: &str, opts: NdjsonOptions) -> StrRecords<'_> {
+    StrRecords {
+        rest: s,
+        records: Records::new(opts),
+    }
+}
+
+/// Reads documents from `src` line by line, holding only one line in memory at a time.
+pub fn from_reader<R: BufRead>(src: R) -> ReadRecords<R> {
+    from_reader_with(src, NdjsonOptions::default())
+}
+
+pub fn from_reader_with<R: BufRead>(src: R, opts: NdjsonOptions) -> ReadRecords<R> {
+    ReadRecords {
+        src,
+        buf: Vec::new(),
+        records: Records::new(opts),
+    }
+}
+
+/// Writes values as NDJSON: each one compact, on a line of its own. Compact output escapes
+/// every line break inside strings, so a value can never span lines.
+pub struct Writer<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+    /// Every value is written with several small writes, so wrap unbuffered sinks in a
+    /// `BufWriter`.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Writes `value` followed by `\n`.
+    pub fn write(&mut self, value: &JsValue) -> io::Result<()> {
+        value.to_writer(&mut self.inner)?;
+        self.inner.write_all(b"\n")
+    }
+
+    pub fn write_all<'v, I>(&mut self, values: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'v JsValue>,
+    {
+        for value in values {
+            self.write(value)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
@@ -0,0 +1,206 @@
+use crate::json::{JsValue, JsonPointer, Map};
+
+/// Arrays whose changed middle parts would need a longest-common-subsequence table larger
+/// than this are only compared position by position.
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+/// A patch that turns `from` into `to`: members are added, removed or diffed recursively,
+/// and array elements are matched up by their longest common subsequence so that only the
+/// elements that actually changed show up. Containers of different kinds and changed
+/// scalars are replaced whole.
+pub fn diff(from: &JsValue, to: &JsValue) -> JsValue {
+    let mut ops = vec![];
+    diff_at(&mut JsonPointer::root(), from, to, &mut ops);
+    JsValue::JsArray(ops)
+}
+
+fn op(name: &str, path: &JsonPointer, value: Option<&JsValue>) -> JsValue {
+    let mut members = Map::with_capacity(3);
+    members.insert("op".into(), JsValue::JsString(name.into()));
+    members.insert("path".into(), JsValue::JsString(path.to_string()));
+    if let Some(value) = value {
+        members.insert("value".into(), value.clone());
+    }
+    JsValue::JsObject(members)
+}
+
+fn diff_at(path: &mut JsonPointer, from: &JsValue, to: &JsValue, ops: &mut Vec<JsValue>) {
+    match (from, to) {
+        _ if from == to => {}
+        (JsValue::JsObject(old), JsValue::JsObject(new)) => {
+            for (k, v) in old.iter() {
+                path.push(k.as_str());
+                match new.get(k) {
+                    Some(w) => diff_at(path, v, w, ops),
+                    None => ops.push(op("remove", path, None)),
+                }
+                path.pop();
+            }
+            for (k, w) in new.iter().filter(|(k, _)| !old.contains_key(k)) {
+                path.push(k.as_str());
+                ops.push(op("add", path, Some(w)));
+                path.pop();
+            }
+        }
+        (JsValue::JsArray(old), JsValue::JsArray(new)) => diff_arrays(path, old, new, ops),
+        _ => ops.push(op("replace", path, Some(to))),
+    }
+}
+
+pub(crate) enum Edit {
+    Keep,
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Lines `old` and `new` up as an edit script, with `eq` deciding which elements are the
+/// same. Indices are into `old` for deletions and into `new` for insertions, and a deletion
+/// directly followed by an insertion stands for an element that changed in place.
+pub(crate) fn align<F>(old: &[JsValue], new: &[JsValue], eq: F) -> Vec<Edit>
+where
+    F: Fn(&JsValue, &JsValue) -> bool,
+{
+    let prefix = old.iter().zip(new).take_while(|(a, b)| eq(a, b)).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| eq(a, b))
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    // pairing elements up by position changes every one of them and adds or removes the tail,
+    // which beats the common subsequence when that is short, e.g. for a reversed array; on
+    // a tie the subsequence wins, so that elements which merely shifted are kept rather than
+    // changed into their neighbours
+    let common = old_mid.len().min(new_mid.len());
+    let mut mid: Vec<Edit> = (0..common)
+        .flat_map(|i| [Edit::Delete(i), Edit::Insert(i)])
+        .collect();
+    mid.extend((common..old_mid.len()).map(Edit::Delete));
+    mid.extend((common..new_mid.len()).map(Edit::Insert));
+    if old_mid.len() * new_mid.len() <= MAX_LCS_CELLS {
+        let lcs = lcs_edits(old_mid, new_mid, &eq);
+        if cost(&lcs) <= cost(&mid) {
+            mid = lcs;
+        }
+    }
+
+    let mut edits = Vec::with_capacity(prefix + mid.len() + suffix);
+    edits.extend((0..prefix).map(|_| Edit::Keep));
+    edits.extend(mid.into_iter().map(|edit| match edit {
+        Edit::Keep => Edit::Keep,
+        Edit::Delete(i) => Edit::Delete(prefix + i),
+        Edit::Insert(j) => Edit::Insert(prefix + j),
+    }));
+    edits.extend((0..suffix).map(|_| Edit::Keep));
+    edits
+}
+
+fn diff_arrays(path: &mut JsonPointer, old: &[JsValue], new: &[JsValue], ops: &mut Vec<JsValue>) {
+    let edits = align(old, new, |a, b| a == b);
+    // the index the next edit applies to in the array as patched so far
+    let mut pos = 0;
+    let mut edits = edits.into_iter().peekable();
+    while let Some(edit) = edits.next() {
+        match edit {
+            Edit::Keep => pos += 1,
+            Edit::Delete(i) => {
+                path.push(pos.to_string());
+                // an element that changed in place is diffed rather than removed and re-added
+                if let Some(Edit::Insert(j)) = edits.peek() {
+                    diff_at(path, &old[i], &new[*j], ops);
+                    edits.next();
+                    pos += 1;
+                } else {
+                    ops.push(op("remove", path, None));
+                }
+                path.pop();
+            }
+            Edit::Insert(j) => {
+                path.push(pos.to_string());
+                ops.push(op("add", path, Some(&new[j])));
+                path.pop();
+                pos += 1;
+            }
+        }
+    }
+}
+
+/// The edit script that turns `old` into `new` while keeping their longest common
+/// subsequence. Within a run of changes, deletions and insertions alternate so that they can
+/// be paired up into in-place changes.
+fn lcs_edits<F>(old: &[JsValue], new: &[JsValue], eq: &F) -> Vec<Edit>
+where
+    F: Fn(&JsValue, &JsValue) -> bool,
+{
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j] is the LCS length of old[i..] and new[j..]
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[at(i, j)] = if eq(&old[i], &new[j]) {
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut edits = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    let (mut deletes, mut inserts) = (vec![], vec![]);
+    while i < n || j < m {
+        if i < n && j < m && eq(&old[i], &new[j]) {
+            pair_up(&mut edits, &mut deletes, &mut inserts);
+            edits.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lengths[at(i + 1, j)] >= lengths[at(i, j + 1)]) {
+            deletes.push(i);
+            i += 1;
+        } else {
+            inserts.push(j);
+            j += 1;
+        }
+    }
+    pair_up(&mut edits, &mut deletes, &mut inserts);
+    edits
+}
+
+/// How many operations `edits` turn into, counting a deletion followed by an insertion as
+/// one in-place change.
+fn cost(edits: &[Edit]) -> usize {
+    let mut ops = 0;
+    let mut paired = false;
+    for (k, edit) in edits.iter().enumerate() {
+        match edit {
+            Edit::Keep => {}
+            Edit::Insert(_) if paired => paired = false,
+            Edit::Delete(_) => {
+                paired = matches!(edits.get(k + 1), Some(Edit::Insert(_)));
+                ops += 1;
+            }
+            Edit::Insert(_) => ops += 1,
+        }
+    }
+    ops
+}
+
+/// Moves a run of changes into `edits`, alternating deletions and insertions.
+fn pair_up(edits: &mut Vec<Edit>, deletes: &mut Vec<usize>, inserts: &mut Vec<usize>) {
+    let mut deletes = deletes.drain(..);
+    let mut inserts = inserts.drain(..);
+    loop {
+        match (deletes.next(), inserts.next()) {
+            (None, None) => break,
+            (d, ins) => {
+                edits.extend(d.map(Edit::Delete));
+                edits.extend(ins.map(Edit::Insert));
+            }
+        }
+    }
+}
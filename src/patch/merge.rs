@@ -0,0 +1,64 @@
+use crate::json::{JsValue, Map};
+
+impl JsValue {
+    /// Applies a JSON Merge Patch (RFC 7396): members of an object patch are merged into
+    /// this value recursively, members set to `null` are removed, and any patch that is not
+    /// an object replaces the value outright.
+    pub fn merge_patch(&mut self, patch: &JsValue) {
+        let JsValue::JsObject(members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, JsValue::JsObject(_)) {
+            *self = JsValue::JsObject(Map::new());
+        }
+        let JsValue::JsObject(target) = self else {
+            return;
+        };
+        for (k, v) in members.iter() {
+            match v {
+                JsValue::JsNull => {
+                    target.remove(k);
+                }
+                _ => match target.get_mut(k) {
+                    Some(existing) => existing.merge_patch(v),
+                    None => {
+                        let mut added = JsValue::JsNull;
+                        added.merge_patch(v);
+                        target.insert(k.clone(), added);
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// The merge patch that turns `from` into `to`, as small as the format allows: unchanged
+/// members are left out, and only changed objects are descended into. Arrays are always
+/// replaced whole.
+///
+/// Merge patches can't set a member to `null`, so nulls inside objects of `to` (other than
+/// at the top level) are dropped when the patch is applied.
+pub fn merge_diff(from: &JsValue, to: &JsValue) -> JsValue {
+    match (from, to) {
+        (JsValue::JsObject(old), JsValue::JsObject(new)) => {
+            let mut patch = Map::new();
+            for k in old.keys().filter(|k| !new.contains_key(k)) {
+                patch.insert(k.clone(), JsValue::JsNull);
+            }
+            for (k, w) in new.iter() {
+                match old.get(k) {
+                    Some(v) if v == w => {}
+                    Some(v) => {
+                        patch.insert(k.clone(), merge_diff(v, w));
+                    }
+                    None => {
+                        patch.insert(k.clone(), w.clone());
+                    }
+                }
+            }
+            JsValue::JsObject(patch)
+        }
+        _ => to.clone(),
+    }
+}
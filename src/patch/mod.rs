@@ -0,0 +1,185 @@
+//! JSON Patch (RFC 6902): documents describing a sequence of changes to a JSON value, as
+//! an array of operations like `{"op": "add", "path": "/a/b", "value": 1}`. Also JSON Merge
+//! Patch (RFC 7396), where the patch is a partial document merged into the target; see
+//! `JsValue::merge_patch`.
+
+pub(crate) mod diff;
+mod merge;
+
+use crate::json::{JsValue, JsonPointer, PointerError};
+use std::fmt::{self, Display, Formatter};
+
+pub use diff::diff;
+pub use merge::merge_diff;
+
+/// Why a patch couldn't be applied. Every variant but `NotAnArray` carries the index of the
+/// operation that failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchError {
+    /// The patch is not an array of operations.
+    NotAnArray,
+    /// The operation is not an object with the members its `op` requires.
+    Malformed { index: usize, reason: String },
+    /// A `path` or `from` pointer of the operation is invalid or leads nowhere.
+    Pointer { index: usize, error: PointerError },
+    /// A `test` operation found a different value at `path`.
+    TestFailed { index: usize, path: String },
+    /// A `move` whose `path` is inside its `from`.
+    MoveIntoChild { index: usize },
+}
+
+impl PatchError {
+    /// The index of the failing operation within the patch.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            PatchError::NotAnArray => None,
+            PatchError::Malformed { index, .. }
+            | PatchError::Pointer { index, .. }
+            | PatchError::TestFailed { index, .. }
+            | PatchError::MoveIntoChild { index } => Some(*index),
+        }
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::NotAnArray => write!(f, "a JSON patch must be an array of operations"),
+            PatchError::Malformed { index, reason } => {
+                write!(f, "operation {} is malformed: {}", index, reason)
+            }
+            PatchError::Pointer { index, error } => write!(f, "operation {}: {}", index, error),
+            PatchError::TestFailed { index, path } => {
+                write!(f, "operation {}: test failed at `{}`", index, path)
+            }
+            PatchError::MoveIntoChild { index } => write!(
+                f,
+                "operation {}: a value can't be moved into one of its children",
+                index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatchError::Pointer { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+enum Operation<'p> {
+    Add(JsonPointer, &'p JsValue),
+    Remove(JsonPointer),
+    Replace(JsonPointer, &'p JsValue),
+    Move {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Copy {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Test(JsonPointer, &'p JsValue),
+}
+
+impl<'p> Operation<'p> {
+    fn parse(index: usize, op: &'p JsValue) -> Result<Self, PatchError> {
+        let malformed = |reason: String| PatchError::Malformed { index, reason };
+        let JsValue::JsObject(members) = op else {
+            return Err(malformed("not an object".into()));
+        };
+        let pointer = |name: &str| match members.get(name) {
+            Some(JsValue::JsString(s)) => {
+                JsonPointer::parse(s).map_err(|error| PatchError::Pointer { index, error })
+            }
+            Some(_) => Err(malformed(format!("`{}` is not a string", name))),
+            None => Err(malformed(format!("`{}` is missing", name))),
+        };
+        let value = || {
+            members
+                .get("value")
+                .ok_or_else(|| malformed("`value` is missing".into()))
+        };
+        let name = match members.get("op") {
+            Some(JsValue::JsString(name)) => name.as_str(),
+            Some(_) => return Err(malformed("`op` is not a string".into())),
+            None => return Err(malformed("`op` is missing".into())),
+        };
+        Ok(match name {
+            "add" => Operation::Add(pointer("path")?, value()?),
+            "remove" => Operation::Remove(pointer("path")?),
+            "replace" => Operation::Replace(pointer("path")?, value()?),
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "test" => Operation::Test(pointer("path")?, value()?),
+            other => return Err(malformed(format!("unknown op `{}`", other))),
+        })
+    }
+
+    fn apply(self, index: usize, doc: &mut JsValue) -> Result<(), PatchError> {
+        let pointer_error = |error| PatchError::Pointer { index, error };
+        match self {
+            Operation::Add(path, value) => {
+                path.insert(doc, value.clone()).map_err(pointer_error)?;
+            }
+            Operation::Remove(path) => {
+                path.remove(doc).map_err(pointer_error)?;
+            }
+            Operation::Replace(path, value) => {
+                path.replace(doc, value.clone()).map_err(pointer_error)?;
+            }
+            Operation::Move { from, path } => {
+                if path == from {
+                    from.get(doc).map_err(pointer_error)?;
+                } else if path.starts_with(&from) {
+                    return Err(PatchError::MoveIntoChild { index });
+                } else {
+                    let value = from.remove(doc).map_err(pointer_error)?;
+                    path.insert(doc, value).map_err(pointer_error)?;
+                }
+            }
+            Operation::Copy { from, path } => {
+                let value = from.get(doc).map_err(pointer_error)?.clone();
+                path.insert(doc, value).map_err(pointer_error)?;
+            }
+            Operation::Test(path, value) => {
+                if path.get(doc).map_err(pointer_error)? != value {
+                    return Err(PatchError::TestFailed {
+                        index,
+                        path: path.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies `patch` to `doc`. Either every operation succeeds or `doc` is left untouched:
+/// the operations run on a copy that only replaces `doc` once all of them went through.
+pub fn apply_patch(doc: &mut JsValue, patch: &JsValue) -> Result<(), PatchError> {
+    let JsValue::JsArray(ops) = patch else {
+        return Err(PatchError::NotAnArray);
+    };
+    // checking the whole patch up front saves copying the document for one that is malformed
+    let ops = ops
+        .iter()
+        .enumerate()
+        .map(|(index, op)| Operation::parse(index, op))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut patched = doc.clone();
+    for (index, op) in ops.into_iter().enumerate() {
+        op.apply(index, &mut patched)?;
+    }
+    *doc = patched;
+    Ok(())
+}
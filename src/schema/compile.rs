@@ -0,0 +1,611 @@
+use crate::json::{JsValue, JsonPointer, Map, Num};
+use crate::schema::SchemaError;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// One compiled (sub)schema. Node 0 is the root; subschemas refer to each other by index,
+/// which is what lets `$ref` form cycles.
+#[derive(Clone, Debug)]
+pub(crate) struct Node {
+    /// Where the schema is in the document, for reporting the keywords that fail.
+    pub(crate) location: JsonPointer,
+    pub(crate) keywords: Vec<Keyword>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Kind {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    String,
+    Integer,
+}
+
+impl Kind {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Kind::Null,
+            "boolean" => Kind::Boolean,
+            "object" => Kind::Object,
+            "array" => Kind::Array,
+            "number" => Kind::Number,
+            "string" => Kind::String,
+            "integer" => Kind::Integer,
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Kind::Null => "null",
+            Kind::Boolean => "boolean",
+            Kind::Object => "object",
+            Kind::Array => "array",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::Integer => "integer",
+        }
+    }
+}
+
+/// A keyword with its operands checked and its subschemas compiled. Keywords that only make
+/// sense together (`additionalProperties` with `properties`, `items` with `prefixItems`,
+/// `contains` with its bounds) carry what they need from their siblings.
+#[derive(Clone, Debug)]
+pub(crate) enum Keyword {
+    /// The `false` schema.
+    False,
+    Ref(&'static str, usize),
+    Type(Vec<Kind>),
+    Enum(Vec<JsValue>),
+    Const(JsValue),
+    MultipleOf(Num),
+    Maximum(Num),
+    ExclusiveMaximum(Num),
+    Minimum(Num),
+    ExclusiveMinimum(Num),
+    MaxLength(u64),
+    MinLength(u64),
+    Pattern(Regex),
+    MaxItems(u64),
+    MinItems(u64),
+    UniqueItems,
+    Contains {
+        schema: usize,
+        min: u64,
+        max: Option<u64>,
+    },
+    MaxProperties(u64),
+    MinProperties(u64),
+    Required(Vec<String>),
+    DependentRequired(Vec<(String, Vec<String>)>),
+    AllOf(Vec<usize>),
+    AnyOf(Vec<usize>),
+    OneOf(Vec<usize>),
+    Not(usize),
+    If {
+        condition: usize,
+        then: Option<usize>,
+        otherwise: Option<usize>,
+    },
+    DependentSchemas(Vec<(String, usize)>),
+    Properties(Vec<(String, usize)>),
+    PatternProperties(Vec<(Regex, usize)>),
+    AdditionalProperties {
+        schema: usize,
+        properties: Vec<String>,
+        patterns: Vec<Regex>,
+    },
+    PropertyNames(usize),
+    PrefixItems(Vec<usize>),
+    Items {
+        schema: usize,
+        skip: usize,
+    },
+}
+
+impl Keyword {
+    /// The keyword as written in the schema, which is where its failures are reported.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Keyword::False => "",
+            Keyword::Ref(name, _) => name,
+            Keyword::Type(_) => "type",
+            Keyword::Enum(_) => "enum",
+            Keyword::Const(_) => "const",
+            Keyword::MultipleOf(_) => "multipleOf",
+            Keyword::Maximum(_) => "maximum",
+            Keyword::ExclusiveMaximum(_) => "exclusiveMaximum",
+            Keyword::Minimum(_) => "minimum",
+            Keyword::ExclusiveMinimum(_) => "exclusiveMinimum",
+            Keyword::MaxLength(_) => "maxLength",
+            Keyword::MinLength(_) => "minLength",
+            Keyword::Pattern(_) => "pattern",
+            Keyword::MaxItems(_) => "maxItems",
+            Keyword::MinItems(_) => "minItems",
+            Keyword::UniqueItems => "uniqueItems",
+            Keyword::Contains { .. } => "contains",
+            Keyword::MaxProperties(_) => "maxProperties",
+            Keyword::MinProperties(_) => "minProperties",
+            Keyword::Required(_) => "required",
+            Keyword::DependentRequired(_) => "dependentRequired",
+            Keyword::AllOf(_) => "allOf",
+            Keyword::AnyOf(_) => "anyOf",
+            Keyword::OneOf(_) => "oneOf",
+            Keyword::Not(_) => "not",
+            Keyword::If { .. } => "if",
+            Keyword::DependentSchemas(_) => "dependentSchemas",
+            Keyword::Properties(_) => "properties",
+            Keyword::PatternProperties(_) => "patternProperties",
+            Keyword::AdditionalProperties { .. } => "additionalProperties",
+            Keyword::PropertyNames(_) => "propertyNames",
+            Keyword::PrefixItems(_) => "prefixItems",
+            Keyword::Items { .. } => "items",
+        }
+    }
+}
+
+/// Where a schema object sits in the document and the base URI in effect there.
+struct Scope {
+    location: JsonPointer,
+    base: String,
+}
+
+/// Members whose values are data rather than schemas, so `$id`s inside them don't count.
+const DATA_KEYWORDS: &[&str] = &["const", "default", "enum", "examples"];
+
+pub(crate) struct Compiler<'s> {
+    root: &'s JsValue,
+    /// Schema resources by their base URI, without a fragment.
+    resources: HashMap<String, &'s JsValue>,
+    /// `$anchor`s and `$dynamicAnchor`s by the base URI of their resource and their name,
+    /// e.g. `https://example.com/tree#node`.
+    anchors: HashMap<String, &'s JsValue>,
+    /// Every schema object in the document, keyed by address. Refs land on arbitrary
+    /// subschemas and need to know where those are.
+    scopes: HashMap<*const JsValue, Scope>,
+    /// Subschemas compiled so far, by address, so each is compiled once however many refs
+    /// lead to it.
+    compiled: HashMap<*const JsValue, usize>,
+    nodes: Vec<Node>,
+}
+
+impl<'s> Compiler<'s> {
+    pub(crate) fn new(root: &'s JsValue) -> Self {
+        Self {
+            root,
+            resources: HashMap::new(),
+            anchors: HashMap::new(),
+            scopes: HashMap::new(),
+            compiled: HashMap::new(),
+            nodes: vec![],
+        }
+    }
+
+    pub(crate) fn run(mut self) -> Result<Vec<Node>, SchemaError> {
+        let root = self.root;
+        self.resources.insert(String::new(), root);
+        self.scan(root, JsonPointer::root(), "");
+        self.compile(root, JsonPointer::root(), "")?;
+        Ok(self.nodes)
+    }
+
+    /// Records the schema resources, anchors and scopes of `value` and everything below it.
+    fn scan(&mut self, value: &'s JsValue, location: JsonPointer, base: &str) {
+        let members = match value {
+            JsValue::JsObject(members) => members,
+            JsValue::JsArray(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.scan(item, child(&location, &i.to_string()), base);
+                }
+                return;
+            }
+            _ => return,
+        };
+        let mut base = base.to_string();
+        if let Some(JsValue::JsString(id)) = members.get("$id") {
+            base = strip_fragment(&resolve(&base, id)).to_string();
+            self.resources.insert(base.clone(), value);
+        }
+        for keyword in ["$anchor", "$dynamicAnchor"] {
+            if let Some(JsValue::JsString(anchor)) = members.get(keyword) {
+                self.anchors.insert(format!("{}#{}", base, anchor), value);
+            }
+        }
+        for (k, v) in members.iter() {
+            if !DATA_KEYWORDS.contains(&k.as_str()) {
+                self.scan(v, child(&location, k), &base);
+            }
+        }
+        let scope = Scope { location, base };
+        self.scopes.insert(value, scope);
+    }
+
+    fn compile(
+        &mut self,
+        value: &'s JsValue,
+        location: JsonPointer,
+        base: &str,
+    ) -> Result<usize, SchemaError> {
+        if let Some(&index) = self.compiled.get(&(value as *const JsValue)) {
+            return Ok(index);
+        }
+        let index = self.nodes.len();
+        self.compiled.insert(value, index);
+        self.nodes.push(Node {
+            location: location.clone(),
+            keywords: vec![],
+        });
+        let keywords = match value {
+            JsValue::JsBool(true) => vec![],
+            JsValue::JsBool(false) => vec![Keyword::False],
+            JsValue::JsObject(members) => {
+                let base = match members.get("$id") {
+                    Some(JsValue::JsString(id)) => strip_fragment(&resolve(base, id)).to_string(),
+                    _ => base.to_string(),
+                };
+                self.keywords(members, &location, &base)?
+            }
+            _ => {
+                return Err(SchemaError::InvalidKeyword {
+                    path: location,
+                    reason: "a schema must be an object or a boolean".into(),
+                })
+            }
+        };
+        self.nodes[index].keywords = keywords;
+        Ok(index)
+    }
+
+    fn keywords(
+        &mut self,
+        members: &'s Map,
+        location: &JsonPointer,
+        base: &str,
+    ) -> Result<Vec<Keyword>, SchemaError> {
+        let mut keywords = vec![];
+        for (name, value) in members.iter() {
+            let at = child(location, name);
+            let keyword = match name.as_str() {
+                "$ref" => Keyword::Ref("$ref", self.reference(value, &at, base)?),
+                "$dynamicRef" => Keyword::Ref("$dynamicRef", self.reference(value, &at, base)?),
+                "type" => {
+                    let kinds = match value {
+                        JsValue::JsArray(names) => names.iter().map(kind).collect(),
+                        name => kind(name).map(|k| vec![k]),
+                    };
+                    Keyword::Type(kinds.ok_or_else(|| invalid(&at, "unknown type"))?)
+                }
+                "enum" => match value {
+                    JsValue::JsArray(values) => Keyword::Enum(values.clone()),
+                    _ => return Err(invalid(&at, "expected an array")),
+                },
+                "const" => Keyword::Const(value.clone()),
+                "multipleOf" => match value {
+                    JsValue::JsNumber(n) if n.as_f64() > 0.0 => Keyword::MultipleOf(*n),
+                    _ => return Err(invalid(&at, "expected a number greater than 0")),
+                },
+                "maximum" => Keyword::Maximum(number(value, &at)?),
+                "exclusiveMaximum" => Keyword::ExclusiveMaximum(number(value, &at)?),
+                "minimum" => Keyword::Minimum(number(value, &at)?),
+                "exclusiveMinimum" => Keyword::ExclusiveMinimum(number(value, &at)?),
+                "maxLength" => Keyword::MaxLength(count(value, &at)?),
+                "minLength" => Keyword::MinLength(count(value, &at)?),
+                "pattern" => Keyword::Pattern(pattern(value, &at)?),
+                "maxItems" => Keyword::MaxItems(count(value, &at)?),
+                "minItems" => Keyword::MinItems(count(value, &at)?),
+                "uniqueItems" => match value {
+                    JsValue::JsBool(true) => Keyword::UniqueItems,
+                    JsValue::JsBool(false) => continue,
+                    _ => return Err(invalid(&at, "expected a boolean")),
+                },
+                "contains" => Keyword::Contains {
+                    schema: self.compile(value, at, base)?,
+                    min: match members.get("minContains") {
+                        Some(min) => count(min, &child(location, "minContains"))?,
+                        None => 1,
+                    },
+                    max: match members.get("maxContains") {
+                        Some(max) => Some(count(max, &child(location, "maxContains"))?),
+                        None => None,
+                    },
+                },
+                "maxProperties" => Keyword::MaxProperties(count(value, &at)?),
+                "minProperties" => Keyword::MinProperties(count(value, &at)?),
+                "required" => Keyword::Required(strings(value, &at)?),
+                "dependentRequired" => {
+                    let mut dependencies = vec![];
+                    for (k, v) in object(value, &at)?.iter() {
+                        dependencies.push((k.clone(), strings(v, &child(&at, k))?));
+                    }
+                    Keyword::DependentRequired(dependencies)
+                }
+                "allOf" => Keyword::AllOf(self.schemas(value, &at, base)?),
+                "anyOf" => Keyword::AnyOf(self.schemas(value, &at, base)?),
+                "oneOf" => Keyword::OneOf(self.schemas(value, &at, base)?),
+                "not" => Keyword::Not(self.compile(value, at, base)?),
+                "if" => Keyword::If {
+                    condition: self.compile(value, at, base)?,
+                    then: self.member(members, "then", location, base)?,
+                    otherwise: self.member(members, "else", location, base)?,
+                },
+                "dependentSchemas" => Keyword::DependentSchemas(self.named(value, &at, base)?),
+                "properties" => Keyword::Properties(self.named(value, &at, base)?),
+                "patternProperties" => {
+                    let mut schemas = vec![];
+                    for (k, v) in object(value, &at)?.iter() {
+                        let at = child(&at, k);
+                        let regex = pattern(&JsValue::JsString(k.clone()), &at)?;
+                        schemas.push((regex, self.compile(v, at, base)?));
+                    }
+                    Keyword::PatternProperties(schemas)
+                }
+                "additionalProperties" => Keyword::AdditionalProperties {
+                    schema: self.compile(value, at, base)?,
+                    properties: match members.get("properties") {
+                        Some(JsValue::JsObject(properties)) => properties.keys().cloned().collect(),
+                        _ => vec![],
+                    },
+                    patterns: match members.get("patternProperties") {
+                        Some(JsValue::JsObject(patterns)) => {
+                            let at = child(location, "patternProperties");
+                            patterns
+                                .keys()
+                                .map(|k| pattern(&JsValue::JsString(k.clone()), &child(&at, k)))
+                                .collect::<Result<_, _>>()?
+                        }
+                        _ => vec![],
+                    },
+                },
+                "propertyNames" => Keyword::PropertyNames(self.compile(value, at, base)?),
+                "prefixItems" => Keyword::PrefixItems(self.schemas(value, &at, base)?),
+                "items" => Keyword::Items {
+                    schema: self.compile(value, at, base)?,
+                    skip: match members.get("prefixItems") {
+                        Some(JsValue::JsArray(prefix)) => prefix.len(),
+                        _ => 0,
+                    },
+                },
+                "$defs" => {
+                    // not applied to the instance, but compiled so that mistakes in unused
+                    // definitions still surface
+                    self.named(value, &at, base)?;
+                    continue;
+                }
+                _ => continue,
+            };
+            keywords.push(keyword);
+        }
+        Ok(keywords)
+    }
+
+    /// Compiles the sibling keyword `name`, if there is one.
+    fn member(
+        &mut self,
+        members: &'s Map,
+        name: &str,
+        location: &JsonPointer,
+        base: &str,
+    ) -> Result<Option<usize>, SchemaError> {
+        match members.get(name) {
+            Some(value) => Ok(Some(self.compile(value, child(location, name), base)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Compiles a non-empty array of schemas.
+    fn schemas(
+        &mut self,
+        value: &'s JsValue,
+        at: &JsonPointer,
+        base: &str,
+    ) -> Result<Vec<usize>, SchemaError> {
+        match value {
+            JsValue::JsArray(schemas) if !schemas.is_empty() => schemas
+                .iter()
+                .enumerate()
+                .map(|(i, schema)| self.compile(schema, child(at, &i.to_string()), base))
+                .collect(),
+            _ => Err(invalid(at, "expected a non-empty array of schemas")),
+        }
+    }
+
+    /// Compiles an object of schemas.
+    fn named(
+        &mut self,
+        value: &'s JsValue,
+        at: &JsonPointer,
+        base: &str,
+    ) -> Result<Vec<(String, usize)>, SchemaError> {
+        let mut schemas = vec![];
+        for (k, v) in object(value, at)?.iter() {
+            schemas.push((k.clone(), self.compile(v, child(at, k), base)?));
+        }
+        Ok(schemas)
+    }
+
+    /// Compiles the target of a `$ref` or `$dynamicRef`.
+    fn reference(
+        &mut self,
+        value: &'s JsValue,
+        at: &JsonPointer,
+        base: &str,
+    ) -> Result<usize, SchemaError> {
+        let JsValue::JsString(reference) = value else {
+            return Err(invalid(at, "expected a string"));
+        };
+        let unresolved = || SchemaError::UnresolvedRef {
+            path: at.clone(),
+            reference: reference.clone(),
+        };
+        let target = resolve(base, reference);
+        let (uri, fragment) = target.split_once('#').unwrap_or((&target, ""));
+        let fragment = percent_decode(fragment).ok_or_else(unresolved)?;
+        let resource = *self.resources.get(uri).ok_or_else(unresolved)?;
+        let target = if fragment.is_empty() {
+            resource
+        } else if fragment.starts_with('/') {
+            let pointer = JsonPointer::parse(&fragment).map_err(|_| unresolved())?;
+            pointer.get(resource).map_err(|_| unresolved())?
+        } else {
+            let anchor = format!("{}#{}", uri, fragment);
+            *self.anchors.get(&anchor).ok_or_else(unresolved)?
+        };
+        let (location, base) = match self.scopes.get(&(target as *const JsValue)) {
+            Some(scope) => (scope.location.clone(), scope.base.clone()),
+            // boolean schemas aren't scanned; they sit in the scope of their resource
+            None => {
+                let resource = self.scopes.get(&(resource as *const JsValue));
+                let mut location = resource.map_or_else(JsonPointer::root, |s| s.location.clone());
+                if let Ok(pointer) = JsonPointer::parse(&fragment) {
+                    for token in pointer.tokens() {
+                        location.push(token.clone());
+                    }
+                }
+                (location, uri.to_string())
+            }
+        };
+        self.compile(target, location, &base)
+    }
+}
+
+fn child(location: &JsonPointer, token: &str) -> JsonPointer {
+    let mut location = location.clone();
+    location.push(token);
+    location
+}
+
+fn invalid(at: &JsonPointer, reason: &str) -> SchemaError {
+    SchemaError::InvalidKeyword {
+        path: at.clone(),
+        reason: reason.into(),
+    }
+}
+
+fn kind(name: &JsValue) -> Option<Kind> {
+    match name {
+        JsValue::JsString(name) => Kind::parse(name),
+        _ => None,
+    }
+}
+
+fn number(value: &JsValue, at: &JsonPointer) -> Result<Num, SchemaError> {
+    match value {
+        JsValue::JsNumber(n) => Ok(*n),
+        _ => Err(invalid(at, "expected a number")),
+    }
+}
+
+/// A non-negative integer; `2.0` counts as one.
+fn count(value: &JsValue, at: &JsonPointer) -> Result<u64, SchemaError> {
+    match value {
+        JsValue::JsNumber(Num::U32(n)) => Ok(*n as u64),
+        JsValue::JsNumber(Num::U64(n)) => Ok(*n),
+        JsValue::JsNumber(Num::F(f)) if *f >= 0.0 && f.fract() == 0.0 => Ok(*f as u64),
+        _ => Err(invalid(at, "expected a non-negative integer")),
+    }
+}
+
+fn object<'a>(value: &'a JsValue, at: &JsonPointer) -> Result<&'a Map, SchemaError> {
+    match value {
+        JsValue::JsObject(members) => Ok(members),
+        _ => Err(invalid(at, "expected an object")),
+    }
+}
+
+fn strings(value: &JsValue, at: &JsonPointer) -> Result<Vec<String>, SchemaError> {
+    let strings = match value {
+        JsValue::JsArray(items) => items
+            .iter()
+            .map(|item| match item {
+                JsValue::JsString(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    };
+    strings.ok_or_else(|| invalid(at, "expected an array of strings"))
+}
+
+/// Patterns are ECMA-262 regexes, which the `regex` crate accepts as long as they stay
+/// clear of backreferences and lookaround.
+fn pattern(value: &JsValue, at: &JsonPointer) -> Result<Regex, SchemaError> {
+    let JsValue::JsString(source) = value else {
+        return Err(invalid(at, "expected a string"));
+    };
+    Regex::new(source).map_err(|_| SchemaError::InvalidPattern {
+        path: at.clone(),
+        pattern: source.clone(),
+    })
+}
+
+fn strip_fragment(uri: &str) -> &str {
+    uri.split_once('#').map_or(uri, |(uri, _)| uri)
+}
+
+/// Resolves `reference` against `base` (RFC 3986, section 5.2), leaving out the removal of
+/// dot segments, which schema identifiers have no use for.
+fn resolve(base: &str, reference: &str) -> String {
+    let base = strip_fragment(base);
+    if reference.starts_with('#') {
+        return format!("{}{}", base, reference);
+    }
+    if has_scheme(reference) {
+        return reference.to_string();
+    }
+    let scheme_end = base.find(':').filter(|_| has_scheme(base));
+    if reference.starts_with("//") {
+        return match scheme_end {
+            Some(end) => format!("{}{}", &base[..=end], reference),
+            None => reference.to_string(),
+        };
+    }
+    if reference.starts_with('/') {
+        let authority_end = base.find("://").map(|start| {
+            base[start + 3..]
+                .find('/')
+                .map_or(base.len(), |p| start + 3 + p)
+        });
+        let root = match (authority_end, scheme_end) {
+            (Some(end), _) => &base[..end],
+            (None, Some(end)) => &base[..=end],
+            (None, None) => "",
+        };
+        return format!("{}{}", root, reference);
+    }
+    let directory = base.rfind('/').map_or("", |p| &base[..=p]);
+    format!("{}{}", directory, reference)
+}
+
+fn has_scheme(uri: &str) -> bool {
+    match uri.find(':') {
+        Some(end) => {
+            let scheme = &uri[..end];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Decodes the `%XX` escapes of a URI fragment; `None` if they don't make valid UTF-8.
+fn percent_decode(fragment: &str) -> Option<String> {
+    let bytes = fragment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = fragment.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,104 @@
+//! JSON Schema validation (draft 2020-12). A schema is compiled once from a parsed
+//! `JsValue` and can then validate any number of instances, reporting every violation with
+//! where it is in the instance and which keyword of the schema it broke.
+//!
+//! Supported are the core, applicator and validation vocabularies: `$ref`, `$defs`, `$id`
+//! and `$anchor`; `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else`,
+//! `dependentSchemas`, `properties`, `patternProperties`, `additionalProperties`,
+//! `propertyNames`, `prefixItems`, `items` and `contains`; and the type, numeric, string,
+//! array and object assertions. `$ref` only resolves within the schema document;
+//! `$dynamicRef` resolves like `$ref`. A chain of `$ref`s that leads back to where it
+//! started without moving into the instance is taken as satisfied. `format` and the
+//! `unevaluated*` keywords are not checked.
+
+mod compile;
+mod validate;
+
+use crate::json::{JsValue, JsonPointer};
+use compile::{Compiler, Node};
+use std::fmt::{self, Display, Formatter};
+use validate::Validator;
+
+/// A compiled schema.
+#[derive(Clone, Debug)]
+pub struct Schema {
+    nodes: Vec<Node>,
+}
+
+/// Why a schema didn't compile. `path` points to the offending keyword in the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaError {
+    /// A keyword has a value of the wrong type, or one out of range.
+    InvalidKeyword { path: JsonPointer, reason: String },
+    /// A `pattern` or `patternProperties` regex that doesn't compile.
+    InvalidPattern { path: JsonPointer, pattern: String },
+    /// A `$ref` that doesn't lead to a schema within the document.
+    UnresolvedRef {
+        path: JsonPointer,
+        reference: String,
+    },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidKeyword { path, reason } => {
+                write!(f, "invalid keyword at `{}`: {}", path, reason)
+            }
+            SchemaError::InvalidPattern { path, pattern } => {
+                write!(f, "invalid regex `{}` at `{}`", pattern, path)
+            }
+            SchemaError::UnresolvedRef { path, reference } => {
+                write!(f, "cannot resolve `{}` at `{}`", reference, path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// One way in which an instance doesn't conform to a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// Where the offending value is in the instance.
+    pub instance_path: JsonPointer,
+    /// The keyword that rejected it, in the schema document. Keywords reached through a
+    /// `$ref` are given where they are written, not by the path through the reference.
+    pub schema_path: JsonPointer,
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}`: {} (schema `{}`)",
+            self.instance_path, self.message, self.schema_path
+        )
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Schema {
+    pub fn compile(schema: &JsValue) -> Result<Self, SchemaError> {
+        Ok(Self {
+            nodes: Compiler::new(schema).run()?,
+        })
+    }
+
+    /// Checks `instance`, returning every violation found.
+    pub fn validate(&self, instance: &JsValue) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator::new(&self.nodes, true);
+        if validator.check(0, instance, &mut JsonPointer::root()) {
+            Ok(())
+        } else {
+            Err(validator.into_errors())
+        }
+    }
+
+    /// Like `validate`, but stops at the first violation.
+    pub fn is_valid(&self, instance: &JsValue) -> bool {
+        Validator::new(&self.nodes, false).check(0, instance, &mut JsonPointer::root())
+    }
+}
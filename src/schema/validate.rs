@@ -0,0 +1,498 @@
+use crate::json::{JsValue, JsonPointer, Num};
+use crate::schema::compile::{Keyword, Kind, Node};
+use crate::schema::ValidationError;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Walks an instance through the compiled nodes. With `collect` off it gives up at the first
+/// failure and records nothing, which is also how subschemas whose failures don't count on
+/// their own (`anyOf` branches, `if`, `not`, `contains`) are probed.
+pub(crate) struct Validator<'n> {
+    nodes: &'n [Node],
+    collect: bool,
+    errors: Vec<ValidationError>,
+    /// The `$ref`s being followed, by target node and instance. Reaching one of them again
+    /// means the references loop without moving into the instance, which adds nothing.
+    following: HashSet<(usize, *const JsValue)>,
+}
+
+impl<'n> Validator<'n> {
+    pub(crate) fn new(nodes: &'n [Node], collect: bool) -> Self {
+        Self {
+            nodes,
+            collect,
+            errors: vec![],
+            following: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+
+    /// Whether `instance`, found at `at`, conforms to node `node`.
+    pub(crate) fn check(&mut self, node: usize, instance: &JsValue, at: &mut JsonPointer) -> bool {
+        let nodes = self.nodes;
+        let node = &nodes[node];
+        let mut valid = true;
+        for keyword in &node.keywords {
+            if !self.keyword(node, keyword, instance, at) {
+                valid = false;
+                if !self.collect {
+                    break;
+                }
+            }
+        }
+        valid
+    }
+
+    /// Checks without recording anything.
+    fn probe(&mut self, node: usize, instance: &JsValue, at: &mut JsonPointer) -> bool {
+        let collect = std::mem::replace(&mut self.collect, false);
+        let valid = self.check(node, instance, at);
+        self.collect = collect;
+        valid
+    }
+
+    /// Checks the member or element `token` of the instance at `at`.
+    fn check_child(
+        &mut self,
+        node: usize,
+        instance: &JsValue,
+        at: &mut JsonPointer,
+        token: &str,
+    ) -> bool {
+        at.push(token);
+        let valid = self.check(node, instance, at);
+        at.pop();
+        valid
+    }
+
+    /// Checks every `(token, instance)` against its node, going on past failures only when
+    /// collecting.
+    fn check_all<'v>(
+        &mut self,
+        at: &mut JsonPointer,
+        children: impl Iterator<Item = (String, usize, &'v JsValue)>,
+    ) -> bool {
+        let mut valid = true;
+        for (token, node, instance) in children {
+            if !self.check_child(node, instance, at, &token) {
+                valid = false;
+                if !self.collect {
+                    break;
+                }
+            }
+        }
+        valid
+    }
+
+    fn fail(&mut self, node: &Node, keyword: &Keyword, at: &JsonPointer, message: String) -> bool {
+        if self.collect {
+            let mut schema_path = node.location.clone();
+            if !matches!(keyword, Keyword::False) {
+                schema_path.push(keyword.name());
+            }
+            self.errors.push(ValidationError {
+                instance_path: at.clone(),
+                schema_path,
+                message,
+            });
+        }
+        false
+    }
+
+    /// Checks a number against a `minimum`/`maximum`-style limit, failing when `violates`
+    /// holds for how the number compares to it; `relation` describes that in the message.
+    fn bound(
+        &mut self,
+        node: &Node,
+        keyword: &Keyword,
+        at: &JsonPointer,
+        (n, limit): (&Num, &Num),
+        relation: &str,
+        violates: fn(Ordering) -> bool,
+    ) -> bool {
+        if !n.compare(limit).is_some_and(violates) {
+            return true;
+        }
+        let message = format!("{} is {} {}", number(n), relation, number(limit));
+        self.fail(node, keyword, at, message)
+    }
+
+    fn keyword(
+        &mut self,
+        node: &Node,
+        keyword: &Keyword,
+        instance: &JsValue,
+        at: &mut JsonPointer,
+    ) -> bool {
+        let fail = |this: &mut Self, at: &JsonPointer, message: String| {
+            this.fail(node, keyword, at, message)
+        };
+        match (keyword, instance) {
+            (Keyword::False, _) => fail(self, at, "no value is allowed here".into()),
+            (Keyword::Ref(_, target), _) => {
+                let key = (*target, instance as *const JsValue);
+                if !self.following.insert(key) {
+                    return true;
+                }
+                let valid = self.check(*target, instance, at);
+                self.following.remove(&key);
+                valid
+            }
+            (Keyword::Type(kinds), _) => {
+                if kinds.iter().any(|&kind| is_kind(instance, kind)) {
+                    return true;
+                }
+                let expected = kinds.iter().map(|k| k.name()).collect::<Vec<_>>();
+                let message = format!(
+                    "expected {}, found {}",
+                    expected.join(" or "),
+                    kind_of(instance).name()
+                );
+                fail(self, at, message)
+            }
+            (Keyword::Enum(values), _) => {
+                if values.contains(instance) {
+                    return true;
+                }
+                fail(
+                    self,
+                    at,
+                    format!("{} is not one of the allowed values", instance),
+                )
+            }
+            (Keyword::Const(value), _) => {
+                if value == instance {
+                    return true;
+                }
+                fail(self, at, format!("expected {}, found {}", value, instance))
+            }
+            (Keyword::MultipleOf(m), JsValue::JsNumber(n)) => {
+                if is_multiple(n, m) {
+                    return true;
+                }
+                fail(
+                    self,
+                    at,
+                    format!("{} is not a multiple of {}", instance, number(m)),
+                )
+            }
+            (Keyword::Maximum(limit), JsValue::JsNumber(n)) => self.bound(
+                node,
+                keyword,
+                at,
+                (n, limit),
+                "greater than",
+                Ordering::is_gt,
+            ),
+            (Keyword::ExclusiveMaximum(limit), JsValue::JsNumber(n)) => self.bound(
+                node,
+                keyword,
+                at,
+                (n, limit),
+                "greater than or equal to",
+                Ordering::is_ge,
+            ),
+            (Keyword::Minimum(limit), JsValue::JsNumber(n)) => {
+                self.bound(node, keyword, at, (n, limit), "less than", Ordering::is_lt)
+            }
+            (Keyword::ExclusiveMinimum(limit), JsValue::JsNumber(n)) => self.bound(
+                node,
+                keyword,
+                at,
+                (n, limit),
+                "less than or equal to",
+                Ordering::is_le,
+            ),
+            (Keyword::MaxLength(max), JsValue::JsString(s)) => {
+                let len = s.chars().count() as u64;
+                if len <= *max {
+                    return true;
+                }
+                fail(
+                    self,
+                    at,
+                    format!("{} is longer than {} characters", instance, max),
+                )
+            }
+            (Keyword::MinLength(min), JsValue::JsString(s)) => {
+                let len = s.chars().count() as u64;
+                if len >= *min {
+                    return true;
+                }
+                fail(
+                    self,
+                    at,
+                    format!("{} is shorter than {} characters", instance, min),
+                )
+            }
+            (Keyword::Pattern(regex), JsValue::JsString(s)) => {
+                if regex.is_match(s) {
+                    return true;
+                }
+                fail(
+                    self,
+                    at,
+                    format!("{} does not match `{}`", instance, regex.as_str()),
+                )
+            }
+            (Keyword::MaxItems(max), JsValue::JsArray(items)) => {
+                if items.len() as u64 <= *max {
+                    return true;
+                }
+                let message = format!("{} items are more than {}", items.len(), max);
+                fail(self, at, message)
+            }
+            (Keyword::MinItems(min), JsValue::JsArray(items)) => {
+                if items.len() as u64 >= *min {
+                    return true;
+                }
+                let message = format!("{} items are fewer than {}", items.len(), min);
+                fail(self, at, message)
+            }
+            (Keyword::UniqueItems, JsValue::JsArray(items)) => {
+                let duplicate = (1..items.len())
+                    .find_map(|j| (0..j).find(|&i| items[i] == items[j]).map(|i| (i, j)));
+                match duplicate {
+                    None => true,
+                    Some((i, j)) => {
+                        let message = format!("items {} and {} are equal", i, j);
+                        fail(self, at, message)
+                    }
+                }
+            }
+            (Keyword::Contains { schema, min, max }, JsValue::JsArray(items)) => {
+                let mut found = 0;
+                for (i, item) in items.iter().enumerate() {
+                    at.push(i.to_string());
+                    found += self.probe(*schema, item, at) as u64;
+                    at.pop();
+                }
+                if found < *min {
+                    let message = format!(
+                        "{} of the items match the `contains` schema, expected at least {}",
+                        found, min
+                    );
+                    return fail(self, at, message);
+                }
+                match max {
+                    Some(max) if found > *max => {
+                        let message = format!(
+                            "{} of the items match the `contains` schema, expected at most {}",
+                            found, max
+                        );
+                        fail(self, at, message)
+                    }
+                    _ => true,
+                }
+            }
+            (Keyword::MaxProperties(max), JsValue::JsObject(members)) => {
+                if members.len() as u64 <= *max {
+                    return true;
+                }
+                let message = format!("{} properties are more than {}", members.len(), max);
+                fail(self, at, message)
+            }
+            (Keyword::MinProperties(min), JsValue::JsObject(members)) => {
+                if members.len() as u64 >= *min {
+                    return true;
+                }
+                let message = format!("{} properties are fewer than {}", members.len(), min);
+                fail(self, at, message)
+            }
+            (Keyword::Required(names), JsValue::JsObject(members)) => {
+                let missing = names
+                    .iter()
+                    .filter(|name| !members.contains_key(name))
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    return true;
+                }
+                fail(self, at, format!("missing required {}", missing.join(", ")))
+            }
+            (Keyword::DependentRequired(dependencies), JsValue::JsObject(members)) => {
+                let missing = dependencies
+                    .iter()
+                    .filter(|(name, _)| members.contains_key(name))
+                    .flat_map(|(name, required)| required.iter().map(move |r| (name, r)))
+                    .filter(|(_, required)| !members.contains_key(required))
+                    .map(|(name, required)| format!("`{}` (required by `{}`)", required, name))
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    return true;
+                }
+                fail(self, at, format!("missing {}", missing.join(", ")))
+            }
+            (Keyword::AllOf(schemas), _) => {
+                let mut valid = true;
+                for &schema in schemas {
+                    if !self.check(schema, instance, at) {
+                        valid = false;
+                        if !self.collect {
+                            break;
+                        }
+                    }
+                }
+                valid
+            }
+            (Keyword::AnyOf(schemas), _) => {
+                if schemas.iter().any(|&s| self.probe(s, instance, at)) {
+                    return true;
+                }
+                fail(self, at, "matches none of the `anyOf` schemas".into())
+            }
+            (Keyword::OneOf(schemas), _) => {
+                let matched = schemas
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &s)| self.probe(s, instance, at))
+                    .map(|(i, _)| i.to_string())
+                    .collect::<Vec<_>>();
+                match matched.len() {
+                    1 => true,
+                    0 => fail(self, at, "matches none of the `oneOf` schemas".into()),
+                    _ => {
+                        let message = format!(
+                            "matches more than one of the `oneOf` schemas ({})",
+                            matched.join(", ")
+                        );
+                        fail(self, at, message)
+                    }
+                }
+            }
+            (Keyword::Not(schema), _) => {
+                if !self.probe(*schema, instance, at) {
+                    return true;
+                }
+                fail(self, at, "matches the `not` schema".into())
+            }
+            (
+                Keyword::If {
+                    condition,
+                    then,
+                    otherwise,
+                },
+                _,
+            ) => {
+                let branch = if self.probe(*condition, instance, at) {
+                    then
+                } else {
+                    otherwise
+                };
+                match branch {
+                    Some(branch) => self.check(*branch, instance, at),
+                    None => true,
+                }
+            }
+            (Keyword::DependentSchemas(schemas), JsValue::JsObject(members)) => {
+                let mut valid = true;
+                for (_, schema) in schemas.iter().filter(|(k, _)| members.contains_key(k)) {
+                    if !self.check(*schema, instance, at) {
+                        valid = false;
+                        if !self.collect {
+                            break;
+                        }
+                    }
+                }
+                valid
+            }
+            (Keyword::Properties(schemas), JsValue::JsObject(members)) => {
+                let children = schemas
+                    .iter()
+                    .filter_map(|(k, s)| members.get(k).map(|v| (k.clone(), *s, v)));
+                self.check_all(at, children)
+            }
+            (Keyword::PatternProperties(schemas), JsValue::JsObject(members)) => {
+                let children = members.iter().flat_map(|(k, v)| {
+                    schemas
+                        .iter()
+                        .filter(|(regex, _)| regex.is_match(k))
+                        .map(move |(_, s)| (k.clone(), *s, v))
+                });
+                self.check_all(at, children)
+            }
+            (
+                Keyword::AdditionalProperties {
+                    schema,
+                    properties,
+                    patterns,
+                },
+                JsValue::JsObject(members),
+            ) => {
+                let children = members
+                    .iter()
+                    .filter(|(k, _)| !properties.contains(k))
+                    .filter(|(k, _)| !patterns.iter().any(|regex| regex.is_match(k)))
+                    .map(|(k, v)| (k.clone(), *schema, v));
+                self.check_all(at, children)
+            }
+            (Keyword::PropertyNames(schema), JsValue::JsObject(members)) => {
+                let mut valid = true;
+                for k in members.keys() {
+                    let name = JsValue::JsString(k.clone());
+                    if !self.check(*schema, &name, at) {
+                        valid = false;
+                        if !self.collect {
+                            break;
+                        }
+                    }
+                }
+                valid
+            }
+            (Keyword::PrefixItems(schemas), JsValue::JsArray(items)) => {
+                let children = schemas
+                    .iter()
+                    .zip(items)
+                    .enumerate()
+                    .map(|(i, (s, item))| (i.to_string(), *s, item));
+                self.check_all(at, children)
+            }
+            (Keyword::Items { schema, skip }, JsValue::JsArray(items)) => {
+                let children = items
+                    .iter()
+                    .enumerate()
+                    .skip(*skip)
+                    .map(|(i, item)| (i.to_string(), *schema, item));
+                self.check_all(at, children)
+            }
+            // assertions about one type ignore instances of the others
+            _ => true,
+        }
+    }
+}
+
+fn number(n: &Num) -> JsValue {
+    JsValue::JsNumber(*n)
+}
+
+fn is_multiple(n: &Num, m: &Num) -> bool {
+    if let (Some(n), Some(m)) = (n.as_i128(), m.as_i128()) {
+        return n % m == 0;
+    }
+    let quotient = n.as_f64() / m.as_f64();
+    quotient.is_finite() && (quotient - quotient.round()).abs() < 1e-9
+}
+
+fn kind_of(instance: &JsValue) -> Kind {
+    match instance {
+        JsValue::JsNull => Kind::Null,
+        JsValue::JsBool(_) => Kind::Boolean,
+        JsValue::JsObject(_) => Kind::Object,
+        JsValue::JsArray(_) => Kind::Array,
+        JsValue::JsNumber(Num::F(f)) if f.fract() != 0.0 => Kind::Number,
+        JsValue::JsNumber(_) => Kind::Integer,
+        JsValue::JsString(_) => Kind::String,
+    }
+}
+
+/// Whether `instance` is of `kind`. Integers are numbers, and so are floats without a
+/// fractional part.
+fn is_kind(instance: &JsValue, kind: Kind) -> bool {
+    match (kind_of(instance), kind) {
+        (Kind::Integer, Kind::Number) => true,
+        (actual, kind) => actual == kind,
+    }
+}
@@ -0,0 +1,329 @@
+use crate::core::events::{Event, EventReader};
+use crate::json::Num;
+use crate::serde::Error;
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::borrow::Cow;
+
+/// A serde `Deserializer` reading from an `EventReader`, one event of lookahead at a time.
+pub struct Deserializer<'de> {
+    reader: EventReader<'de>,
+    peeked: Option<Event<'de>>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(s: &'de str) -> Self {
+        Self {
+            reader: EventReader::new(s),
+            peeked: None,
+        }
+    }
+
+    /// Byte offset of the event read last.
+    pub fn offset(&self) -> usize {
+        self.reader.offset()
+    }
+
+    /// Fails unless the document has been read completely and only whitespace follows.
+    pub fn end(&mut self) -> Result<(), Error> {
+        match self.next()? {
+            None => Ok(self.reader.finish()?),
+            Some(_) => Err(de::Error::custom("trailing content after the value")),
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Event<'de>>, Error> {
+        match self.peeked.take() {
+            Some(event) => Ok(Some(event)),
+            None => Ok(self.reader.next_event()?),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Event<'de>>, Error> {
+        if self.peeked.is_none() {
+            self.peeked = self.reader.next_event()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    /// The next event, which the grammar guarantees is there.
+    fn event(&mut self) -> Result<Event<'de>, Error> {
+        self.next()?
+            .ok_or_else(|| de::Error::custom("unexpected end of input"))
+    }
+
+    /// Consumes the `]` or `}` closing a container whose visitor is done with it.
+    fn close(&mut self, end: Event<'static>, container: &str) -> Result<(), Error> {
+        if self.event()? == end {
+            Ok(())
+        } else {
+            Err(de::Error::custom(format!(
+                "expected the end of the {}",
+                container
+            )))
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.event()? {
+            Event::StartObject => {
+                let value = visitor.visit_map(Members { de: self })?;
+                self.close(Event::EndObject, "object")?;
+                Ok(value)
+            }
+            Event::StartArray => {
+                let value = visitor.visit_seq(Elements { de: self })?;
+                self.close(Event::EndArray, "array")?;
+                Ok(value)
+            }
+            Event::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Event::String(Cow::Owned(s)) => visitor.visit_string(s),
+            Event::Number(Num::U32(n)) => visitor.visit_u32(n),
+            Event::Number(Num::U64(n)) => visitor.visit_u64(n),
+            Event::Number(Num::I64(n)) => visitor.visit_i64(n),
+            Event::Number(Num::F(f)) => visitor.visit_f64(f),
+            Event::Bool(b) => visitor.visit_bool(b),
+            Event::Null => visitor.visit_unit(),
+            event => Err(de::Error::custom(format!("unexpected {:?}", event))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if matches!(self.peek()?, Some(Event::Null)) {
+            self.next()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.event()? {
+            Event::String(name) => visitor.visit_enum(Key(name)),
+            Event::StartObject => {
+                let value = visitor.visit_enum(Variant { de: self })?;
+                self.close(Event::EndObject, "object")?;
+                Ok(value)
+            }
+            _ => Err(de::Error::custom(
+                "expected a string or an object with a single member for an enum",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct Elements<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if matches!(self.de.peek()?, Some(Event::EndArray)) {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct Members<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> MapAccess<'de> for Members<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.de.peek()? {
+            Some(Event::Key(_)) => match self.de.next()? {
+                Some(Event::Key(key)) => seed.deserialize(Key(key)).map(Some),
+                _ => unreachable!("just peeked a key"),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// The `{"Variant": value}` form of an enum, positioned after the `{`.
+struct Variant<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'_, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        match self.de.event()? {
+            Event::Key(name) => Ok((seed.deserialize(Key(name))?, self)),
+            _ => Err(de::Error::custom(
+                "expected an object with a single member for an enum",
+            )),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for Variant<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+/// An object key, or the name of a unit variant. Keys can also be read as numbers, for maps
+/// keyed by integers.
+struct Key<'de>(Cow<'de, str>);
+
+macro_rules! parse_key {
+    ($($method:ident => $visit:ident),*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0.parse() {
+                Ok(n) => visitor.$visit(n),
+                Err(_) => self.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Key<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
+    }
+
+    parse_key! {
+        deserialize_i8 => visit_i8, deserialize_i16 => visit_i16, deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64, deserialize_u8 => visit_u8, deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32, deserialize_u64 => visit_u64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> EnumAccess<'de> for Key<'de> {
+    type Error = Error;
+    type Variant = UnitVariant;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        Ok((seed.deserialize(self)?, UnitVariant))
+    }
+}
+
+/// What follows a variant given as a bare string: nothing, so only unit variants fit.
+struct UnitVariant;
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
@@ -0,0 +1,108 @@
+//! serde support, behind the `serde` feature: `JsValue` implements `Serialize` and
+//! `Deserialize`, and [`from_str`] and [`to_string`] convert any serde type straight from and
+//! to JSON text. `from_str` deserializes from the events of an [`crate::EventReader`], so no
+//! `JsValue` is built in between and strings without escapes are borrowed from the input.
+//!
+//! Enums use serde's externally tagged representation, like serde_json: a unit variant is
+//! its name as a string, other variants are an object with the name as the only key.
+
+mod de;
+mod ser;
+mod value;
+
+use crate::error::ParseError;
+use std::fmt::{self, Display, Formatter};
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input isn't valid JSON. Boxed to keep the happy path's `Result` small.
+    Parse(Box<ParseError>),
+    /// The JSON doesn't fit the type, or a `Serialize` or `Deserialize` impl failed.
+    /// `offset` is the byte offset into the input of the event being deserialized when it
+    /// happened; serialization errors have none.
+    Custom {
+        message: String,
+        offset: Option<usize>,
+    },
+}
+
+impl Error {
+    /// Places an error that has no position yet at `offset`.
+    fn at(self, offset: usize) -> Self {
+        match self {
+            Error::Custom {
+                message,
+                offset: None,
+            } => Error::Custom {
+                message,
+                offset: Some(offset),
+            },
+            e => e,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(e) => e.fmt(f),
+            Error::Custom {
+                message,
+                offset: Some(offset),
+            } => write!(f, "{} (byte {})", message, offset),
+            Error::Custom { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e) => Some(e.as_ref()),
+            Error::Custom { .. } => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(Box::new(e))
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom {
+            message: msg.to_string(),
+            offset: None,
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom {
+            message: msg.to_string(),
+            offset: None,
+        }
+    }
+}
+
+/// Deserializes a `T` from the JSON document `s`. Anything but whitespace after the
+/// document is an error.
+pub fn from_str<'a, T: serde::Deserialize<'a>>(s: &'a str) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(s);
+    let value = T::deserialize(&mut deserializer).map_err(|e| e.at(deserializer.offset()))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Serializes `value` as compact JSON.
+pub fn to_string<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut serializer = Serializer::new();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
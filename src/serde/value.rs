@@ -0,0 +1,107 @@
+use crate::json::{JsValue, Map, Num};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::fmt::{self, Formatter};
+
+impl Serialize for JsValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsValue::JsNull => serializer.serialize_unit(),
+            JsValue::JsString(s) => serializer.serialize_str(s),
+            JsValue::JsNumber(Num::U32(n)) => serializer.serialize_u32(*n),
+            JsValue::JsNumber(Num::U64(n)) => serializer.serialize_u64(*n),
+            JsValue::JsNumber(Num::I64(n)) => serializer.serialize_i64(*n),
+            JsValue::JsNumber(Num::F(f)) => serializer.serialize_f64(*f),
+            JsValue::JsBool(b) => serializer.serialize_bool(*b),
+            JsValue::JsObject(members) => {
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for (k, v) in members.iter() {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            JsValue::JsArray(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsValueVisitor)
+    }
+}
+
+struct JsValueVisitor;
+
+impl<'de> Visitor<'de> for JsValueVisitor {
+    type Value = JsValue;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<JsValue, E> {
+        Ok(JsValue::JsBool(v))
+    }
+
+    /// Integers get the variant the parser would give them.
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<JsValue, E> {
+        match u64::try_from(v) {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Ok(JsValue::JsNumber(Num::I64(v))),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<JsValue, E> {
+        Ok(JsValue::JsNumber(match u32::try_from(v) {
+            Ok(v) => Num::U32(v),
+            Err(_) => Num::U64(v),
+        }))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<JsValue, E> {
+        Ok(JsValue::JsNumber(Num::F(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<JsValue, E> {
+        Ok(JsValue::JsString(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<JsValue, E> {
+        Ok(JsValue::JsString(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsValue, E> {
+        Ok(JsValue::JsNull)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsValue, E> {
+        Ok(JsValue::JsNull)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsValue, D::Error> {
+        JsValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsValue, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsValue::JsArray(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsValue, A::Error> {
+        let mut members = Map::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((k, v)) = map.next_entry::<String, JsValue>()? {
+            members.insert(k, v);
+        }
+        Ok(JsValue::JsObject(members))
+    }
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use arjer::json::{JsValue, JsValueRef};
+use arjer::{parse_borrowed, parse_borrowed_with, DuplicateKeys, ParseOptions};
+use common::parse;
+use std::borrow::Cow;
+
+/// Whether `s` is a slice of `input` rather than a copy.
+fn points_into(s: &str, input: &str) -> bool {
+    input.as_bytes().as_ptr_range().contains(&s.as_ptr())
+}
+
+fn string<'v, 'a>(value: &'v JsValueRef<'a>) -> &'v Cow<'a, str> {
+    match value {
+        JsValueRef::JsString(s) => s,
+        other => panic!("not a string: {:?}", other),
+    }
+}
+
+#[test]
+fn strings_are_borrowed_unless_escaped() {
+    let input = r#"["plain", "", "uni\u00e9", "tab\t", "é direct", "a\"b"]"#;
+    let JsValueRef::JsArray(values) = parse_borrowed(input).unwrap() else {
+        panic!("not an array");
+    };
+    let expected = [
+        ("plain", true),
+        ("", true),
+        ("unié", false),
+        ("tab\t", false),
+        ("é direct", true),
+        ("a\"b", false),
+    ];
+    assert_eq!(values.len(), expected.len());
+    for (value, (text, borrowed)) in values.iter().zip(expected) {
+        let s = string(value);
+        assert_eq!(s, text);
+        let from_input = matches!(s, Cow::Borrowed(b) if points_into(b, input));
+        assert_eq!(from_input, borrowed, "{:?}", s);
+        assert_eq!(matches!(s, Cow::Owned(_)), !borrowed, "{:?}", s);
+    }
+}
+
+#[test]
+fn keys_are_borrowed_unless_escaped() {
+    let input = r#"{"plain": "v", "esc\naped": {"inner": "w\/"}}"#;
+    let JsValueRef::JsObject(members) = parse_borrowed(input).unwrap() else {
+        panic!("not an object");
+    };
+    let keys: Vec<_> = members.keys().collect();
+    assert_eq!(keys, ["plain", "esc\naped"]);
+    assert!(matches!(keys[0], Cow::Borrowed(k) if points_into(k, input)));
+    assert!(matches!(keys[1], Cow::Owned(_)));
+    assert!(matches!(
+        string(members.get("plain").unwrap()),
+        Cow::Borrowed(v) if points_into(v, input)
+    ));
+
+    let JsValueRef::JsObject(inner) = members.get("esc\naped").unwrap() else {
+        panic!("not an object");
+    };
+    let (key, value) = inner.iter().next().unwrap();
+    assert!(matches!(key, Cow::Borrowed(k) if points_into(k, input)));
+    assert_eq!(string(value), "w/");
+    assert!(matches!(string(value), Cow::Owned(_)));
+}
+
+#[test]
+fn into_owned_is_the_same_as_parse() {
+    for input in [
+        "null",
+        "true",
+        "-0",
+        "18446744073709551615",
+        "1.5e3",
+        r#""😀 and \\ back""#,
+        r#"{"a": [1, {"b": null}], "c\td": "e", "": ""}"#,
+        r#"[[], {}, [{}], "x", false]"#,
+    ] {
+        assert_eq!(
+            format!("{:?}", parse_borrowed(input).unwrap().into_owned()),
+            format!("{:?}", parse(input)),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn into_owned_keeps_duplicate_keys() {
+    let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+    for duplicate_keys in [
+        DuplicateKeys::KeepFirst,
+        DuplicateKeys::KeepLast,
+        DuplicateKeys::CollectAll,
+    ] {
+        let opts = ParseOptions {
+            duplicate_keys,
+            ..ParseOptions::default()
+        };
+        let owned = parse_borrowed_with(input, &opts).unwrap().into_owned();
+        let JsValue::JsObject(members) = &owned else {
+            panic!("not an object");
+        };
+        let JsValue::JsObject(expected) = arjer::parse_with(input, &opts).unwrap() else {
+            panic!("not an object");
+        };
+        assert_eq!(
+            format!("{:?}", members.iter().collect::<Vec<_>>()),
+            format!("{:?}", expected.iter().collect::<Vec<_>>()),
+            "{:?}",
+            duplicate_keys
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// Every test crate compiles its own copy of this module and uses only part of it.
+#![allow(dead_code)]
+
+use arjer::json::JsValue;
+
+/// Parses `input`, which the test expects to be valid JSON.
+pub fn parse(input: &str) -> JsValue {
+    arjer::parse(input).unwrap()
+}
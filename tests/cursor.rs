@@ -0,0 +1,262 @@
+mod common;
+
+use arjer::convert::FromJsonError;
+use arjer::error::PathSegment::{self, Index, Key};
+use arjer::json::{CursorError, JsValue};
+use common::parse;
+
+fn job() -> JsValue {
+    parse(
+        r#"{
+            "job": {
+                "title": "dev",
+                "details": [{"level": 1}, {"level": 2}, {"level": "three"}, []],
+                "tags": ["a", 2]
+            }
+        }"#,
+    )
+}
+
+#[test]
+fn missing_key_carries_the_path_of_its_object() {
+    let doc = job();
+    let e = doc
+        .cursor()
+        .obj("job")
+        .arr("details".into())
+        .nth(1)
+        .field::<u32>("rank")
+        .unwrap_err();
+    assert!(matches!(&e, CursorError::MissingKey { key, .. } if key == "rank"));
+    assert_eq!(e.json_path(), "$.job.details[1]");
+    assert_eq!(e.to_string(), "missing key `rank` at $.job.details[1]");
+
+    let e = doc.cursor().obj("job").obj("salary").get().unwrap_err();
+    assert_eq!(e.to_string(), "missing key `salary` at $.job");
+    let e = doc.cursor().obj("nope").obj("job").get().unwrap_err();
+    assert_eq!(e.to_string(), "missing key `nope` at $");
+}
+
+#[test]
+fn type_mismatch_carries_the_path_of_the_value() {
+    let doc = job();
+    let details = || doc.cursor().obj("job").arr("details".into());
+    let e = details().nth(2).field::<u32>("level").unwrap_err();
+    assert_eq!(
+        e,
+        CursorError::TypeMismatch {
+            expected: "integer",
+            found: "string",
+            path: vec![
+                Key("job".into()),
+                Key("details".into()),
+                Index(2),
+                Key("level".into())
+            ],
+        }
+    );
+    assert_eq!(
+        e.to_string(),
+        "expected integer, found string at $.job.details[2].level"
+    );
+    // a step into a value of the wrong kind
+    let e = details().nth(3).field::<u32>("level").unwrap_err();
+    assert!(matches!(
+        &e,
+        CursorError::TypeMismatch {
+            expected: "object",
+            found: "array",
+            ..
+        }
+    ));
+    assert_eq!(e.json_path(), "$.job.details[3]");
+    let e = doc.cursor().obj("job").obj("title").get().unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "expected object, found string at $.job.title"
+    );
+    let e = doc
+        .cursor()
+        .obj("job")
+        .obj("title")
+        .nth(0)
+        .get()
+        .unwrap_err();
+    assert_eq!(e.json_path(), "$.job.title");
+    // elements converted along with their container report their own index
+    let e = doc
+        .cursor()
+        .obj("job")
+        .field::<Vec<String>>("tags")
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "expected string, found number at $.job.tags[1]"
+    );
+}
+
+#[test]
+fn index_out_of_bounds_carries_the_path_of_the_array() {
+    let doc = job();
+    let e = doc
+        .cursor()
+        .obj("job")
+        .arr("details".into())
+        .nth(4)
+        .field::<u32>("level")
+        .unwrap_err();
+    assert_eq!(
+        e,
+        CursorError::IndexOutOfBounds {
+            index: 4,
+            len: 4,
+            path: vec![Key("job".into()), Key("details".into())],
+        }
+    );
+    assert_eq!(
+        e.to_string(),
+        "index 4 out of bounds for an array of 4 elements at $.job.details"
+    );
+    let e = doc.cursor().obj("job").arr("tags".into()).at::<String>(2);
+    assert_eq!(e.unwrap_err().json_path(), "$.job.tags");
+    // the first failure is kept however many steps follow it
+    let e = doc.cursor().nth(0).obj("job").nth(9).get().unwrap_err();
+    assert_eq!(e.to_string(), "expected array, found object at $");
+}
+
+fn doc() -> JsValue {
+    parse(
+        r#"{
+            "config": {
+                "small": 7,
+                "big": 5000000000,
+                "negative": -3,
+                "ratio": 0.5,
+                "name": "svc",
+                "verbose": true,
+                "nothing": null,
+                "ports": [80, 443]
+            }
+        }"#,
+    )
+}
+
+#[test]
+fn integers_widen_to_any_type_that_holds_them() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().field::<u32>("small"), Ok(7));
+    assert_eq!(config().field::<u64>("small"), Ok(7));
+    assert_eq!(config().field::<i64>("small"), Ok(7));
+    assert_eq!(config().field::<u8>("small"), Ok(7));
+    assert_eq!(config().field::<f64>("small"), Ok(7.0));
+    assert_eq!(config().field::<u64>("big"), Ok(5_000_000_000));
+    assert_eq!(config().field::<i64>("big"), Ok(5_000_000_000));
+    assert_eq!(config().field::<f64>("big"), Ok(5e9));
+    assert_eq!(config().field::<i32>("negative"), Ok(-3));
+    assert_eq!(config().field::<f64>("ratio"), Ok(0.5));
+}
+
+#[test]
+fn narrowing_out_of_range_fails() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    let e = config().field::<u32>("big").unwrap_err();
+    assert_eq!(e.json_path(), "$.config.big");
+    assert!(matches!(
+        &e,
+        CursorError::Conversion(FromJsonError::OutOfRange { target: "u32", .. })
+    ));
+    assert_eq!(e.to_string(), "number out of range for u32 at $.config.big");
+    assert!(matches!(
+        config().field::<u64>("negative").unwrap_err(),
+        CursorError::Conversion(FromJsonError::OutOfRange { target: "u64", .. })
+    ));
+    assert_eq!(
+        config().field::<u32>("ratio").unwrap_err(),
+        CursorError::TypeMismatch {
+            expected: "integer",
+            found: "number",
+            path: config_path("ratio"),
+        }
+    );
+}
+
+fn config_path(key: &str) -> Vec<PathSegment> {
+    vec![Key("config".into()), Key(key.into())]
+}
+
+#[test]
+fn other_types() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().field::<String>("name"), Ok("svc".to_string()));
+    assert_eq!(config().field::<bool>("verbose"), Ok(true));
+    assert_eq!(config().field::<Vec<u16>>("ports"), Ok(vec![80, 443]));
+    assert_eq!(config().arr("ports".into()).at::<u16>(1), Ok(443));
+}
+
+#[test]
+fn opt_is_none_for_missing_and_null() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().opt::<u32>("absent"), Ok(None));
+    assert_eq!(config().opt::<u32>("nothing"), Ok(None));
+    assert_eq!(config().opt::<u32>("small"), Ok(Some(7)));
+    assert!(config().opt::<u32>("name").is_err());
+    // a missing parent is still an error
+    assert!(matches!(
+        doc.cursor().obj("absent").opt::<u32>("x"),
+        Err(CursorError::MissingKey { .. })
+    ));
+}
+
+#[test]
+fn or_default_fills_in_missing_and_null() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().or_default::<u32>("absent"), Ok(0));
+    assert_eq!(config().or_default::<String>("nothing"), Ok(String::new()));
+    assert_eq!(config().or_default::<Vec<u8>>("absent"), Ok(vec![]));
+    assert_eq!(config().or_default::<u32>("small"), Ok(7));
+    assert!(config().or_default::<bool>("small").is_err());
+}
+
+#[test]
+fn field_of_option_is_opt() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().field::<Option<bool>>("absent"), Ok(None));
+    assert_eq!(config().field::<Option<bool>>("verbose"), Ok(Some(true)));
+    assert!(matches!(
+        config().field::<bool>("absent"),
+        Err(CursorError::MissingKey { key, .. }) if key == "absent"
+    ));
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_accessors_keep_their_strict_matching() {
+    let doc = doc();
+    let config = || doc.cursor().obj("config");
+    assert_eq!(config().string("name"), Ok("svc".to_string()));
+    assert_eq!(config().boolean("verbose"), Ok(true));
+    assert_eq!(config().num_u32("small"), Ok(7));
+    assert_eq!(config().num_u64("big"), Ok(5_000_000_000));
+    assert_eq!(config().num_f64("ratio"), Ok(0.5));
+    // a number is only read back the way it was parsed, unlike with `field`
+    assert_eq!(
+        config().num_u64("small").unwrap_err(),
+        CursorError::TypeMismatch {
+            expected: "u64",
+            found: "number",
+            path: config_path("small"),
+        }
+    );
+    assert!(config().num_f64("small").is_err());
+    assert!(config().num_u32("big").is_err());
+    assert!(matches!(
+        config().num_u32("absent"),
+        Err(CursorError::MissingKey { key, .. }) if key == "absent"
+    ));
+}
@@ -0,0 +1,94 @@
+use arjer::error::ParseError;
+use arjer::ParseOptions;
+
+/// The configured depth and the offset a `DepthLimitExceeded` reports.
+fn depth_error(e: ParseError) -> (usize, usize) {
+    match e {
+        ParseError::DepthLimitExceeded { depth, at } => (depth, at.offset),
+        other => panic!("expected DepthLimitExceeded, got {:?}", other),
+    }
+}
+
+#[test]
+fn deep_arrays_fail_at_the_first_bracket_past_the_limit() {
+    let input = "[".repeat(100_000);
+    let max = ParseOptions::default().max_depth;
+    assert_eq!(depth_error(arjer::parse(&input).unwrap_err()), (max, max));
+    assert_eq!(
+        depth_error(arjer::parse_borrowed(&input).unwrap_err()),
+        (max, max)
+    );
+    assert_eq!(
+        depth_error(arjer::from_reader(input.as_bytes()).unwrap_err()),
+        (max, max)
+    );
+}
+
+#[test]
+fn deep_objects_fail_at_the_first_brace_past_the_limit() {
+    let input = "{\"a\":".repeat(100_000);
+    let max = ParseOptions::default().max_depth;
+    let offset = max * "{\"a\":".len();
+    assert_eq!(
+        depth_error(arjer::parse(&input).unwrap_err()),
+        (max, offset)
+    );
+    let from_events = arjer::events(&input).find_map(Result::err).unwrap();
+    assert_eq!(depth_error(from_events), (max, offset));
+}
+
+#[test]
+fn custom_max_depth_is_honoured() {
+    let opts = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::default()
+    };
+    assert!(arjer::parse_with("[[1], {\"a\": 2}]", &opts).is_ok());
+    assert_eq!(
+        depth_error(arjer::parse_with("[[[1]]]", &opts).unwrap_err()),
+        (2, 2)
+    );
+    assert_eq!(
+        depth_error(arjer::parse_with(r#"{"a": {"b": {"c": 1}}}"#, &opts).unwrap_err()),
+        (2, 12)
+    );
+
+    let opts = ParseOptions {
+        max_depth: 1000,
+        ..ParseOptions::default()
+    };
+    let input = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    assert!(arjer::parse_with(&input, &opts).is_ok());
+    let deeper = format!("[{}]", input);
+    assert_eq!(
+        depth_error(arjer::parse_with(&deeper, &opts).unwrap_err()),
+        (1000, 1000)
+    );
+}
+
+#[test]
+fn depth_error_reports_the_path() {
+    let opts = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::default()
+    };
+    let error = arjer::parse_with(r#"{"a": [0, [1]]}"#, &opts).unwrap_err();
+    assert_eq!(error.location().offset, 10);
+    assert!(error.to_string().contains("$.a[1]"), "{}", error);
+}
+
+#[test]
+fn nesting_deeper_than_the_native_stack() {
+    let depth = 10_000;
+    let opts = ParseOptions {
+        max_depth: depth,
+        ..ParseOptions::default()
+    };
+    let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(arjer::parse_with(&input, &opts).is_ok());
+    let deeper = format!("[{}]", input);
+    assert_eq!(
+        depth_error(arjer::parse_with(&deeper, &opts).unwrap_err()),
+        (depth, depth)
+    );
+}
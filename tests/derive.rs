@@ -0,0 +1,340 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use arjer::convert::FromJsonError;
+use arjer::error::PathSegment;
+use arjer::json::JsValue;
+use arjer::{FromJson, ToJson};
+use common::parse;
+use std::collections::{BTreeMap, HashMap};
+
+fn from<T: FromJson>(input: &str) -> Result<T, FromJsonError> {
+    T::from_json(&parse(input))
+}
+
+fn key(k: &str) -> PathSegment {
+    PathSegment::Key(k.into())
+}
+
+#[derive(Debug, Default, PartialEq, FromJson, ToJson)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+fn default_level() -> u8 {
+    3
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Job {
+    title: String,
+    #[json(rename = "self_employed", default)]
+    independent: bool,
+    #[json(skip)]
+    cached: Option<u64>,
+    #[json(default = "default_level")]
+    level: u8,
+    #[json(flatten)]
+    address: Address,
+    r#type: String,
+}
+
+fn unset() -> Option<u64> {
+    Some(0)
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Cache {
+    key: String,
+    #[json(skip, default = "unset")]
+    hits: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Team {
+    jobs: Vec<Job>,
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Marker;
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+enum Color {
+    Red,
+    #[json(rename = "green")]
+    Green,
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Tagged<T> {
+    tags: Vec<T>,
+}
+
+#[test]
+fn skipped_fields_take_their_default_function() {
+    // even when the input has the member
+    let cache: Cache = from(r#"{"key":"k","hits":7}"#).unwrap();
+    assert_eq!(
+        cache,
+        Cache {
+            key: "k".into(),
+            hits: Some(0)
+        }
+    );
+    assert_eq!(cache.to_json().to_string(), r#"{"key":"k"}"#);
+}
+
+#[test]
+fn rename_default_skip_and_flatten() {
+    let job: Job =
+        from(r#"{"title":"dev","self_employed":true,"cached":5,"city":"Oslo","type":"x"}"#)
+            .unwrap();
+    assert_eq!(
+        job,
+        Job {
+            title: "dev".into(),
+            independent: true,
+            cached: None,
+            level: 3,
+            address: Address {
+                city: "Oslo".into(),
+                zip: None
+            },
+            r#type: "x".into(),
+        }
+    );
+    // the renamed member is the only one read, and the defaults fill in what's missing
+    let job: Job = from(
+        r#"{"title":"dev","independent":true,"level":7,"city":"Oslo","zip":"0150","type":"x"}"#,
+    )
+    .unwrap();
+    assert!(!job.independent);
+    assert_eq!(job.level, 7);
+    assert_eq!(job.address.zip.as_deref(), Some("0150"));
+
+    let written = Job {
+        cached: Some(9),
+        ..job
+    }
+    .to_json();
+    assert_eq!(
+        written.to_string(),
+        r#"{"title":"dev","self_employed":false,"level":7,"city":"Oslo","zip":"0150","type":"x"}"#
+    );
+}
+
+#[test]
+fn missing_and_mistyped_fields_name_their_path() {
+    assert_eq!(
+        from::<Job>(r#"{"city":"Oslo","type":"x"}"#),
+        Err(FromJsonError::MissingField {
+            field: "title".into(),
+            path: vec![]
+        })
+    );
+    assert_eq!(
+        from::<Job>(r#"{"title":1,"city":"Oslo","type":"x"}"#),
+        Err(FromJsonError::TypeMismatch {
+            expected: "string",
+            found: "number",
+            path: vec![key("title")]
+        })
+    );
+    assert_eq!(
+        from::<Job>(r#"{"title":"a","level":300,"city":"Oslo","type":"x"}"#),
+        Err(FromJsonError::OutOfRange {
+            target: "u8",
+            path: vec![key("level")]
+        })
+    );
+
+    let job = r#"{"title":"a","city":"Oslo","type":"x"}"#;
+    let error = from::<Team>(&format!(
+        r#"{{"jobs":[{},{{"title":"b","type":"y"}}]}}"#,
+        job
+    ))
+    .unwrap_err();
+    assert_eq!(error.json_path(), "$.jobs[1]");
+    assert_eq!(error.to_string(), "missing field `city` at $.jobs[1]");
+    let error = from::<Team>(&format!(
+        r#"{{"jobs":[{},{{"title":"b","city":[],"type":"y"}}]}}"#,
+        job
+    ))
+    .unwrap_err();
+    assert_eq!(
+        error.path(),
+        [key("jobs"), PathSegment::Index(1), key("city")]
+    );
+    assert_eq!(
+        error.to_string(),
+        "expected string, found array at $.jobs[1].city"
+    );
+    assert_eq!(
+        from::<Team>("[]").unwrap_err().to_string(),
+        "expected object, found array at $"
+    );
+}
+
+#[test]
+fn tuple_unit_and_enum_types() {
+    assert_eq!(from::<Point>("[1,-2]"), Ok(Point(1, -2)));
+    assert_eq!(Point(1, -2).to_json(), parse("[1,-2]"));
+    assert_eq!(
+        from::<Point>("[1,2,3]"),
+        Err(FromJsonError::WrongLength {
+            expected: 2,
+            found: 3,
+            path: vec![]
+        })
+    );
+    assert_eq!(from::<Point>(r#"[1,"2"]"#).unwrap_err().json_path(), "$[1]");
+
+    assert_eq!(from::<Meters>("1.5"), Ok(Meters(1.5)));
+    assert_eq!(Meters(2.0).to_json(), parse("2.0"));
+    assert_eq!(from::<Marker>("null"), Ok(Marker));
+    assert_eq!(Marker.to_json(), JsValue::JsNull);
+    assert!(from::<Marker>("0").is_err());
+
+    assert_eq!(from::<Color>(r#""Red""#), Ok(Color::Red));
+    assert_eq!(from::<Color>(r#""green""#), Ok(Color::Green));
+    assert_eq!(Color::Green.to_json(), parse(r#""green""#));
+    assert_eq!(
+        from::<Color>(r#""Green""#),
+        Err(FromJsonError::UnknownVariant {
+            variant: "Green".into(),
+            path: vec![]
+        })
+    );
+    assert_eq!(
+        from::<Color>("1").unwrap_err().to_string(),
+        "expected string, found number at $"
+    );
+
+    let tagged: Tagged<char> = from(r#"{"tags":["a","b"]}"#).unwrap();
+    assert_eq!(tagged.tags, ['a', 'b']);
+    assert_eq!(tagged.to_json(), parse(r#"{"tags":["a","b"]}"#));
+}
+
+#[test]
+fn primitives() {
+    assert_eq!(from::<bool>("true"), Ok(true));
+    assert_eq!(from::<String>(r#""a\nb""#), Ok("a\nb".into()));
+    assert_eq!(from::<char>(r#""é""#), Ok('é'));
+    assert!(from::<char>(r#""ab""#).is_err());
+    assert_eq!(from::<u64>("18446744073709551615"), Ok(u64::MAX));
+    assert_eq!(from::<i64>("-9223372036854775808"), Ok(i64::MIN));
+    assert_eq!(from::<i8>("-128"), Ok(-128));
+    assert_eq!(
+        from::<u32>("-1"),
+        Err(FromJsonError::OutOfRange {
+            target: "u32",
+            path: vec![]
+        })
+    );
+    assert_eq!(
+        from::<i32>("1.0"),
+        Err(FromJsonError::TypeMismatch {
+            expected: "integer",
+            found: "number",
+            path: vec![]
+        })
+    );
+    assert_eq!(from::<f64>("3"), Ok(3.0));
+    assert_eq!(from::<f32>("0.5"), Ok(0.5));
+    assert!(from::<f32>("1e300").is_err());
+    assert_eq!(from::<()>("null"), Ok(()));
+    assert_eq!(from::<JsValue>("[1]"), Ok(parse("[1]")));
+
+    assert_eq!(u64::MAX.to_json(), parse("18446744073709551615"));
+    assert_eq!((-5i16).to_json(), parse("-5"));
+    assert_eq!('x'.to_json(), parse(r#""x""#));
+    assert_eq!("s".to_json(), parse(r#""s""#));
+    assert_eq!(0.25f32.to_json(), parse("0.25"));
+}
+
+#[test]
+fn options() {
+    #[derive(Debug, PartialEq, FromJson, ToJson)]
+    struct Maybe {
+        a: Option<u8>,
+        b: Option<Option<u8>>,
+    }
+    assert_eq!(from::<Option<u8>>("null"), Ok(None));
+    assert_eq!(from::<Option<u8>>("4"), Ok(Some(4)));
+    assert_eq!(from::<Maybe>("{}"), Ok(Maybe { a: None, b: None }));
+    assert_eq!(
+        from::<Maybe>(r#"{"a":1,"b":2}"#),
+        Ok(Maybe {
+            a: Some(1),
+            b: Some(Some(2))
+        })
+    );
+    assert_eq!(
+        from::<Maybe>(r#"{"a":"1"}"#).unwrap_err().json_path(),
+        "$.a"
+    );
+    assert_eq!(
+        Maybe { a: None, b: None }.to_json(),
+        parse(r#"{"a":null,"b":null}"#)
+    );
+}
+
+#[test]
+fn containers() {
+    assert_eq!(from::<Vec<u8>>("[1,2]"), Ok(vec![1, 2]));
+    assert_eq!(from::<Vec<u8>>("[1,256]").unwrap_err().json_path(), "$[1]");
+    assert_eq!(vec![Some(1), None].to_json(), parse("[1,null]"));
+    assert_eq!([true; 2].to_json(), parse("[true,true]"));
+
+    let map: HashMap<String, Vec<i32>> = from(r#"{"a":[1],"b":[]}"#).unwrap();
+    assert_eq!(map["a"], [1]);
+    assert!(map["b"].is_empty());
+    assert_eq!(map.to_json(), parse(r#"{"b":[],"a":[1]}"#));
+    // whatever order the map iterates in, the output is sorted by key
+    let many: HashMap<String, usize> = (0..50).map(|i| (format!("k{:02}", i), i)).collect();
+    let written = many.to_json().to_string();
+    let sorted: BTreeMap<_, _> = many.into_iter().collect();
+    assert_eq!(written, sorted.to_json().to_string());
+    assert_eq!(
+        from::<HashMap<String, i32>>(r#"{"a":1,"b":"2"}"#)
+            .unwrap_err()
+            .json_path(),
+        "$.b"
+    );
+    let sorted: BTreeMap<String, bool> = from(r#"{"b":true,"a":false}"#).unwrap();
+    assert_eq!(sorted.to_json().to_string(), r#"{"a":false,"b":true}"#);
+
+    assert_eq!(
+        from::<(u8, String, Option<bool>)>(r#"[1,"a",null]"#),
+        Ok((1, "a".into(), None))
+    );
+    assert_eq!((1, "a", [2.5]).to_json(), parse(r#"[1,"a",[2.5]]"#));
+    assert_eq!(
+        from::<(u8, u8)>("[1]"),
+        Err(FromJsonError::WrongLength {
+            expected: 2,
+            found: 1,
+            path: vec![]
+        })
+    );
+    assert_eq!(
+        from::<(u8, (u8, u8))>("[1,[2,-3]]")
+            .unwrap_err()
+            .json_path(),
+        "$[1][1]"
+    );
+    assert_eq!(from::<Box<u8>>("1"), Ok(Box::new(1)));
+}
+
+#[test]
+fn bad_attributes_do_not_compile() {
+    trybuild::TestCases::new().compile_fail("tests/ui/derive/*.rs");
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use arjer::diff::{diff, diff_with, Change, DiffOptions};
+use arjer::error::PathSegment;
+use common::parse;
+
+#[test]
+fn the_report_aligns_arrays_like_patch_diff() {
+    // see `shifted_elements_are_kept_on_a_tie` in tests/patch.rs
+    let (left, right) = (parse("[1,2]"), parse("[2,3]"));
+    let report = diff(&left, &right);
+    let changes: Vec<_> = report.iter().map(|d| (d.json_path(), &d.change)).collect();
+    assert_eq!(
+        changes,
+        [
+            ("$[0]".to_string(), &Change::Removed(&parse("1"))),
+            ("$[1]".to_string(), &Change::Added(&parse("3"))),
+        ]
+    );
+    assert_eq!(report.differences()[1].path, [PathSegment::Index(1)]);
+}
+
+/// `(path, change)` for every difference between `left` and `right` under `opts`, with the
+/// values printed compactly.
+fn changes(left: &str, right: &str, opts: &DiffOptions) -> Vec<(String, String)> {
+    let (left, right) = (parse(left), parse(right));
+    diff_with(&left, &right, opts)
+        .iter()
+        .map(|d| {
+            let change = match &d.change {
+                Change::Added(v) => format!("+{}", v),
+                Change::Removed(v) => format!("-{}", v),
+                Change::Changed { left, right } => format!("{} -> {}", left, right),
+            };
+            (d.json_path(), change)
+        })
+        .collect()
+}
+
+fn expected(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(p, c)| (p.to_string(), c.to_string()))
+        .collect()
+}
+
+#[test]
+fn unordered_arrays_compare_as_multisets() {
+    let unordered = DiffOptions {
+        ordered_arrays: false,
+        ..DiffOptions::default()
+    };
+    let (left, right) = (
+        r#"{"tags":["a","b","c"],"n":[1,2,2],"m":[[1,2],{"k":[3,4]}]}"#,
+        r#"{"tags":["c","a","b"],"n":[2,1,3],"m":[{"k":[4,3]},[2,1]]}"#,
+    );
+    assert_eq!(
+        changes(left, right, &unordered),
+        expected(&[("$.n[2]", "-2"), ("$.n[2]", "+3")])
+    );
+    assert!(diff_with(&parse("[1,1,2]"), &parse("[2,1,1]"), &unordered).is_empty());
+    assert!(!diff_with(&parse("[1,1]"), &parse("[1]"), &unordered).is_empty());
+
+    // in order, the same arrays differ
+    assert_eq!(
+        changes(
+            r#"["a","b","c"]"#,
+            r#"["c","a","b"]"#,
+            &DiffOptions::default()
+        ),
+        expected(&[("$[0]", r#"+"c""#), ("$[2]", r#"-"c""#)])
+    );
+}
+
+#[test]
+fn float_tolerance() {
+    let (left, right) = (
+        r#"{"x":1.0,"y":[0.1,2],"z":3,"i":1}"#,
+        r#"{"x":1.0005,"y":[0.1000001,2],"z":3.0001,"i":2}"#,
+    );
+    let tolerant = DiffOptions {
+        float_tolerance: 0.001,
+        ..DiffOptions::default()
+    };
+    // integers are compared exactly whatever the tolerance
+    assert_eq!(
+        changes(left, right, &tolerant),
+        expected(&[("$.i", "1 -> 2")])
+    );
+    assert_eq!(
+        changes(left, right, &DiffOptions::default()),
+        expected(&[
+            ("$.x", "1.0 -> 1.0005"),
+            ("$.y[0]", "0.1 -> 0.1000001"),
+            ("$.z", "3 -> 3.0001"),
+            ("$.i", "1 -> 2"),
+        ])
+    );
+    let loose = DiffOptions {
+        float_tolerance: 10.0,
+        ..DiffOptions::default()
+    };
+    assert_eq!(
+        changes("[1.5, 100]", "[9.5, 90]", &loose),
+        expected(&[("$[1]", "100 -> 90")])
+    );
+    assert!(changes("1", "1.0", &DiffOptions::default()).is_empty());
+}
+
+const RENDER_LEFT: &str = r#"{"a":1,"b":[true],"d":{"x":[1,{}]}}"#;
+const RENDER_RIGHT: &str = r#"{"a":2,"b":[true,null],"d":"gone","c":"x"}"#;
+
+#[test]
+fn render_without_color() {
+    let (left, right) = (parse(RENDER_LEFT), parse(RENDER_RIGHT));
+    let report = diff(&left, &right);
+    let expected = r#"--- left
++++ right
+@@ $.a @@
+-1
++2
+@@ $.b[1] @@
++null
+@@ $.d @@
+-{
+-  "x": [
+-    1,
+-    {}
+-  ]
+-}
++"gone"
+@@ $.c @@
++"x"
+"#;
+    assert_eq!(report.render(false), expected);
+    assert_eq!(report.to_string(), expected);
+    assert_eq!(diff(&left, &left).render(false), "");
+}
+
+#[test]
+fn render_with_color() {
+    let (left, right) = (parse(r#"{"a":1,"b":[true]}"#), parse(r#"{"a":2,"b":[]}"#));
+    let report = diff(&left, &right);
+    let expected = concat!(
+        "\x1b[1m--- left\x1b[0m\n",
+        "\x1b[1m+++ right\x1b[0m\n",
+        "\x1b[36m@@ $.a @@\x1b[0m\n",
+        "\x1b[31m-1\x1b[0m\n",
+        "\x1b[32m+2\x1b[0m\n",
+        "\x1b[36m@@ $.b[0] @@\x1b[0m\n",
+        "\x1b[31m-true\x1b[0m\n",
+    );
+    assert_eq!(report.render(true), expected);
+    assert_eq!(diff(&left, &left).render(true), "");
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use arjer::error::{ParseError, PathSegment};
+use arjer::json::{JsValue, Map, Num};
+use arjer::{DuplicateKeys, ParseOptions};
+use common::parse;
+
+const INPUT: &str = r#"{"a": 1, "b": 2, "a": 3, "c": {"x": true, "x": false}, "a": 5}"#;
+
+fn options(policy: DuplicateKeys) -> ParseOptions {
+    ParseOptions {
+        duplicate_keys: policy,
+        ..ParseOptions::default()
+    }
+}
+
+fn members(policy: DuplicateKeys) -> Map {
+    match arjer::parse_with(INPUT, &options(policy)).unwrap() {
+        JsValue::JsObject(members) => members,
+        other => panic!("not an object: {:?}", other),
+    }
+}
+
+fn keys(members: &Map) -> Vec<&str> {
+    members.keys().map(String::as_str).collect()
+}
+
+fn int(value: &JsValue) -> u32 {
+    match value {
+        JsValue::JsNumber(Num::U32(i)) => *i,
+        other => panic!("not an integer: {:?}", other),
+    }
+}
+
+/// The values stored under `x` in the nested object `c`.
+fn xs(members: &Map) -> Vec<bool> {
+    let Some(JsValue::JsObject(c)) = members.get("c") else {
+        panic!("c is not an object");
+    };
+    c.get_all("x")
+        .map(|v| match v {
+            JsValue::JsBool(b) => *b,
+            other => panic!("not a boolean: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn reject_points_at_the_repeated_key() {
+    let opts = options(DuplicateKeys::Reject);
+    match arjer::parse_with(INPUT, &opts).unwrap_err() {
+        ParseError::DuplicateKey { key, at } => {
+            assert_eq!(key, "a");
+            assert_eq!(at.offset, 17);
+            assert_eq!((at.line, at.column), (1, 18));
+        }
+        other => panic!("expected DuplicateKey, got {:?}", other),
+    }
+
+    let e = arjer::parse_with(r#"{"o": {"x": 1, "x": 2}}"#, &opts).unwrap_err();
+    assert!(matches!(&e, ParseError::DuplicateKey { key, .. } if key == "x"));
+    assert_eq!(e.location().offset, 15);
+    assert_eq!(e.location().path, vec![PathSegment::Key("o".into())]);
+    assert!(arjer::parse_with(r#"{"a": {"a": 1}, "b": {"a": 2}}"#, &opts).is_ok());
+}
+
+#[test]
+fn keep_first_ignores_later_values() {
+    let members = members(DuplicateKeys::KeepFirst);
+    assert_eq!(keys(&members), ["a", "b", "c"]);
+    assert_eq!(int(members.get("a").unwrap()), 1);
+    assert_eq!(xs(&members), [true]);
+}
+
+#[test]
+fn keep_last_overwrites_earlier_values() {
+    let members = members(DuplicateKeys::KeepLast);
+    assert_eq!(keys(&members), ["a", "b", "c"]);
+    assert_eq!(int(members.get("a").unwrap()), 5);
+    assert_eq!(xs(&members), [false]);
+    // the default policy
+    let JsValue::JsObject(default) = parse(INPUT) else {
+        panic!("not an object");
+    };
+    assert_eq!(keys(&default), keys(&members));
+    assert_eq!(int(default.get("a").unwrap()), 5);
+}
+
+#[test]
+fn collect_all_keeps_every_entry() {
+    let members = members(DuplicateKeys::CollectAll);
+    assert_eq!(keys(&members), ["a", "b", "a", "c", "a"]);
+    assert_eq!(members.get_all("a").map(int).collect::<Vec<_>>(), [1, 3, 5]);
+    assert_eq!(int(members.get("a").unwrap()), 1);
+    assert_eq!(xs(&members), [true, false]);
+}
+
+#[test]
+fn equality_compares_every_collected_entry() {
+    let collect = options(DuplicateKeys::CollectAll);
+    let read = |input: &str| arjer::parse_with(input, &collect).unwrap();
+    // key order doesn't matter, but the order of a repeated key's values does
+    assert_eq!(
+        read(r#"{"a": 1, "b": 2, "a": 3}"#),
+        read(r#"{"b": 2, "a": 1, "a": 3}"#)
+    );
+    assert_ne!(
+        read(r#"{"a": 1, "b": 2, "a": 3}"#),
+        read(r#"{"a": 3, "b": 2, "a": 1}"#)
+    );
+    assert_ne!(read(r#"{"a": 1, "a": 2}"#), read(r#"{"a": 1, "a": 3}"#));
+    assert_ne!(read(r#"{"a": 1, "a": 2}"#), read(r#"{"a": 1, "b": 2}"#));
+    assert_ne!(read(r#"{"a": 1, "b": 2}"#), read(r#"{"a": 1, "a": 1}"#));
+    assert_ne!(read(r#"{"a": 1, "a": 1}"#), parse(r#"{"a": 1}"#));
+    // and the same holds once the maps are large enough to be indexed
+    let many = |last: u32| {
+        let members: Vec<_> = (0..12).map(|i| format!("\"k{}\": {}", i % 6, i)).collect();
+        read(&format!("{{{}, \"k0\": {}}}", members.join(", "), last))
+    };
+    assert_eq!(many(1), many(1));
+    assert_ne!(many(1), many(2));
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::Num;
+use arjer::Event::{self, *};
+use common::parse;
+use std::borrow::Cow;
+
+/// Every event of `input`, which the test expects to be valid.
+fn events(input: &str) -> Vec<Event<'_>> {
+    arjer::events(input)
+        .collect::<Result<_, _>>()
+        .map_err(Box::new)
+        .unwrap()
+}
+
+/// The events read before the first error, and the error.
+fn failure(input: &str) -> (Vec<Event<'_>>, ParseError) {
+    let mut reader = arjer::events(input);
+    let mut read = vec![];
+    loop {
+        match reader.next() {
+            Some(Ok(event)) => read.push(event),
+            Some(Err(e)) => {
+                assert!(reader.next().is_none(), "{}: reader not exhausted", input);
+                return (read, e);
+            }
+            None => panic!("{} was read without an error", input),
+        }
+    }
+}
+
+fn key(k: &str) -> Event<'_> {
+    Key(Cow::Borrowed(k))
+}
+
+fn string(s: &str) -> Event<'_> {
+    Event::String(Cow::Borrowed(s))
+}
+
+#[test]
+fn nested_containers() {
+    assert_eq!(
+        events(r#"{"a": [1, {"b": null}, []], "c": {}, "d": [true, "x"]}"#),
+        [
+            StartObject,
+            key("a"),
+            StartArray,
+            Number(Num::U32(1)),
+            StartObject,
+            key("b"),
+            Null,
+            EndObject,
+            StartArray,
+            EndArray,
+            EndArray,
+            key("c"),
+            StartObject,
+            EndObject,
+            key("d"),
+            StartArray,
+            Bool(true),
+            string("x"),
+            EndArray,
+            EndObject,
+        ]
+    );
+    assert_eq!(
+        events("[[[]], {}]"),
+        [
+            StartArray,
+            StartArray,
+            StartArray,
+            EndArray,
+            EndArray,
+            StartObject,
+            EndObject,
+            EndArray,
+        ]
+    );
+    assert_eq!(events(" \"top\" "), [string("top")]);
+    assert_eq!(events("false"), [Bool(false)]);
+}
+
+#[test]
+fn escaped_keys_and_strings_are_decoded() {
+    let input = r#"{"plain": "a\"b", "tab\tkey": "é", "": ""}"#;
+    let read = events(input);
+    assert_eq!(
+        read,
+        [
+            StartObject,
+            key("plain"),
+            string("a\"b"),
+            key("tab\tkey"),
+            string("é"),
+            key(""),
+            string(""),
+            EndObject,
+        ]
+    );
+    // only what needed decoding is copied
+    assert!(matches!(&read[1], Key(Cow::Borrowed(_))));
+    assert!(matches!(&read[2], Event::String(Cow::Owned(_))));
+    assert!(matches!(&read[3], Key(Cow::Owned(_))));
+    assert!(matches!(&read[4], Event::String(Cow::Borrowed(_))));
+}
+
+#[test]
+fn every_number_kind() {
+    let read = events(
+        "[0, 4294967296, -1, -9223372036854775808, 1.5, -0,
+ 1e-400]",
+    );
+    let read: Vec<_> = read
+        .iter()
+        .filter_map(|e| match e {
+            Number(n) => Some(*n),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(read[0], Num::U32(0)));
+    assert!(matches!(read[1], Num::U64(4294967296)));
+    assert!(matches!(read[2], Num::I64(-1)));
+    assert!(matches!(read[3], Num::I64(i64::MIN)));
+    assert!(matches!(read[4], Num::F(f) if f == 1.5));
+    assert!(matches!(read[5], Num::F(f) if f == 0.0 && f.is_sign_negative()));
+    assert!(matches!(read[6], Num::F(f) if f == 0.0));
+    assert_eq!(read.len(), 7);
+}
+
+#[test]
+fn errors_are_located_after_the_events_before_them() {
+    let (read, e) = failure("[1,]");
+    assert_eq!(read, [StartArray, Number(Num::U32(1))]);
+    assert!(matches!(e, ParseError::UnexpectedToken { .. }), "{:?}", e);
+    assert_eq!(e.location().offset, 3);
+    assert!(e.to_string().contains("$[1]"), "{}", e);
+
+    let (read, e) = failure("{\"a\": {\"b\"\n  1}}");
+    assert_eq!(read, [StartObject, key("a"), StartObject, key("b")]);
+    let at = e.location();
+    assert_eq!((at.offset, at.line, at.column), (13, 2, 3));
+    // the key is complete but its member isn't, so the error belongs to the object
+    assert!(e.to_string().contains("in $.a\n"), "{}", e);
+
+    let (read, e) = failure("[true, [nul]]");
+    assert_eq!(read, [StartArray, Bool(true), StartArray]);
+    assert_eq!(e.location().offset, 8);
+    assert!(e.to_string().contains("$[1][0]"), "{}", e);
+
+    let (read, e) = failure("{\"a\": 1} 2");
+    assert_eq!(
+        read,
+        [StartObject, key("a"), Number(Num::U32(1)), EndObject]
+    );
+    assert!(
+        matches!(e, ParseError::TrailingCharacters { .. }),
+        "{:?}",
+        e
+    );
+    assert_eq!(e.location().offset, 9);
+
+    let (read, e) = failure("[\"a\", ");
+    assert_eq!(read, [StartArray, string("a")]);
+    assert!(matches!(e, ParseError::EOF { .. }), "{:?}", e);
+    assert_eq!(e.location().offset, 6);
+}
+
+#[test]
+fn errors_match_parse() {
+    for input in [
+        "[1,]",
+        "{\"a\" 1}",
+        "{\"a\": [tru]}",
+        "[1 2]",
+        "{} {}",
+        "[\"\\x\"]",
+        "",
+    ] {
+        let (_, e) = failure(input);
+        assert_eq!(
+            e.to_string(),
+            arjer::parse(input).unwrap_err().to_string(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn the_reader_tracks_depth_and_offset() {
+    let mut reader = arjer::events("[{\"a\": 1}]");
+    assert_eq!((reader.depth(), reader.offset()), (0, 0));
+    assert_eq!(reader.next_event().unwrap(), Some(StartArray));
+    assert_eq!(reader.next_event().unwrap(), Some(StartObject));
+    assert_eq!(reader.depth(), 2);
+    assert_eq!(reader.next_event().unwrap(), Some(key("a")));
+    assert_eq!(reader.next_event().unwrap(), Some(Number(Num::U32(1))));
+    assert_eq!(reader.next_event().unwrap(), Some(EndObject));
+    assert_eq!(reader.depth(), 1);
+    assert_eq!(reader.next_event().unwrap(), Some(EndArray));
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!((reader.depth(), reader.offset()), (0, 10));
+}
+
+#[test]
+fn trees_built_from_events_match_the_input() {
+    let input = r#"{"a": [1, -2, 3.5, {"b": null}], "c": "é", "d": {"e": [true, false]}}"#;
+    assert_eq!(
+        parse(input).to_string(),
+        r#"{"a":[1,-2,3.5,{"b":null}],"c":"é","d":{"e":[true,false]}}"#
+    );
+}
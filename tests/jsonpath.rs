@@ -0,0 +1,586 @@
+mod common;
+
+use arjer::json::JsValue;
+use arjer::jsonpath::{JsonPath, JsonPathError};
+use common::parse;
+
+/// The normalized path and value of every node `query` selects from `doc`.
+fn select(doc: &JsValue, query: &str) -> Vec<(String, JsValue)> {
+    JsonPath::compile(query)
+        .unwrap_or_else(|e| panic!("{}: {}", query, e))
+        .query(doc)
+        .into_iter()
+        .map(|node| (node.path.to_string(), node.value.clone()))
+        .collect()
+}
+
+/// Runs every `(query, [(normalized path, value)])` case against `doc`.
+fn check(doc: &str, cases: &[(&str, &[(&str, &str)])]) {
+    let doc = parse(doc);
+    for (query, expected) in cases {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(path, value)| (path.to_string(), parse(value)))
+            .collect();
+        assert_eq!(select(&doc, query), expected, "{}", query);
+    }
+}
+
+// RFC 9535, section 1.5
+const BOOKSTORE: &str = r#"{ "store": {
+    "book": [
+      { "category": "reference", "author": "Nigel Rees",
+        "title": "Sayings of the Century", "price": 8.95 },
+      { "category": "fiction", "author": "Evelyn Waugh",
+        "title": "Sword of Honour", "price": 12.99 },
+      { "category": "fiction", "author": "Herman Melville", "title": "Moby Dick",
+        "isbn": "0-553-21311-3", "price": 8.99 },
+      { "category": "fiction", "author": "J. R. R. Tolkien",
+        "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99 }
+    ],
+    "bicycle": { "color": "red", "price": 399 }
+  } }"#;
+
+#[test]
+fn rfc_overview_examples() {
+    let authors: &[(&str, &str)] = &[
+        ("$['store']['book'][0]['author']", r#""Nigel Rees""#),
+        ("$['store']['book'][1]['author']", r#""Evelyn Waugh""#),
+        ("$['store']['book'][2]['author']", r#""Herman Melville""#),
+        ("$['store']['book'][3]['author']", r#""J. R. R. Tolkien""#),
+    ];
+    let doc = parse(BOOKSTORE);
+    let book = |i: usize| {
+        (
+            format!("$['store']['book'][{}]", i),
+            doc.pointer(&format!("/store/book/{}", i)).unwrap().clone(),
+        )
+    };
+    check(
+        BOOKSTORE,
+        &[
+            ("$.store.book[*].author", authors),
+            ("$..author", authors),
+            (
+                "$.store..price",
+                &[
+                    ("$['store']['book'][0]['price']", "8.95"),
+                    ("$['store']['book'][1]['price']", "12.99"),
+                    ("$['store']['book'][2]['price']", "8.99"),
+                    ("$['store']['book'][3]['price']", "22.99"),
+                    ("$['store']['bicycle']['price']", "399"),
+                ],
+            ),
+            (
+                "$..book[2].author",
+                &[("$['store']['book'][2]['author']", r#""Herman Melville""#)],
+            ),
+            ("$..book[2].publisher", &[]),
+        ],
+    );
+    assert_eq!(select(&doc, "$..book[2]"), [book(2)]);
+    assert_eq!(select(&doc, "$..book[-1]"), [book(3)]);
+    assert_eq!(select(&doc, "$..book[0,1]"), [book(0), book(1)]);
+    assert_eq!(select(&doc, "$..book[:2]"), [book(0), book(1)]);
+    assert_eq!(select(&doc, "$..book[?@.isbn]"), [book(2), book(3)]);
+    assert_eq!(select(&doc, "$..book[?@.price<10]"), [book(0), book(2)]);
+    let store: Vec<_> = select(&doc, "$.store.*")
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(store, ["$['store']['book']", "$['store']['bicycle']"]);
+    assert_eq!(select(&doc, "$..*").len(), 27);
+}
+
+#[test]
+fn rfc_name_selector_examples() {
+    // RFC 9535, section 2.3.1.3
+    check(
+        r#"{"o": {"j j": {"k.k": 3}}, "'": {"@": 2}}"#,
+        &[
+            ("$.o['j j']", &[("$['o']['j j']", r#"{"k.k": 3}"#)]),
+            ("$.o['j j']['k.k']", &[("$['o']['j j']['k.k']", "3")]),
+            (r#"$.o["j j"]["k.k"]"#, &[("$['o']['j j']['k.k']", "3")]),
+            (r#"$["'"]["@"]"#, &[(r"$['\'']['@']", "2")]),
+        ],
+    );
+}
+
+#[test]
+fn rfc_wildcard_and_index_examples() {
+    // RFC 9535, section 2.3.2.3
+    check(
+        r#"{"o": {"j": 1, "k": 2}, "a": [5, 3]}"#,
+        &[
+            (
+                "$[*]",
+                &[("$['o']", r#"{"j": 1, "k": 2}"#), ("$['a']", "[5, 3]")],
+            ),
+            ("$.o[*]", &[("$['o']['j']", "1"), ("$['o']['k']", "2")]),
+            (
+                "$.o[*, *]",
+                &[
+                    ("$['o']['j']", "1"),
+                    ("$['o']['k']", "2"),
+                    ("$['o']['j']", "1"),
+                    ("$['o']['k']", "2"),
+                ],
+            ),
+            ("$.a[*]", &[("$['a'][0]", "5"), ("$['a'][1]", "3")]),
+        ],
+    );
+    // section 2.3.3.3
+    check(
+        r#"["a","b"]"#,
+        &[
+            ("$[1]", &[("$[1]", r#""b""#)]),
+            ("$[-2]", &[("$[0]", r#""a""#)]),
+            ("$[2]", &[]),
+            ("$[-3]", &[]),
+        ],
+    );
+}
+
+#[test]
+fn rfc_slice_examples() {
+    // RFC 9535, section 2.3.4.3
+    check(
+        r#"["a", "b", "c", "d", "e", "f", "g"]"#,
+        &[
+            ("$[1:3]", &[("$[1]", r#""b""#), ("$[2]", r#""c""#)]),
+            ("$[5:]", &[("$[5]", r#""f""#), ("$[6]", r#""g""#)]),
+            ("$[1:5:2]", &[("$[1]", r#""b""#), ("$[3]", r#""d""#)]),
+            ("$[5:1:-2]", &[("$[5]", r#""f""#), ("$[3]", r#""d""#)]),
+            (
+                "$[::-1]",
+                &[
+                    ("$[6]", r#""g""#),
+                    ("$[5]", r#""f""#),
+                    ("$[4]", r#""e""#),
+                    ("$[3]", r#""d""#),
+                    ("$[2]", r#""c""#),
+                    ("$[1]", r#""b""#),
+                    ("$[0]", r#""a""#),
+                ],
+            ),
+            // bounds are clamped, and a step of 0 selects nothing
+            ("$[-2:100]", &[("$[5]", r#""f""#), ("$[6]", r#""g""#)]),
+            ("$[:-5:-2]", &[("$[6]", r#""g""#), ("$[4]", r#""e""#)]),
+            ("$[-100:1]", &[("$[0]", r#""a""#)]),
+            ("$[0:7:0]", &[]),
+            ("$[3:1]", &[]),
+        ],
+    );
+}
+
+// RFC 9535, section 2.3.5.3
+const FILTERED: &str = r#"{
+    "a": [3, 5, 1, 2, 4, 6, {"b": "j"}, {"b": "k"}, {"b": {}}, {"b": "kilo"}],
+    "o": {"p": 1, "q": 2, "r": 3, "s": 5, "t": {"u": 6}},
+    "e": "f"
+}"#;
+
+#[test]
+fn rfc_filter_examples() {
+    let kilo: &[(&str, &str)] = &[("$['a'][9]", r#"{"b": "kilo"}"#)];
+    let numbers: &[(&str, &str)] = &[
+        ("$['a'][0]", "3"),
+        ("$['a'][1]", "5"),
+        ("$['a'][2]", "1"),
+        ("$['a'][3]", "2"),
+        ("$['a'][4]", "4"),
+        ("$['a'][5]", "6"),
+    ];
+    check(
+        FILTERED,
+        &[
+            ("$.a[?@.b == 'kilo']", kilo),
+            ("$.a[?(@.b == 'kilo')]", kilo),
+            (
+                "$.a[?@>3.5]",
+                &[("$['a'][1]", "5"), ("$['a'][4]", "4"), ("$['a'][5]", "6")],
+            ),
+            (
+                "$.a[?@.b]",
+                &[
+                    ("$['a'][6]", r#"{"b": "j"}"#),
+                    ("$['a'][7]", r#"{"b": "k"}"#),
+                    ("$['a'][8]", r#"{"b": {}}"#),
+                    ("$['a'][9]", r#"{"b": "kilo"}"#),
+                ],
+            ),
+            (
+                "$[?@.*]",
+                &[
+                    (
+                        "$['a']",
+                        r#"[3, 5, 1, 2, 4, 6, {"b": "j"}, {"b": "k"}, {"b": {}}, {"b": "kilo"}]"#,
+                    ),
+                    (
+                        "$['o']",
+                        r#"{"p": 1, "q": 2, "r": 3, "s": 5, "t": {"u": 6}}"#,
+                    ),
+                ],
+            ),
+            (
+                "$[?@[?@.b]]",
+                &[(
+                    "$['a']",
+                    r#"[3, 5, 1, 2, 4, 6, {"b": "j"}, {"b": "k"}, {"b": {}}, {"b": "kilo"}]"#,
+                )],
+            ),
+            (
+                "$.o[?@<3, ?@<3]",
+                &[
+                    ("$['o']['p']", "1"),
+                    ("$['o']['q']", "2"),
+                    ("$['o']['p']", "1"),
+                    ("$['o']['q']", "2"),
+                ],
+            ),
+            (
+                r#"$.a[?@<2 || @.b == "k"]"#,
+                &[("$['a'][2]", "1"), ("$['a'][7]", r#"{"b": "k"}"#)],
+            ),
+            (
+                r#"$.a[?match(@.b, "[jk]")]"#,
+                &[
+                    ("$['a'][6]", r#"{"b": "j"}"#),
+                    ("$['a'][7]", r#"{"b": "k"}"#),
+                ],
+            ),
+            (
+                r#"$.a[?search(@.b, "[jk]")]"#,
+                &[
+                    ("$['a'][6]", r#"{"b": "j"}"#),
+                    ("$['a'][7]", r#"{"b": "k"}"#),
+                    ("$['a'][9]", r#"{"b": "kilo"}"#),
+                ],
+            ),
+            (
+                "$.o[?@>1 && @<4]",
+                &[("$['o']['q']", "2"), ("$['o']['r']", "3")],
+            ),
+            ("$.o[?@.u || @.x]", &[("$['o']['t']", r#"{"u": 6}"#)]),
+            ("$.a[?@.b == $.x]", numbers),
+        ],
+    );
+    assert_eq!(select(&parse(FILTERED), "$.a[?@ == @]").len(), 10);
+}
+
+#[test]
+fn rfc_comparison_examples() {
+    // RFC 9535, table 11, each comparison as the filter of `$[?...]` on a one-element array
+    // so that it selects that element exactly when it holds
+    let doc = r#"{"obj": {"x": "y"}, "arr": [2, 3]}"#;
+    let holds = [
+        "$.absent1 == $.absent2",
+        "$.absent1 <= $.absent2",
+        "$.absent != 'g'",
+        "1 <= 2",
+        "1 < 2.0",
+        "'a' < 'b'",
+        "$.obj == $.obj",
+        "$.obj != $.arr",
+        "$.arr == $.arr",
+        "1 != 'a'",
+        "1 == 1.0",
+        "$.obj <= $.obj",
+        "$.arr <= $.arr",
+        "$.obj != 17",
+        "true <= true",
+    ];
+    let fails = [
+        "$.absent == 'g'",
+        "1 > 2",
+        "1 < 'a'",
+        "$.obj != $.obj",
+        "$.obj == $.arr",
+        "$.obj < $.obj",
+        "$.arr < $.arr",
+        "true > true",
+        "13 == '13'",
+        "$.obj <= $.arr",
+        "1 <= $.arr",
+        "1 > $.arr",
+    ];
+    let doc = parse(&format!("[{}]", doc));
+    for comparison in holds {
+        let query = format!("$[?{}]", comparison.replace('$', "$[0]"));
+        assert_eq!(select(&doc, &query).len(), 1, "{}", comparison);
+    }
+    for comparison in fails {
+        let query = format!("$[?{}]", comparison.replace('$', "$[0]"));
+        assert_eq!(select(&doc, &query).len(), 0, "{}", comparison);
+    }
+}
+
+#[test]
+fn rfc_descendant_examples() {
+    // RFC 9535, section 2.5.2.3
+    let all: &[(&str, &str)] = &[
+        ("$['o']", r#"{"j": 1, "k": 2}"#),
+        ("$['a']", r#"[5, 3, [{"j": 4}, {"k": 6}]]"#),
+        ("$['o']['j']", "1"),
+        ("$['o']['k']", "2"),
+        ("$['a'][0]", "5"),
+        ("$['a'][1]", "3"),
+        ("$['a'][2]", r#"[{"j": 4}, {"k": 6}]"#),
+        ("$['a'][2][0]", r#"{"j": 4}"#),
+        ("$['a'][2][1]", r#"{"k": 6}"#),
+        ("$['a'][2][0]['j']", "4"),
+        ("$['a'][2][1]['k']", "6"),
+    ];
+    check(
+        r#"{"o": {"j": 1, "k": 2}, "a": [5, 3, [{"j": 4}, {"k": 6}]]}"#,
+        &[
+            ("$..j", &[("$['o']['j']", "1"), ("$['a'][2][0]['j']", "4")]),
+            (
+                "$..[0]",
+                &[("$['a'][0]", "5"), ("$['a'][2][0]", r#"{"j": 4}"#)],
+            ),
+            ("$..[*]", all),
+            ("$..*", all),
+            ("$..o", &[("$['o']", r#"{"j": 1, "k": 2}"#)]),
+            (
+                "$.o..[*, *]",
+                &[
+                    ("$['o']['j']", "1"),
+                    ("$['o']['k']", "2"),
+                    ("$['o']['j']", "1"),
+                    ("$['o']['k']", "2"),
+                ],
+            ),
+            (
+                "$.a..[0, 1]",
+                &[
+                    ("$['a'][0]", "5"),
+                    ("$['a'][1]", "3"),
+                    ("$['a'][2][0]", r#"{"j": 4}"#),
+                    ("$['a'][2][1]", r#"{"k": 6}"#),
+                ],
+            ),
+            (
+                "$..[-1:]",
+                &[
+                    ("$['a'][2]", r#"[{"j": 4}, {"k": 6}]"#),
+                    ("$['a'][2][1]", r#"{"k": 6}"#),
+                ],
+            ),
+        ],
+    );
+}
+
+#[test]
+fn rfc_null_examples() {
+    // RFC 9535, section 2.6.1
+    check(
+        r#"{"a": null, "b": [null], "c": [{}], "null": 1}"#,
+        &[
+            ("$.a", &[("$['a']", "null")]),
+            ("$.a[0]", &[]),
+            ("$.a.d", &[]),
+            ("$.b[0]", &[("$['b'][0]", "null")]),
+            ("$.b[*]", &[("$['b'][0]", "null")]),
+            ("$.b[?@]", &[("$['b'][0]", "null")]),
+            ("$.b[?@==null]", &[("$['b'][0]", "null")]),
+            ("$.c[?@.d==null]", &[]),
+            ("$.null", &[("$['null']", "1")]),
+        ],
+    );
+}
+
+#[test]
+fn rfc_normalized_path_examples() {
+    // RFC 9535, table 16
+    check(
+        r#"{"a": {"b": [0, 1, 2, 3, 4]}, "\u000B": 1, "'\\": 2, "\b\f\n\r\t": 3}"#,
+        &[
+            ("$.a", &[("$['a']", r#"{"b": [0, 1, 2, 3, 4]}"#)]),
+            ("$.a.b[1]", &[("$['a']['b'][1]", "1")]),
+            ("$.a.b[-3]", &[("$['a']['b'][2]", "2")]),
+            ("$.a.b[1:2]", &[("$['a']['b'][1]", "1")]),
+            (r#"$["\u000B"]"#, &[(r"$['\u000b']", "1")]),
+            (r#"$["a"]"#, &[("$['a']", r#"{"b": [0, 1, 2, 3, 4]}"#)]),
+            (r#"$["'\\"]"#, &[(r"$['\'\\']", "2")]),
+            (r#"$["\b\f\n\r\t"]"#, &[(r"$['\b\f\n\r\t']", "3")]),
+        ],
+    );
+    let doc = parse(r#"{"a~b": [{"c/d": 1}]}"#);
+    let found = JsonPath::compile("$..['c/d']").unwrap().query(&doc);
+    assert_eq!(found[0].path.to_pointer().to_string(), "/a~0b/0/c~1d");
+}
+
+#[test]
+fn function_extensions() {
+    // RFC 9535, section 2.4
+    check(
+        r#"[
+            {"name": "ab", "tags": ["x"], "date": "1974-05-01", "parts": {"color": "red"}},
+            {"name": "abcd", "tags": ["x", "y"], "date": "1974-06-01", "parts": {"c": {"color": "red"}, "d": {"color": "blue"}}},
+            {"name": 12, "tags": {}, "date": "1974-05-30"}
+        ]"#,
+        &[
+            (
+                "$[?length(@.name) < 3].name",
+                &[("$[0]['name']", r#""ab""#)],
+            ),
+            (
+                "$[?length(@.tags) == 2].name",
+                &[("$[1]['name']", r#""abcd""#)],
+            ),
+            (
+                "$[?count(@.tags.*) == 1].name",
+                &[("$[0]['name']", r#""ab""#)],
+            ),
+            (
+                "$[?count(@..color) == 2].name",
+                &[("$[1]['name']", r#""abcd""#)],
+            ),
+            (
+                r#"$[?value(@..color) == "red"].name"#,
+                &[("$[0]['name']", r#""ab""#)],
+            ),
+            (
+                r#"$[?match(@.date, "1974-05-..")].name"#,
+                &[("$[0]['name']", r#""ab""#), ("$[2]['name']", "12")],
+            ),
+        ],
+    );
+}
+
+#[test]
+fn iregexp_match_and_search() {
+    let doc = parse(r#"["abc", "xabc", "a\nc", "a\rc", "ABC", "é", "a-b", "a]"]"#);
+    let strings = |query: &str| -> Vec<String> {
+        select(&doc, query)
+            .into_iter()
+            .map(|(_, v)| v.to_string())
+            .collect()
+    };
+    // `match` is anchored at both ends, `search` isn't
+    assert_eq!(strings("$[?match(@, 'a.c')]"), [r#""abc""#]);
+    assert_eq!(strings("$[?search(@, 'a.c')]"), [r#""abc""#, r#""xabc""#]);
+    // `.` matches anything but a line break
+    assert_eq!(
+        strings("$[?search(@, 'a[^b]c')]"),
+        [r#""a\nc""#, r#""a\rc""#]
+    );
+    assert_eq!(strings(r"$[?match(@, '\\p{Lu}+')]"), [r#""ABC""#]);
+    assert_eq!(strings(r"$[?match(@, '\\p{L}')]"), [r#""é""#]);
+    assert_eq!(strings(r"$[?match(@, 'a\\-b')]"), [r#""a-b""#]);
+    assert_eq!(strings("$[?match(@, '[a-c]{3}')]"), [r#""abc""#]);
+    assert_eq!(strings("$[?search(@, '^a')]"), Vec::<String>::new());
+    assert_eq!(strings("$[?!match(@, '.*')]").len(), 2);
+    // patterns that aren't I-Regexps match nothing rather than failing the query
+    for invalid in ["'['", "'a{'", r"'\\d'", "'(?i)abc'", "'[]a]'", "'a$'"] {
+        let query = format!("$[?search(@, {})]", invalid);
+        assert_eq!(strings(&query), Vec::<String>::new(), "{}", query);
+    }
+}
+
+#[test]
+fn well_typedness() {
+    // RFC 9535, section 2.4.9
+    for query in [
+        "$[?length(@) < 3]",
+        "$[?count(@.*) == 1]",
+        "$[?match(@.timezone, 'Europe/.*')]",
+        r#"$[?value(@..color) == "red"]"#,
+    ] {
+        assert!(JsonPath::compile(query).is_ok(), "{}", query);
+    }
+    for query in [
+        "$[?length(@.*) < 3]",
+        "$[?count(1) == 1]",
+        "$[?match(@.timezone, 'Europe/.*') == true]",
+        "$[?value(@..color)]",
+        "$[?@.* == 1]",
+        "$[?@..a]x",
+    ] {
+        let error = JsonPath::compile(query).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                JsonPathError::Type { .. } | JsonPathError::Syntax { .. }
+            ),
+            "{}: {:?}",
+            query,
+            error
+        );
+    }
+    assert_eq!(
+        JsonPath::compile("$[?count(foo(@.*)) == 1]").unwrap_err(),
+        JsonPathError::UnknownFunction {
+            offset: 9,
+            name: "foo".into()
+        }
+    );
+}
+
+#[test]
+fn invalid_queries() {
+    // a subset of the invalid cases of the JSONPath compliance test suite
+    for query in [
+        "",
+        "$.",
+        "$..",
+        " $",
+        "$ ",
+        "@.a",
+        "$a",
+        "$[",
+        "$['a'",
+        "$['a\"]",
+        "$[01]",
+        "$[-0]",
+        "$[1.0]",
+        "$[:::]",
+        "$[0,]",
+        "$[,0]",
+        "$.a[?]",
+        "$[?@.a == 1 == 1]",
+        "$[?(@.a]",
+        "$[?@.a = 1]",
+        "$[?@.a == 1.]",
+        "$[?@.a == 01]",
+        "$[?@.a == 'x\\q']",
+        "$[?!@.a == 1]",
+        "$[?@.a === 1]",
+        "$.1",
+        "$.-a",
+        "$['\u{1}']",
+        "$..['a'",
+    ] {
+        assert!(JsonPath::compile(query).is_err(), "{:?} compiled", query);
+    }
+    assert_eq!(
+        JsonPath::compile("$[9007199254740992]").unwrap_err(),
+        JsonPathError::IntegerOutOfRange { offset: 2 }
+    );
+    assert!(JsonPath::compile("$[9007199254740991]").is_ok());
+    assert!(matches!(
+        JsonPath::compile("$.a]").unwrap_err(),
+        JsonPathError::Syntax { offset: 3, .. }
+    ));
+}
+
+#[test]
+fn whitespace_and_shorthands() {
+    check(
+        r#"{"a": [{"b": 1}, {"b": 2}], "ü": 3, "_x": 4}"#,
+        &[
+            (
+                "$ [ 'a' ] [ 0 , 1 ] .b",
+                &[("$['a'][0]['b']", "1"), ("$['a'][1]['b']", "2")],
+            ),
+            ("$.a[ ?@.b > 1 ]", &[("$['a'][1]", r#"{"b": 2}"#)]),
+            ("$.ü", &[("$['ü']", "3")]),
+            ("$._x", &[("$['_x']", "4")]),
+            (
+                "$",
+                &[("$", r#"{"a": [{"b": 1}, {"b": 2}], "ü": 3, "_x": 4}"#)],
+            ),
+        ],
+    );
+}
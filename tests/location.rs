@@ -0,0 +1,83 @@
+use arjer::error::{ParseError, PathSegment};
+
+fn error(input: &str) -> ParseError {
+    arjer::parse(input).unwrap_err()
+}
+
+/// `(offset, line, column)` of the error `input` fails with.
+fn position(input: &str) -> (usize, usize, usize) {
+    let e = error(input);
+    let at = e.location();
+    (at.offset, at.line, at.column)
+}
+
+#[test]
+fn line_and_column_after_newlines() {
+    assert_eq!(position("x"), (0, 1, 1));
+    assert_eq!(position("[1,\n x]"), (5, 2, 2));
+    assert_eq!(position("[1,\n\n\n   x]"), (9, 4, 4));
+}
+
+#[test]
+fn crlf_counts_as_one_line_break() {
+    assert_eq!(position("[1,\r\n x]"), (6, 2, 2));
+    assert_eq!(position("{\r\n\"a\": 1,\r\n\"b\": x}"), (17, 3, 6));
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    // é is two bytes, 日 three and 😀 four, but each is one column
+    assert_eq!(position(r#"["é", x]"#), (7, 1, 7));
+    assert_eq!(position(r#"["日本", x]"#), (11, 1, 8));
+    assert_eq!(position("[\"😀\",\n \"😀\", x]"), (18, 2, 7));
+}
+
+#[test]
+fn display_places_the_caret_under_the_error() {
+    let e = error("{\"a\": [1, 2,\n   \"é\" 3]}");
+    assert_eq!(
+        e.to_string(),
+        concat!(
+            "unexpected token `3`, expected `,` or `]` at line 2, column 8 (byte 21), in $.a\n",
+            "       \"é\" 3]}\n",
+            "           ^",
+        )
+    );
+}
+
+#[test]
+fn excerpt_is_shortened_around_the_error_on_long_lines() {
+    let input = format!(
+        "[{}, x, {}]",
+        "1, ".repeat(40) + "1",
+        "2, ".repeat(40) + "2"
+    );
+    let e = error(&input);
+    let at = e.location();
+    let mut lines = at.excerpt.lines();
+    let (text, caret) = (lines.next().unwrap(), lines.next().unwrap());
+    assert!(text.chars().count() <= 64, "{:?}", text);
+    assert_eq!(caret.trim_start().len(), 1);
+    assert_eq!(text.chars().nth(caret.len() - 1), Some('x'));
+}
+
+#[test]
+fn path_leads_to_the_offending_value() {
+    let input = r#"{"name": "Ann", "hobbies": ["a", "b", "c", nope]}"#;
+    let e = error(input);
+    assert_eq!(
+        e.location().path,
+        vec![PathSegment::Key("hobbies".into()), PathSegment::Index(3)]
+    );
+    assert_eq!(e.location().json_path(), "$.hobbies[3]");
+    assert!(e.to_string().contains("in $.hobbies[3]"), "{}", e);
+}
+
+#[test]
+fn paths_quote_keys_that_are_not_identifiers() {
+    let e = error(r#"{"a b": {"it's": [x]}}"#);
+    assert_eq!(e.location().json_path(), r"$['a b']['it\'s'][0]");
+    let e = error(r#"{"a": {"b": x}}"#);
+    assert_eq!(e.location().json_path(), "$.a.b");
+    assert_eq!(error("x").location().json_path(), "$");
+}
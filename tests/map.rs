@@ -0,0 +1,152 @@
+mod common;
+
+use arjer::json::{JsValue, Map, Num};
+use common::parse;
+
+fn keys(map: &Map) -> Vec<&str> {
+    map.keys().map(String::as_str).collect()
+}
+
+fn number(i: usize) -> JsValue {
+    JsValue::JsNumber(Num::U32(i as u32))
+}
+
+/// The number stored under `key`.
+fn get(map: &Map, key: &str) -> Option<usize> {
+    match map.get(key)? {
+        JsValue::JsNumber(Num::U32(i)) => Some(*i as usize),
+        other => panic!("{} holds {:?}", key, other),
+    }
+}
+
+/// Checks that every key can be found and holds the value it was given.
+fn check_lookups(map: &Map) {
+    for (pos, k) in map.keys().enumerate() {
+        let stored = map.iter().nth(pos).map(|(_, v)| v);
+        assert!(
+            matches!((map.get(k), stored), (Some(a), Some(b)) if std::ptr::eq(a, b)),
+            "{}",
+            k
+        );
+        assert!(map.contains_key(k));
+    }
+    assert!(map.get("missing").is_none());
+}
+
+// not in any sorted order, so that insertion order is what's being kept
+const NAMES: [&str; 20] = [
+    "m", "c", "x", "a", "q", "b", "z", "k", "e", "y", "d", "p", "w", "f", "o", "g", "v", "h", "n",
+    "i",
+];
+
+fn map(n: usize) -> Map {
+    NAMES[..n]
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.to_string(), number(i)))
+        .collect()
+}
+
+#[test]
+fn insertion_order_is_kept_as_the_map_grows() {
+    let mut map = Map::new();
+    for (i, k) in NAMES.iter().enumerate() {
+        assert!(map.insert(k.to_string(), number(i)).is_none());
+        assert_eq!(keys(&map), NAMES[..=i]);
+        check_lookups(&map);
+    }
+    // replacing a value keeps the key where it was, on either side of the threshold
+    assert!(map.insert("c".into(), number(100)).is_some());
+    assert!(map.insert("i".into(), number(200)).is_some());
+    assert_eq!(keys(&map), NAMES);
+    assert_eq!(get(&map, "c"), Some(100));
+    assert_eq!(get(&map, "i"), Some(200));
+    check_lookups(&map);
+}
+
+#[test]
+fn remove_keeps_order_and_lookups_consistent() {
+    let mut map = map(20);
+    let mut expected = NAMES.to_vec();
+    // from the front, the middle and the end, until the map is small again
+    for k in [
+        "m", "o", "i", "k", "c", "w", "a", "h", "z", "e", "n", "q", "b",
+    ] {
+        let value = NAMES.iter().position(|n| n == &k).unwrap();
+        assert!(
+            matches!(map.remove(k), Some(JsValue::JsNumber(Num::U32(v))) if v as usize == value)
+        );
+        assert!(map.remove(k).is_none());
+        expected.retain(|n| n != &k);
+        assert_eq!(keys(&map), expected);
+        check_lookups(&map);
+        for (i, n) in NAMES.iter().enumerate() {
+            assert_eq!(get(&map, n), expected.contains(n).then_some(i), "{}", n);
+        }
+    }
+    assert_eq!(map.len(), 7);
+    // and growing past the threshold again appends
+    for (i, k) in ["m", "o", "i"].into_iter().enumerate() {
+        map.insert(k.into(), number(30 + i));
+        expected.push(k);
+    }
+    assert_eq!(keys(&map), expected);
+    check_lookups(&map);
+    assert_eq!(get(&map, "o"), Some(31));
+}
+
+#[test]
+fn duplicates_across_the_threshold() {
+    for n in [3, 8, 9, 15] {
+        let mut map = map(n);
+        map.append("c".into(), number(50));
+        map.append("new".into(), number(51));
+        map.append("c".into(), number(52));
+        // lookups find the first entry, `get_all` finds all of them in order
+        assert_eq!(get(&map, "c"), Some(1));
+        let all: Vec<_> = map
+            .get_all("c")
+            .map(|v| match v {
+                JsValue::JsNumber(Num::U32(i)) => *i,
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(all, [1, 50, 52]);
+        assert_eq!(get(&map, "new"), Some(51));
+        assert_eq!(map.len(), n + 3);
+
+        // removing takes out every entry for the key
+        assert!(matches!(
+            map.remove("c"),
+            Some(JsValue::JsNumber(Num::U32(1)))
+        ));
+        assert_eq!(map.get_all("c").count(), 0);
+        let mut expected: Vec<_> = NAMES[..n].iter().copied().filter(|&k| k != "c").collect();
+        expected.push("new");
+        assert_eq!(keys(&map), expected);
+        check_lookups(&map);
+    }
+}
+
+#[test]
+fn parsed_objects_keep_their_order() {
+    let input = format!(
+        "{{{}}}",
+        NAMES
+            .iter()
+            .enumerate()
+            .map(|(i, k)| format!("\"{}\": {}", k, i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let JsValue::JsObject(mut members) = parse(&input) else {
+        panic!("not an object");
+    };
+    assert_eq!(keys(&members), NAMES);
+    members.remove("a");
+    members.insert("a".into(), JsValue::JsNull);
+    assert_eq!(keys(&members).last(), Some(&"a"));
+    assert!(matches!(members.get("a"), Some(JsValue::JsNull)));
+    assert_eq!(get(&members, "i"), Some(19));
+    assert_eq!(members.len(), 20);
+}
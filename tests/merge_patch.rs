@@ -0,0 +1,99 @@
+mod common;
+
+use arjer::patch::merge_diff;
+use common::parse;
+
+// RFC 7396, appendix A
+const EXAMPLES: &[(&str, &str, &str)] = &[
+    (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+    (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+    (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+    (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+    (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+    (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+    (
+        r#"{"a":{"b":"c"}}"#,
+        r#"{"a":{"b":"d","c":null}}"#,
+        r#"{"a":{"b":"d"}}"#,
+    ),
+    (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+    (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+    (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+    (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+    (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+    (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+    (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+    (
+        r#"{}"#,
+        r#"{"a":{"bb":{"ccc":null}}}"#,
+        r#"{"a":{"bb":{}}}"#,
+    ),
+];
+
+#[test]
+fn appendix_examples() {
+    for (original, patch, result) in EXAMPLES {
+        let mut target = parse(original);
+        target.merge_patch(&parse(patch));
+        assert_eq!(target, parse(result), "{} patched with {}", original, patch);
+    }
+}
+
+// RFC 7396, section 3
+#[test]
+fn section_3_example() {
+    let mut target = parse(
+        r#"{
+            "title": "Goodbye!",
+            "author" : {
+                "givenName" : "John",
+                "familyName" : "Doe"
+            },
+            "tags":[ "example", "sample" ],
+            "content": "This will be unchanged"
+        }"#,
+    );
+    let patch = parse(
+        r#"{
+            "title": "Hello!",
+            "phoneNumber": "+01-123-456-7890",
+            "author": {
+                "familyName": null
+            },
+            "tags": [ "example" ]
+        }"#,
+    );
+    target.merge_patch(&patch);
+    let expected = parse(
+        r#"{
+            "title": "Hello!",
+            "author" : {
+                "givenName" : "John"
+            },
+            "tags": [ "example" ],
+            "content": "This will be unchanged",
+            "phoneNumber": "+01-123-456-7890"
+        }"#,
+    );
+    assert_eq!(target, expected);
+}
+
+#[test]
+fn computed_patch_reproduces_the_result() {
+    for (original, _, result) in EXAMPLES {
+        let (original, result) = (parse(original), parse(result));
+        let mut target = original.clone();
+        target.merge_patch(&merge_diff(&original, &result));
+        assert_eq!(target, result);
+    }
+}
+
+#[test]
+fn computed_patch_leaves_out_unchanged_members() {
+    let from = parse(r#"{"a":1,"b":{"c":2,"d":3},"e":[1,2]}"#);
+    let to = parse(r#"{"a":1,"b":{"c":2,"d":4},"f":true}"#);
+    assert_eq!(
+        merge_diff(&from, &to),
+        parse(r#"{"b":{"d":4},"e":null,"f":true}"#)
+    );
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::JsValue;
+use arjer::ndjson::{self, NdjsonOptions, Writer};
+use common::parse;
+use std::io::{self, BufReader, Read};
+
+/// The message, offset, line and column of `e`; the excerpt is left out.
+fn describe(e: &ParseError) -> String {
+    let at = e.location();
+    let message = e.to_string();
+    let message = message.split(" at line").next().unwrap().to_string();
+    format!("{} @ {}:{}:{}", message, at.offset, at.line, at.column)
+}
+
+/// A record's line and its value (in `Debug` form) or error.
+type Item = (usize, Result<String, String>);
+
+fn items(records: impl Iterator<Item = (usize, Result<JsValue, ParseError>)>) -> Vec<Item> {
+    records
+        .map(|(line, result)| {
+            let result = result.map(|v| format!("{:?}", v));
+            (line, result.map_err(|e| describe(&e)))
+        })
+        .collect()
+}
+
+/// Reads `input` both from the string and through a `BufRead` with a tiny buffer, checks
+/// that they agree, and returns what they read.
+fn read(input: &str, opts: &NdjsonOptions) -> Vec<Item> {
+    let from_str = items(ndjson::from_str_with(input, opts.clone()));
+    let reader = BufReader::with_capacity(3, input.as_bytes());
+    let from_reader = items(ndjson::from_reader_with(reader, opts.clone()));
+    assert_eq!(from_str, from_reader);
+    from_str
+}
+
+fn ok(line: usize, value: &str) -> Item {
+    (line, Ok(format!("{:?}", parse(value))))
+}
+
+fn err(line: usize, description: &str) -> Item {
+    (line, Err(description.to_string()))
+}
+
+// line offsets: 0, 8, 9, 14, 21, 27, 31
+const INPUT: &str = "{\"a\":1}\n\n  \t \n[1,2]\r\n{\"b\":\nnul\n\"x\"";
+
+#[test]
+fn errors_are_located_on_their_line() {
+    assert_eq!(
+        read(INPUT, &NdjsonOptions::default()),
+        [
+            ok(1, r#"{"a":1}"#),
+            ok(4, "[1,2]"),
+            err(5, "unexpected end of input @ 26:5:6"),
+        ]
+    );
+    let mut lines = ndjson::from_str("1\n2\n[3,\n");
+    lines.next();
+    lines.next();
+    let (line, e) = lines.next().unwrap();
+    let e = e.unwrap_err();
+    assert_eq!(line, 3);
+    assert_eq!(e.location().excerpt, "[3,\n   ^");
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn continue_after_errors() {
+    let opts = NdjsonOptions {
+        continue_on_error: true,
+        ..NdjsonOptions::default()
+    };
+    assert_eq!(
+        read(INPUT, &opts),
+        [
+            ok(1, r#"{"a":1}"#),
+            ok(4, "[1,2]"),
+            err(5, "unexpected end of input @ 26:5:6"),
+            err(6, "unexpected token `nul`, expected `null` @ 27:6:1"),
+            ok(7, r#""x""#),
+        ]
+    );
+}
+
+#[test]
+fn blank_lines_are_errors_unless_skipped() {
+    let strict = NdjsonOptions {
+        skip_blank_lines: false,
+        ..NdjsonOptions::default()
+    };
+    assert_eq!(
+        read(INPUT, &strict),
+        [
+            ok(1, r#"{"a":1}"#),
+            err(2, "unexpected end of input @ 8:2:1"),
+        ]
+    );
+    let all = NdjsonOptions {
+        skip_blank_lines: false,
+        continue_on_error: true,
+        ..NdjsonOptions::default()
+    };
+    assert_eq!(
+        read(INPUT, &all)[..4],
+        [
+            ok(1, r#"{"a":1}"#),
+            err(2, "unexpected end of input @ 8:2:1"),
+            err(3, "unexpected end of input @ 13:3:5"),
+            ok(4, "[1,2]"),
+        ]
+    );
+    // a final newline doesn't make for an extra blank line
+    assert_eq!(read("1\n2\n", &strict), [ok(1, "1"), ok(2, "2")]);
+    assert_eq!(read("", &strict), []);
+}
+
+#[test]
+fn each_line_follows_the_parse_options() {
+    let opts = NdjsonOptions {
+        parse: arjer::ParseOptions {
+            max_depth: 2,
+            ..arjer::ParseOptions::default()
+        },
+        continue_on_error: true,
+        ..NdjsonOptions::default()
+    };
+    let read = read("[[1]]\n[[[1]]]\n{}", &opts);
+    assert_eq!(read[0], ok(1, "[[1]]"));
+    assert!(
+        matches!(&read[1], (2, Err(e)) if e.ends_with("@ 8:2:3")),
+        "{:?}",
+        read[1]
+    );
+    assert_eq!(read[2], ok(3, "{}"));
+}
+
+#[test]
+fn reader_errors() {
+    let opts = NdjsonOptions {
+        continue_on_error: true,
+        ..NdjsonOptions::default()
+    };
+    let input: &[u8] = b"1\n\"\xff\"\n3\n";
+    let read = items(ndjson::from_reader_with(input, opts));
+    assert_eq!(read[0], ok(1, "1"));
+    assert_eq!(read[1].0, 2);
+    assert!(read[1].1.as_ref().unwrap_err().ends_with("@ 3:2:2"));
+    assert_eq!(read[2], ok(3, "3"));
+
+    /// Yields its input, then fails.
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("disk on fire"));
+            }
+            self.0.read(buf)
+        }
+    }
+    let mut records = ndjson::from_reader(BufReader::new(Failing(b"1\n2\n")));
+    assert!(matches!(
+        records.next(),
+        Some((1, Ok(JsValue::JsNumber(_))))
+    ));
+    assert!(matches!(
+        records.next(),
+        Some((2, Ok(JsValue::JsNumber(_))))
+    ));
+    let (line, e) = records.next().unwrap();
+    assert_eq!(line, 3);
+    assert!(matches!(e, Err(ParseError::Io { .. })));
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn writer_puts_one_value_per_line() {
+    let values = [
+        parse(r#"{"text":"two\nlines","crlf":"\r\n"}"#),
+        parse("[1, 2.5, null]"),
+        parse("\"\u{2028}\""),
+    ];
+    let mut writer = Writer::new(Vec::new());
+    writer.write(&values[0]).unwrap();
+    writer.write_all(&values[1..]).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().iter().filter(|&&b| b == b'\n').count(), 3);
+    let written = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        written,
+        "{\"text\":\"two\\nlines\",\"crlf\":\"\\r\\n\"}\n[1,2.5,null]\n\"\u{2028}\"\n"
+    );
+    let read: Vec<_> = ndjson::from_str(&written)
+        .map(|(_, value)| format!("{:?}", value.unwrap()))
+        .collect();
+    let values: Vec<_> = values.iter().map(|v| format!("{:?}", v)).collect();
+    assert_eq!(read, values);
+}
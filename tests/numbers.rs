@@ -0,0 +1,163 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::{JsValue, Num};
+use common::parse;
+use std::cmp::Ordering;
+
+fn number(input: &str) -> Num {
+    match parse(input) {
+        JsValue::JsNumber(n) => n,
+        other => panic!("{} parsed as {:?}", input, other),
+    }
+}
+
+#[test]
+fn accepted_numbers() {
+    assert!(matches!(number("0"), Num::U32(0)));
+    assert!(matches!(number("-1"), Num::I64(-1)));
+    assert!(matches!(number("4294967296"), Num::U64(4294967296)));
+    assert!(matches!(number("-9223372036854775808"), Num::I64(i64::MIN)));
+    // integers that don't fit any integer variant become floats
+    assert!(matches!(number("18446744073709551616"), Num::F(_)));
+    assert!(matches!(number("-9223372036854775809"), Num::F(_)));
+    assert_eq!(
+        number("18446744073709551616"),
+        Num::F(18446744073709551616.0)
+    );
+    assert_eq!(number("1e10"), Num::F(1e10));
+    assert_eq!(number("2.5E-3"), Num::F(0.0025));
+    assert_eq!(number("1E+2"), Num::F(100.0));
+    assert_eq!(number("-0.5"), Num::F(-0.5));
+    assert!(matches!(number("1.0"), Num::F(_)));
+}
+
+#[test]
+fn negative_zero_keeps_its_sign() {
+    for input in ["-0", "-0.0", "-0e5"] {
+        let Num::F(f) = number(input) else {
+            panic!("{} is not a float", input);
+        };
+        assert!(f == 0.0 && f.is_sign_negative(), "{}", input);
+    }
+    assert!(matches!(number("0"), Num::U32(0)));
+}
+
+/// The error `input` fails with, as its variant name and offset.
+fn rejection(input: &str) -> (&'static str, usize) {
+    let error = arjer::parse(input).unwrap_err();
+    let kind = match &error {
+        ParseError::InvalidNumber { .. } => "InvalidNumber",
+        ParseError::UnexpectedToken { .. } => "UnexpectedToken",
+        ParseError::EOF { .. } => "EOF",
+        ParseError::TrailingCharacters { .. } => "TrailingCharacters",
+        other => panic!("{}: unexpected {:?}", input, other),
+    };
+    (kind, error.location().offset)
+}
+
+#[test]
+fn rejected_numbers() {
+    assert_eq!(rejection("01"), ("InvalidNumber", 0));
+    assert_eq!(rejection("-01"), ("InvalidNumber", 0));
+    assert_eq!(rejection("1.2.3"), ("TrailingCharacters", 3));
+    assert_eq!(rejection("-"), ("EOF", 1));
+    assert_eq!(rejection("1."), ("EOF", 2));
+    assert_eq!(rejection("1e"), ("EOF", 2));
+    assert_eq!(rejection("1e+"), ("EOF", 3));
+    assert_eq!(rejection("+1"), ("UnexpectedToken", 0));
+    assert_eq!(rejection("-a"), ("UnexpectedToken", 1));
+    assert_eq!(rejection(".5"), ("UnexpectedToken", 0));
+    assert_eq!(rejection("[1.]"), ("UnexpectedToken", 3));
+    assert_eq!(rejection("[1, 01]"), ("InvalidNumber", 4));
+    assert_eq!(rejection("1e400"), ("InvalidNumber", 0));
+}
+
+#[test]
+fn rejected_numbers_are_located() {
+    let error = arjer::parse("{\"a\":\n  [1, 1.]}").unwrap_err();
+    let at = error.location();
+    assert_eq!((at.offset, at.line, at.column), (14, 2, 9));
+    assert!(error.to_string().contains("$.a[1]"), "{}", error);
+
+    match arjer::parse("[0, 012]").unwrap_err() {
+        ParseError::InvalidNumber { value, at, .. } => {
+            assert_eq!(value, "01");
+            assert_eq!(at.offset, 4);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn try_from_follows_the_json_grammar() {
+    let num = |s: &str| Num::try_from(s.to_string()).map_err(Box::new);
+    assert!(matches!(num("12"), Ok(Num::U32(12))));
+    assert!(matches!(num("-12"), Ok(Num::I64(-12))));
+    assert!(matches!(num("-0"), Ok(Num::F(f)) if f.is_sign_negative()));
+    assert!(matches!(num("1.5e3"), Ok(Num::F(f)) if f == 1500.0));
+    for invalid in [
+        "01", "+1", "1.", ".5", "1.2.3", " 1", "1 ", "1e", "", "0x10", "NaN",
+    ] {
+        assert!(num(invalid).is_err(), "{:?}", invalid);
+    }
+    assert!(
+        matches!(num("01"), Err(e) if matches!(&*e, ParseError::InvalidNumber { value, .. } if value == "01"))
+    );
+    // errors are located in the string
+    let e = num("12x").unwrap_err();
+    assert!(matches!(*e, ParseError::UnexpectedToken { .. }));
+    assert_eq!((e.location().offset, e.location().column), (2, 3));
+    // an integer too large for an `f64` is still reported as a number
+    let huge = "9".repeat(400);
+    match *num(&huge).unwrap_err() {
+        ParseError::InvalidNumber { tpe, value, .. } => {
+            assert_eq!(tpe, "number");
+            assert_eq!(value, huge);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn equality_is_by_value() {
+    assert_eq!(Num::U32(1), Num::U64(1));
+    assert_eq!(Num::U32(1), Num::F(1.0));
+    assert_eq!(Num::I64(-3), Num::F(-3.0));
+    assert_ne!(Num::I64(-1), Num::U32(1));
+    assert_ne!(Num::F(0.5), Num::U32(0));
+    assert_ne!(Num::F(f64::NAN), Num::F(f64::NAN));
+    assert_eq!(number("100"), number("1e2"));
+}
+
+#[test]
+fn compare_across_representations() {
+    assert_eq!(Num::U32(1).compare(&Num::F(1.0)), Some(Ordering::Equal));
+    assert_eq!(Num::I64(-1).compare(&Num::U64(1)), Some(Ordering::Less));
+    assert_eq!(Num::F(0.5).compare(&Num::U32(0)), Some(Ordering::Greater));
+    assert_eq!(Num::F(-0.5).compare(&Num::I64(-1)), Some(Ordering::Greater));
+    assert_eq!(Num::F(f64::NAN).compare(&Num::U32(0)), None);
+    assert_eq!(
+        Num::U32(0).compare(&Num::F(f64::INFINITY)),
+        Some(Ordering::Less)
+    );
+}
+
+#[test]
+fn compare_is_exact_above_2_pow_53() {
+    let two_53 = 9007199254740992_u64;
+    // 2^53 + 1 has no f64 of its own and rounds to 2^53
+    assert_eq!(
+        Num::U64(two_53 + 1).compare(&Num::F(two_53 as f64)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Num::F(two_53 as f64).compare(&Num::U64(two_53 + 1)),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        Num::U64(u64::MAX).compare(&Num::F(18446744073709551616.0)),
+        Some(Ordering::Less)
+    );
+    assert_ne!(number("9007199254740993"), number("9007199254740992.0"));
+}
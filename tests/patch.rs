@@ -0,0 +1,290 @@
+mod common;
+
+use arjer::json::{JsValue, Map, Num, PointerError};
+use arjer::patch::{apply_patch, diff, PatchError};
+use arjer::{DuplicateKeys, ParseOptions};
+use common::parse;
+use proptest::prelude::*;
+
+fn patched(doc: &str, patch: &str) -> Result<JsValue, PatchError> {
+    let mut doc = parse(doc);
+    apply_patch(&mut doc, &parse(patch)).map(|()| doc)
+}
+
+// RFC 6902, appendix A: (document, patch, result)
+const APPLIED: &[(&str, &str, &str)] = &[
+    // A.1
+    (
+        r#"{"foo":"bar"}"#,
+        r#"[{"op":"add","path":"/baz","value":"qux"}]"#,
+        r#"{"baz":"qux","foo":"bar"}"#,
+    ),
+    // A.2
+    (
+        r#"{"foo":["bar","baz"]}"#,
+        r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#,
+        r#"{"foo":["bar","qux","baz"]}"#,
+    ),
+    // A.3
+    (
+        r#"{"baz":"qux","foo":"bar"}"#,
+        r#"[{"op":"remove","path":"/baz"}]"#,
+        r#"{"foo":"bar"}"#,
+    ),
+    // A.4
+    (
+        r#"{"foo":["bar","qux","baz"]}"#,
+        r#"[{"op":"remove","path":"/foo/1"}]"#,
+        r#"{"foo":["bar","baz"]}"#,
+    ),
+    // A.5
+    (
+        r#"{"baz":"qux","foo":"bar"}"#,
+        r#"[{"op":"replace","path":"/baz","value":"boo"}]"#,
+        r#"{"baz":"boo","foo":"bar"}"#,
+    ),
+    // A.6
+    (
+        r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#,
+        r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#,
+        r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#,
+    ),
+    // A.7
+    (
+        r#"{"foo":["all","grass","cows","eat"]}"#,
+        r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#,
+        r#"{"foo":["all","cows","eat","grass"]}"#,
+    ),
+    // A.8
+    (
+        r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+        r#"[{"op":"test","path":"/baz","value":"qux"},{"op":"test","path":"/foo/1","value":2}]"#,
+        r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+    ),
+    // A.10
+    (
+        r#"{"foo":"bar"}"#,
+        r#"[{"op":"add","path":"/child","value":{"grandchild":{}}}]"#,
+        r#"{"foo":"bar","child":{"grandchild":{}}}"#,
+    ),
+    // A.11
+    (
+        r#"{"foo":"bar"}"#,
+        r#"[{"op":"add","path":"/baz","value":"qux","xyz":123}]"#,
+        r#"{"foo":"bar","baz":"qux"}"#,
+    ),
+    // A.14
+    (
+        r#"{"/":9,"~1":10}"#,
+        r#"[{"op":"test","path":"/~01","value":10}]"#,
+        r#"{"/":9,"~1":10}"#,
+    ),
+    // A.16
+    (
+        r#"{"foo":["bar"]}"#,
+        r#"[{"op":"add","path":"/foo/-","value":["abc","def"]}]"#,
+        r#"{"foo":["bar",["abc","def"]]}"#,
+    ),
+];
+
+#[test]
+fn rfc_examples() {
+    for (doc, patch, result) in APPLIED {
+        assert_eq!(patched(doc, patch), Ok(parse(result)), "{}", patch);
+    }
+}
+
+#[test]
+fn rfc_error_examples() {
+    // A.9
+    assert_eq!(
+        patched(
+            r#"{"baz":"qux"}"#,
+            r#"[{"op":"test","path":"/baz","value":"bar"}]"#
+        ),
+        Err(PatchError::TestFailed {
+            index: 0,
+            path: "/baz".into()
+        })
+    );
+    // A.12
+    assert!(matches!(
+        patched(
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/baz/bat","value":"qux"}]"#
+        ),
+        Err(PatchError::Pointer {
+            index: 0,
+            error: PointerError::NoSuchKey { .. }
+        })
+    ));
+    // A.13: the duplicate `op` makes the patch invalid JSON under a strict policy
+    let reject = ParseOptions {
+        duplicate_keys: DuplicateKeys::Reject,
+        ..ParseOptions::default()
+    };
+    assert!(arjer::parse_with(
+        r#"[{"op":"add","path":"/baz","value":"qux","op":"remove"}]"#,
+        &reject
+    )
+    .is_err());
+    // A.15
+    assert_eq!(
+        patched(
+            r#"{"/":9,"~1":10}"#,
+            r#"[{"op":"test","path":"/~01","value":"10"}]"#
+        ),
+        Err(PatchError::TestFailed {
+            index: 0,
+            path: "/~01".into()
+        })
+    );
+}
+
+#[test]
+fn failed_patches_leave_the_document_untouched() {
+    let original = parse(r#"{"a":1,"b":[1,2],"c":{"d":true}}"#);
+    let failing = [
+        (
+            r#"[
+                {"op":"add","path":"/e","value":3},
+                {"op":"remove","path":"/b/0"},
+                {"op":"replace","path":"/a","value":5},
+                {"op":"test","path":"/a","value":1}
+            ]"#,
+            3,
+        ),
+        (
+            r#"[
+                {"op":"move","from":"/c","path":"/f"},
+                {"op":"remove","path":"/c/d"}
+            ]"#,
+            1,
+        ),
+        (
+            r#"[
+                {"op":"copy","from":"/b","path":"/b/-"},
+                {"op":"move","from":"/b","path":"/b/0"}
+            ]"#,
+            1,
+        ),
+        (
+            r#"[{"op":"remove","path":"/a"},{"op":"frobnicate","path":"/b"}]"#,
+            1,
+        ),
+        (
+            r#"[{"op":"remove","path":"/a"},{"op":"add","path":"/b/3","value":0}]"#,
+            1,
+        ),
+    ];
+    for (patch, index) in failing {
+        let mut doc = original.clone();
+        let error = apply_patch(&mut doc, &parse(patch)).unwrap_err();
+        assert_eq!(error.index(), Some(index), "{}", patch);
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("operation {}", index)),
+            "{}",
+            error
+        );
+        assert_eq!(doc, original);
+    }
+    assert_eq!(
+        apply_patch(&mut original.clone(), &parse("{}")),
+        Err(PatchError::NotAnArray)
+    );
+}
+
+#[test]
+fn diff_patches() {
+    let cases = [
+        (r#"{"a":1,"b":2}"#, r#"{"a":1,"c":2}"#),
+        ("[1,2,3,4,5]", "[1,3,4,6,5]"),
+        ("[1,2,3]", "[3,2,1]"),
+        (
+            r#"{"a":[{"b":1},{"c":2}]}"#,
+            r#"{"a":[{"b":1,"x":0},{"c":2}]}"#,
+        ),
+        (r#"{"a":[]}"#, r#"{"a":{}}"#),
+        ("1", "[1]"),
+    ];
+    for (from, to) in cases {
+        let (from, to) = (parse(from), parse(to));
+        let mut doc = from.clone();
+        apply_patch(&mut doc, &diff(&from, &to)).unwrap();
+        assert_eq!(doc, to);
+    }
+    assert_eq!(diff(&parse("[1,2]"), &parse("[1,2]")), parse("[]"));
+    assert_eq!(
+        diff(&parse(r#"{"a":[1,2,3]}"#), &parse(r#"{"a":[1,3]}"#)),
+        parse(r#"[{"op":"remove","path":"/a/1"}]"#)
+    );
+}
+
+#[test]
+fn shifted_elements_are_kept_on_a_tie() {
+    // replacing both elements in place costs as many operations as removing `1` and adding
+    // `3`; the latter is what happened, and it keeps `2`
+    let (from, to) = (parse("[1,2]"), parse("[2,3]"));
+    assert_eq!(
+        diff(&from, &to),
+        parse(r#"[{"op":"remove","path":"/0"},{"op":"add","path":"/1","value":3}]"#)
+    );
+    assert_eq!(
+        diff(
+            &parse(r#"{"a":["x","y","z"]}"#),
+            &parse(r#"{"a":["y","z","w"]}"#)
+        ),
+        parse(r#"[{"op":"remove","path":"/a/0"},{"op":"add","path":"/a/2","value":"w"}]"#)
+    );
+    // a clear win for pairing up by position is still taken
+    assert_eq!(
+        diff(&parse("[1,2,3]"), &parse("[4,5,6]")),
+        parse(
+            r#"[{"op":"replace","path":"/0","value":4},{"op":"replace","path":"/1","value":5},{"op":"replace","path":"/2","value":6}]"#
+        )
+    );
+}
+
+/// Values drawn from a small pool of keys, strings and numbers, so that two of them often
+/// share structure for `diff` to find.
+fn value() -> impl Strategy<Value = JsValue> {
+    let leaf = prop_oneof![
+        Just(JsValue::JsNull),
+        any::<bool>().prop_map(JsValue::JsBool),
+        (0u32..4).prop_map(|n| JsValue::JsNumber(Num::U32(n))),
+        Just(JsValue::JsNumber(Num::F(0.5))),
+        "[ab~/]{0,2}".prop_map(JsValue::JsString),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(JsValue::JsArray),
+            prop::collection::vec(("[ab~/]{0,2}", inner), 0..6)
+                .prop_map(|members| JsValue::JsObject(members.into_iter().collect::<Map>())),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn applying_a_diff_gives_its_target(from in value(), to in value()) {
+        let mut doc = from.clone();
+        apply_patch(&mut doc, &diff(&from, &to)).unwrap();
+        prop_assert_eq!(doc, to);
+    }
+
+    #[test]
+    fn applying_an_array_diff_gives_its_target(
+        from in prop::collection::vec(0u32..4, 0..12),
+        to in prop::collection::vec(0u32..4, 0..12),
+    ) {
+        let array = |items: Vec<u32>| {
+            JsValue::JsArray(items.into_iter().map(|n| JsValue::JsNumber(Num::U32(n))).collect())
+        };
+        let (from, to) = (array(from), array(to));
+        let mut doc = from.clone();
+        apply_patch(&mut doc, &diff(&from, &to)).unwrap();
+        prop_assert_eq!(doc, to);
+    }
+}
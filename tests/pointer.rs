@@ -0,0 +1,222 @@
+mod common;
+
+use arjer::json::{JsValue, JsonPointer, PointerError};
+use common::parse;
+
+/// `input` written back out compactly, to compare documents by.
+fn json(input: &str) -> String {
+    parse(input).to_string()
+}
+
+fn text(value: &JsValue) -> String {
+    value.to_string()
+}
+
+fn pointer(s: &str) -> JsonPointer {
+    JsonPointer::parse(s).unwrap()
+}
+
+// RFC 6901, section 5
+const DOC: &str = r#"{
+    "foo": ["bar", "baz"],
+    "": 0,
+    "a/b": 1,
+    "c%d": 2,
+    "e^f": 3,
+    "g|h": 4,
+    "i\\j": 5,
+    "k\"l": 6,
+    " ": 7,
+    "m~n": 8
+}"#;
+
+#[test]
+fn rfc_examples() {
+    let doc = parse(DOC);
+    let cases = [
+        ("", DOC),
+        ("/foo", r#"["bar", "baz"]"#),
+        ("/foo/0", r#""bar""#),
+        ("/", "0"),
+        ("/a~1b", "1"),
+        ("/c%d", "2"),
+        ("/e^f", "3"),
+        ("/g|h", "4"),
+        (r"/i\j", "5"),
+        (r#"/k"l"#, "6"),
+        ("/ ", "7"),
+        ("/m~0n", "8"),
+    ];
+    for (p, expected) in cases {
+        assert_eq!(doc.pointer(p).map(text), Ok(json(expected)), "{:?}", p);
+        // rendering gives back the pointer that was parsed
+        assert_eq!(pointer(p).to_string(), p);
+    }
+}
+
+#[test]
+fn tokens_are_unescaped() {
+    assert!(pointer("").is_root());
+    assert_eq!(pointer("/").tokens(), [""]);
+    assert_eq!(pointer("//").tokens(), ["", ""]);
+    // `~01` is `~1` unescaped once, not `/`
+    assert_eq!(pointer("/~01/a~1b~0").tokens(), ["~1", "a/b~"]);
+    let mut built = JsonPointer::root();
+    built.push("a/b");
+    built.push("~");
+    assert_eq!(built.to_string(), "/a~1b/~0");
+    assert_eq!(built, pointer("/a~1b/~0"));
+    assert_eq!(built.pop().as_deref(), Some("~"));
+    assert!(pointer("/a/b").starts_with(&pointer("/a")));
+    assert!(!pointer("/ab").starts_with(&pointer("/a")));
+    assert_eq!(
+        pointer("/a/b~1c").split_last(),
+        Some((pointer("/a"), "b/c"))
+    );
+    assert!(JsonPointer::root().split_last().is_none());
+}
+
+#[test]
+fn syntax_errors() {
+    let syntax = |s: &str, offset| PointerError::Syntax {
+        pointer: s.into(),
+        offset,
+    };
+    for (s, offset) in [
+        ("a", 0),
+        (" /a", 0),
+        ("/~2", 1),
+        ("/a~2", 2),
+        ("/ok/~", 4),
+        ("/ok/x~a", 5),
+        ("/é/~", 4),
+    ] {
+        assert_eq!(JsonPointer::parse(s), Err(syntax(s, offset)), "{:?}", s);
+    }
+    assert_eq!(
+        JsonPointer::parse("/~2").unwrap_err().to_string(),
+        "`/~2` is not a JSON pointer (at byte 1)"
+    );
+    assert_eq!(parse("{}").pointer("/a~").map(text), Err(syntax("/a~", 2)));
+}
+
+#[test]
+fn lookup_errors() {
+    let doc = parse(DOC);
+    let err = |p: &str| doc.pointer(p).unwrap_err();
+    assert_eq!(
+        err("/nope/x"),
+        PointerError::NoSuchKey {
+            pointer: "/nope".into()
+        }
+    );
+    assert_eq!(
+        err("/foo/2"),
+        PointerError::IndexOutOfBounds {
+            pointer: "/foo/2".into(),
+            index: 2,
+            len: 2
+        }
+    );
+    for invalid in [
+        "/foo/01", "/foo/00", "/foo/-1", "/foo/+1", "/foo/1a", "/foo/", "/foo/ 1",
+    ] {
+        assert_eq!(
+            err(invalid),
+            PointerError::InvalidIndex {
+                pointer: invalid.into()
+            }
+        );
+    }
+    assert_eq!(
+        err("/foo/0/x"),
+        PointerError::NotAContainer {
+            pointer: "/foo/0/x".into()
+        }
+    );
+    // a leading zero is just part of the name on an object
+    assert_eq!(parse(r#"{"01":1}"#).pointer("/01").map(text), Ok(json("1")));
+    assert_eq!(
+        err("/foo/01").to_string(),
+        "`/foo/01` does not end in a valid array index"
+    );
+}
+
+#[test]
+fn dash_appends_on_insert_but_names_nothing_on_get() {
+    let mut doc = parse(DOC);
+    assert_eq!(
+        doc.pointer("/foo/-").map(text),
+        Err(PointerError::IndexOutOfBounds {
+            pointer: "/foo/-".into(),
+            index: 2,
+            len: 2
+        })
+    );
+    assert!(matches!(doc.insert_at("/foo/-", parse("1")), Ok(None)));
+    assert_eq!(
+        doc.pointer("/foo").map(text),
+        Ok(json(r#"["bar", "baz", 1]"#))
+    );
+    assert_eq!(
+        doc.remove_at("/foo/-").map(|v| text(&v)),
+        Err(PointerError::IndexOutOfBounds {
+            pointer: "/foo/-".into(),
+            index: 3,
+            len: 3
+        })
+    );
+    assert!(doc.replace_at("/foo/-", parse("2")).is_err());
+    // on an object `-` is an ordinary key
+    assert!(matches!(doc.insert_at("/-", parse("9")), Ok(None)));
+    assert_eq!(doc.pointer("/-").map(text), Ok(json("9")));
+}
+
+#[test]
+fn insert_remove_and_replace() {
+    let mut doc = parse(r#"{"a": [1, 2], "b": {"c": 3}}"#);
+    // inserting shifts later elements up, and the index right after the last is allowed
+    assert!(matches!(doc.insert_at("/a/0", parse("0")), Ok(None)));
+    assert!(matches!(doc.insert_at("/a/3", parse("3")), Ok(None)));
+    assert_eq!(
+        doc.insert_at("/a/5", parse("5")).map(|_| ()),
+        Err(PointerError::IndexOutOfBounds {
+            pointer: "/a/5".into(),
+            index: 5,
+            len: 4
+        })
+    );
+    assert_eq!(
+        doc.insert_at("/b/c", parse("4"))
+            .map(|v| v.map(|v| text(&v))),
+        Ok(Some(json("3")))
+    );
+    assert_eq!(doc.remove_at("/a/1").map(|v| text(&v)), Ok(json("1")));
+    assert_eq!(
+        doc.replace_at("/a/0", parse("-1")).map(|v| text(&v)),
+        Ok(json("0"))
+    );
+    assert_eq!(
+        doc.replace_at("/b/x", parse("0")).map(|_| ()),
+        Err(PointerError::NoSuchKey {
+            pointer: "/b/x".into()
+        })
+    );
+    assert_eq!(text(&doc), json(r#"{"a": [-1, 2, 3], "b": {"c": 4}}"#));
+
+    *doc.pointer_mut("/b/c").unwrap() = parse("null");
+    assert_eq!(doc.pointer("/b").map(text), Ok(json(r#"{"c": null}"#)));
+    assert_eq!(
+        doc.insert_at("/a/0/x", parse("0")).map(|_| ()),
+        Err(PointerError::NotAContainer {
+            pointer: "/a/0/x".into()
+        })
+    );
+    assert_eq!(doc.remove_at("").map(|_| ()), Err(PointerError::Root));
+    let before = text(&doc);
+    assert_eq!(
+        doc.insert_at("", parse("1")).map(|v| v.map(|v| text(&v))),
+        Ok(Some(before))
+    );
+    assert_eq!(text(&doc), "1");
+}
@@ -0,0 +1,177 @@
+use arjer::error::ParseError;
+use arjer::{Event, Feed, PushParser};
+
+/// The events a parser produced, and the error that stopped it, if one did.
+type Outcome = (Vec<Event<'static>>, Option<String>);
+
+/// An error as far as it must agree between the parsers: its message and location. The
+/// excerpt is left out, since a push parser only has the input it hasn't consumed yet to
+/// take it from.
+fn describe(e: &ParseError) -> String {
+    let at = e.location();
+    let message = e.to_string();
+    let first_line = message.lines().next().unwrap();
+    format!(
+        "{} [offset {}, line {}, column {}, path {:?}]",
+        first_line, at.offset, at.line, at.column, at.path
+    )
+}
+
+const GOOD: &[&str] = &[
+    r#"{"name": "Ann", "age": 31, "tags": ["a", "b"], "nested": {"ok": true, "no": null}}"#,
+    r#"[1, -2, 3.5, -0.25e-3, 12345678901234567890, 1E+2]"#,
+    r#""escapes \" \\ \/ \b\f\n\r\t é 😀""#,
+    "\"multi-byte é ü 日本 😀\"",
+    "  [true , false,null ]  \n",
+    "123",
+    "-0.5 ",
+    "[]",
+    "{}",
+    "[[[[{}]]]]",
+];
+
+const BAD: &[&str] = &[
+    r#"{"a": [1, {"b": [true, fals]}]}"#,
+    "{\"a\":\n [1, {\"b\": [true 1]}]}",
+    r#"{"a" 1}"#,
+    "[1,]",
+    "{} x",
+    "[1, 2",
+    "",
+    "  ",
+    r#"["\ud800A"]"#,
+    "[12.]",
+    "[01]",
+    "{\"x\":\n\n   \"ééé\" 3}",
+    "[1] \n 2",
+    "tru",
+    r#"["a\x"]"#,
+    "[\"tab\there\"]",
+    r#"{"a":1,}"#,
+];
+
+fn expected(input: &str) -> Outcome {
+    let mut events = vec![];
+    for event in arjer::events(input) {
+        match event {
+            Ok(event) => events.push(event.into_owned()),
+            Err(e) => return (events, Some(describe(&e))),
+        }
+    }
+    (events, None)
+}
+
+/// Feeds `chunks` one at a time, closing the parser once they are used up.
+fn push(chunks: &[&[u8]]) -> Outcome {
+    let mut parser = PushParser::new();
+    let mut chunks = chunks.iter();
+    let mut closed = false;
+    let mut events = vec![];
+    loop {
+        match parser.next_event() {
+            Ok(Feed::Event(event)) => events.push(event.into_owned()),
+            Ok(Feed::NeedMoreInput) => match chunks.next() {
+                Some(chunk) => parser.feed(chunk),
+                None => {
+                    assert!(!closed, "asked for more input after close");
+                    parser.close();
+                    closed = true;
+                }
+            },
+            Ok(Feed::Done) => return (events, None),
+            Err(e) => return (events, Some(describe(&e))),
+        }
+    }
+}
+
+fn check_all_splits(input: &str) {
+    let want = expected(input);
+    let bytes = input.as_bytes();
+    for i in 0..=bytes.len() {
+        let (a, b) = bytes.split_at(i);
+        assert_eq!(push(&[a, b]), want, "{:?} split at {}", input, i);
+    }
+    for i in 0..=bytes.len() {
+        for j in i..=bytes.len() {
+            let chunks = [&bytes[..i], &bytes[i..j], &bytes[j..]];
+            assert_eq!(push(&chunks), want, "{:?} split at {} and {}", input, i, j);
+        }
+    }
+    let bytewise = bytes.chunks(1).collect::<Vec<_>>();
+    assert_eq!(push(&bytewise), want, "{:?} byte at a time", input);
+}
+
+#[test]
+fn good_inputs_in_every_split() {
+    for input in GOOD {
+        check_all_splits(input);
+    }
+}
+
+#[test]
+fn bad_inputs_in_every_split() {
+    for input in BAD {
+        check_all_splits(input);
+    }
+}
+
+#[test]
+fn large_input_in_chunks() {
+    let input = include_str!("data/large.json");
+    let want = expected(input);
+    assert_eq!(want.1, None);
+    for size in [1, 2, 3, 7, 100, 4096] {
+        let chunks = input.as_bytes().chunks(size).collect::<Vec<_>>();
+        assert_eq!(push(&chunks), want, "chunks of {}", size);
+    }
+}
+
+/// A prefix of a valid document that stops inside a token is not an error yet: the parser
+/// has to wait for the rest.
+#[test]
+fn prefixes_ask_for_more_input() {
+    for input in GOOD {
+        let bytes = input.as_bytes();
+        for i in 0..bytes.len() {
+            let mut parser = PushParser::new();
+            parser.feed(&bytes[..i]);
+            loop {
+                match parser.next_event() {
+                    Ok(Feed::Event(_)) => {}
+                    Ok(Feed::NeedMoreInput) => break,
+                    other => panic!("{:?} cut at {}: {:?}", input, i, other),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_utf8_is_an_error() {
+    for chunk in [1, 2, 100] {
+        let chunks = b"[\"a\xff\"]".chunks(chunk).collect::<Vec<_>>();
+        let (_, error) = push(&chunks);
+        assert!(error.unwrap().contains("UTF-8"), "chunks of {}", chunk);
+
+        let chunks = b"[\"a\xc3".chunks(chunk).collect::<Vec<_>>();
+        assert!(push(&chunks).1.is_some(), "chunks of {}", chunk);
+    }
+}
+
+/// An error that no further input could fix is reported as soon as it is fed, even at the
+/// very end of the chunk.
+#[test]
+fn hard_errors_do_not_wait_for_more_input() {
+    for input in ["[1,]", "[1, tx", "{\"a\" 1", "[nul,", "[\"\\q", "{} x"] {
+        let mut parser = PushParser::new();
+        parser.feed(input.as_bytes());
+        let error = loop {
+            match parser.next_event() {
+                Ok(Feed::Event(_)) => {}
+                Ok(other) => panic!("{:?}: {:?} before the error", input, other),
+                Err(e) => break describe(&e),
+            }
+        };
+        assert_eq!(Some(error), expected(input).1, "{:?}", input);
+    }
+}
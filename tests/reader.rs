@@ -0,0 +1,230 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::{Event, IoEventReader, ParseOptions};
+use common::parse;
+use std::io::{self, Read};
+
+/// Hands out its input a few bytes per `read`, cycling through `sizes`, so that tokens keep
+/// landing on refill boundaries.
+struct Trickle<'a> {
+    input: &'a [u8],
+    sizes: &'a [usize],
+    reads: usize,
+}
+
+impl<'a> Trickle<'a> {
+    fn new(input: &'a str, sizes: &'a [usize]) -> Self {
+        Self {
+            input: input.as_bytes(),
+            sizes,
+            reads: 0,
+        }
+    }
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.sizes[self.reads % self.sizes.len()];
+        self.reads += 1;
+        let n = size.min(buf.len()).min(self.input.len());
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+const READ_SIZES: &[&[usize]] = &[&[1], &[2], &[3], &[1, 2, 3], &[3, 1, 2, 2]];
+
+const GOOD: &[&str] = &[
+    r#""a string that is a good deal longer than any of the buffers used here""#,
+    "[12345678901234567890, -9223372036854775808, 3.14159265358979e-300, -0.0]",
+    r#"["\" \\ \/ \b \f \n \r \t", "é日", "😀 and 𝄞"]"#,
+    "[\"é\", \"日本語\", \"😀😀\", \"aé日😀\"]",
+    r#"["\u00e9\ud83d\ude00\u0041", "x\uD834\uDD1Ey"]"#,
+    "[true, false, null, true,false,null]",
+    r#"{"key with spaces": {"nested": [{"deeper": [[], {}]}]}, "k2": "A"}"#,
+    "  \n\t 42 \r\n ",
+    "true",
+];
+
+const BAD: &[&str] = &[
+    "[tru]",
+    "[nul, 1]",
+    "[1.5e]",
+    r#"["\ud800"]"#,
+    r#"["\uDE00x"]"#,
+    r#"["\q"]"#,
+    "[\"unterminated",
+    "{\"a\": 1} x",
+    "[1 2]",
+    "[\"é\" 1]",
+];
+
+/// An error as far as it must agree between the readers; the excerpt is left out, since a
+/// buffered reader only has what is still buffered to take it from.
+fn describe(e: &ParseError) -> String {
+    let at = e.location();
+    let message = e.to_string();
+    format!(
+        "{} [offset {}, line {}, column {}, path {:?}]",
+        message.lines().next().unwrap(),
+        at.offset,
+        at.line,
+        at.column,
+        at.path
+    )
+}
+
+fn collect<'a>(
+    events: impl Iterator<Item = Result<Event<'a>, ParseError>>,
+) -> (Vec<Event<'static>>, Option<String>) {
+    let mut out = vec![];
+    for event in events {
+        match event {
+            Ok(event) => out.push(event.into_owned()),
+            Err(e) => return (out, Some(describe(&e))),
+        }
+    }
+    (out, None)
+}
+
+#[test]
+fn from_reader_matches_parse() {
+    for input in GOOD.iter().chain(BAD) {
+        let want = arjer::parse(input)
+            .map(|v| format!("{:?}", v))
+            .map_err(|e| describe(&e));
+        for sizes in READ_SIZES {
+            let got = arjer::from_reader(Trickle::new(input, sizes))
+                .map(|v| format!("{:?}", v))
+                .map_err(|e| describe(&e));
+            assert_eq!(got, want, "{:?} read {:?} at a time", input, sizes);
+        }
+    }
+}
+
+#[test]
+fn small_buffers_match_slice_events() {
+    for input in GOOD.iter().chain(BAD) {
+        let want = collect(arjer::events(input));
+        for capacity in 1..=9 {
+            for sizes in READ_SIZES {
+                let reader = IoEventReader::with_capacity(
+                    Trickle::new(input, sizes),
+                    capacity,
+                    &ParseOptions::default(),
+                );
+                assert_eq!(
+                    collect(reader),
+                    want,
+                    "{:?} through {} bytes, read {:?} at a time",
+                    input,
+                    capacity,
+                    sizes
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn large_document_through_a_small_buffer() {
+    let input = include_str!("data/large.json");
+    let want = collect(arjer::events(input));
+    for sizes in READ_SIZES {
+        let reader =
+            IoEventReader::with_capacity(Trickle::new(input, sizes), 7, &ParseOptions::default());
+        assert_eq!(collect(reader), want, "read {:?} at a time", sizes);
+    }
+    let reads = [1000, 333, 4096];
+    let got = arjer::from_reader(Trickle::new(input, &reads)).unwrap();
+    assert_eq!(format!("{:?}", got), format!("{:?}", parse(input)));
+}
+
+#[test]
+fn invalid_utf8_split_across_reads() {
+    let input = b"[\"ab\xe6\x97\"]";
+    for sizes in READ_SIZES {
+        let reader = Trickle {
+            input,
+            sizes,
+            reads: 0,
+        };
+        let error = arjer::from_reader(reader).unwrap_err();
+        assert!(
+            matches!(error, ParseError::InvalidUtf8 { .. }),
+            "{:?}",
+            error
+        );
+    }
+}
+
+/// Hands out `.0` one chunk per `read`.
+struct Chunks<'a>(&'a [&'a str]);
+
+impl Read for Chunks<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some((chunk, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        buf[..chunk.len()].copy_from_slice(chunk.as_bytes());
+        self.0 = rest;
+        Ok(chunk.len())
+    }
+}
+
+/// Fails the test if it is read from.
+struct Unread;
+
+impl Read for Unread {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        panic!("read past input that should already have failed");
+    }
+}
+
+#[test]
+fn errors_at_the_end_of_a_chunk_are_reported_at_once() {
+    // each of these is wrong whatever the next read brings
+    for chunk in [
+        "[1,]",
+        "[1, tx",
+        "{\"a\" 1",
+        "[\"\\q",
+        "[1 2",
+        "[nul,",
+        "{\"a\": 1}}",
+    ] {
+        let error = arjer::from_reader(Chunks(&[chunk]).chain(Unread)).unwrap_err();
+        let want = arjer::parse(chunk).unwrap_err();
+        assert_eq!(describe(&error), describe(&want), "{:?}", chunk);
+    }
+}
+
+#[test]
+fn tokens_cut_off_by_a_chunk_are_read_again() {
+    for chunks in [
+        &["[tr", "ue]"][..],
+        &["[fals", "e]"],
+        &["[n", "ull]"],
+        &["[\"ab", "c\"]"],
+        &["[12", "34]"],
+        &["[1.", "5]"],
+        &["[1e", "+2]"],
+        &["[-", "1]"],
+        &["[\"\\", "n\"]"],
+        &["[\"\\u00", "e9\"]"],
+        &["[\"\\ud83d", "\\ude00\"]"],
+        &["[\"\\ud83d\\", "ude00\"]"],
+        &["{\"a\"", ": 1", "}"],
+    ] {
+        let got = arjer::from_reader(Chunks(chunks)).map_err(Box::new);
+        let want = parse(&chunks.concat());
+        assert_eq!(
+            format!("{:?}", got.unwrap()),
+            format!("{:?}", want),
+            "{:?}",
+            chunks
+        );
+    }
+}
@@ -0,0 +1,355 @@
+mod common;
+
+use arjer::json::JsonPointer;
+use arjer::schema::{Schema, SchemaError};
+use common::parse;
+
+fn schema(input: &str) -> Schema {
+    Schema::compile(&parse(input)).unwrap()
+}
+
+fn pointer(s: &str) -> JsonPointer {
+    JsonPointer::parse(s).unwrap()
+}
+
+/// Checks that `schema` accepts every instance in `valid` and rejects every one in
+/// `invalid`, both with `is_valid` and with `validate`.
+fn check(schema_src: &str, valid: &[&str], invalid: &[&str]) {
+    let schema = schema(schema_src);
+    for instance in valid {
+        let instance = parse(instance);
+        assert!(
+            schema.is_valid(&instance),
+            "{} should accept {}",
+            schema_src,
+            instance
+        );
+        assert_eq!(schema.validate(&instance), Ok(()));
+    }
+    for instance in invalid {
+        let instance = parse(instance);
+        assert!(
+            !schema.is_valid(&instance),
+            "{} should reject {}",
+            schema_src,
+            instance
+        );
+        assert!(schema.validate(&instance).is_err());
+    }
+}
+
+/// The `(instance path, schema path)` of every error `schema` reports for `instance`.
+fn error_paths(schema_src: &str, instance: &str) -> Vec<(String, String)> {
+    schema(schema_src)
+        .validate(&parse(instance))
+        .unwrap_err()
+        .into_iter()
+        .map(|e| (e.instance_path.to_string(), e.schema_path.to_string()))
+        .collect()
+}
+
+fn paths(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(i, s)| (i.to_string(), s.to_string()))
+        .collect()
+}
+
+#[test]
+fn type_keyword() {
+    check(r#"{"type":"string"}"#, &[r#""a""#], &["1", "null", "[]"]);
+    check(
+        r#"{"type":"integer"}"#,
+        &["1", "-1", "1.0"],
+        &["1.5", r#""1""#],
+    );
+    check(r#"{"type":"number"}"#, &["1", "1.5", "-2e3"], &["true"]);
+    check(
+        r#"{"type":["null","boolean"]}"#,
+        &["null", "false"],
+        &["0", "{}"],
+    );
+}
+
+#[test]
+fn boolean_schemas() {
+    check("true", &["1", "{}", "null"], &[]);
+    check("false", &[], &["1", "{}", "null"]);
+}
+
+#[test]
+fn properties_and_required() {
+    let s = r#"{
+        "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+        "required": ["name"]
+    }"#;
+    check(
+        s,
+        &[r#"{"name":"a"}"#, r#"{"name":"a","age":3,"x":null}"#, "[]"],
+        &[r#"{}"#, r#"{"name":1}"#, r#"{"name":"a","age":"3"}"#],
+    );
+}
+
+#[test]
+fn pattern_and_additional_properties() {
+    let s = r#"{
+        "properties": {"id": true},
+        "patternProperties": {"^x-": {"type": "string"}},
+        "additionalProperties": false
+    }"#;
+    check(
+        s,
+        &[r#"{"id":1,"x-a":"b"}"#, r#"{}"#],
+        &[r#"{"x-a":1}"#, r#"{"other":1}"#],
+    );
+    check(
+        r#"{"propertyNames": {"maxLength": 3}}"#,
+        &[r#"{"abc":1}"#],
+        &[r#"{"abcd":1}"#],
+    );
+}
+
+#[test]
+fn prefix_items_and_items() {
+    let s = r#"{
+        "prefixItems": [{"type": "string"}, {"type": "integer"}],
+        "items": {"type": "boolean"}
+    }"#;
+    check(
+        s,
+        &["[]", r#"["a"]"#, r#"["a",1]"#, r#"["a",1,true,false]"#],
+        &[r#"[1]"#, r#"["a","b"]"#, r#"["a",1,null]"#],
+    );
+    check(
+        r#"{"prefixItems": [true], "items": false}"#,
+        &["[]", "[1]"],
+        &["[1,2]"],
+    );
+}
+
+#[test]
+fn contains_with_bounds() {
+    let s = r#"{"contains": {"type": "integer"}, "minContains": 2, "maxContains": 3}"#;
+    check(s, &["[1,2]", r#"[1,"a",2,3]"#], &["[1]", "[1,2,3,4]", "[]"]);
+}
+
+#[test]
+fn enum_and_const() {
+    check(
+        r#"{"enum": [1, "a", {"b": [null]}]}"#,
+        &["1", "1.0", r#""a""#, r#"{"b":[null]}"#],
+        &["2", r#""b""#, r#"{"b":[]}"#],
+    );
+    check(
+        r#"{"const": {"a": [1, 2]}}"#,
+        &[r#"{"a":[1,2]}"#],
+        &[r#"{"a":[2,1]}"#],
+    );
+}
+
+#[test]
+fn numeric_bounds() {
+    check(
+        r#"{"minimum": 1, "maximum": 3}"#,
+        &["1", "2.5", "3", r#""x""#],
+        &["0", "0.99", "3.01"],
+    );
+    check(
+        r#"{"exclusiveMinimum": 1, "exclusiveMaximum": 3}"#,
+        &["1.5", "2"],
+        &["1", "3"],
+    );
+    check(r#"{"multipleOf": 0.5}"#, &["1", "1.5", "-2"], &["1.25"]);
+    check(r#"{"multipleOf": 3}"#, &["9", "-3", "0"], &["10"]);
+}
+
+#[test]
+fn string_and_collection_bounds() {
+    check(
+        r#"{"minLength": 2, "maxLength": 3}"#,
+        &[r#""ab""#, r#""été""#, "5"],
+        &[r#""a""#, r#""abcd""#],
+    );
+    check(
+        r#"{"minItems": 1, "maxItems": 2, "uniqueItems": true}"#,
+        &["[1]", "[1,2]"],
+        &["[]", "[1,2,3]", "[1,1.0]"],
+    );
+    check(
+        r#"{"minProperties": 1, "maxProperties": 1}"#,
+        &[r#"{"a":1}"#],
+        &["{}", r#"{"a":1,"b":2}"#],
+    );
+    check(
+        r#"{"dependentRequired": {"card": ["billing"]}}"#,
+        &[r#"{}"#, r#"{"card":1,"billing":2}"#],
+        &[r#"{"card":1}"#],
+    );
+}
+
+#[test]
+fn pattern() {
+    check(
+        r#"{"pattern": "^[a-z]+[0-9]?$"}"#,
+        &[r#""abc""#, r#""abc1""#, "1"],
+        &[r#""ABC""#, r#""abc12""#],
+    );
+    check(r#"{"pattern": "b"}"#, &[r#""abc""#], &[r#""xyz""#]);
+}
+
+#[test]
+fn applicators() {
+    check(
+        r#"{"allOf": [{"minimum": 1}, {"maximum": 2}]}"#,
+        &["1", "2"],
+        &["0", "3"],
+    );
+    check(
+        r#"{"anyOf": [{"type": "string"}, {"minimum": 10}]}"#,
+        &[r#""a""#, "10"],
+        &["9", "-1.5"],
+    );
+    check(
+        r#"{"oneOf": [{"multipleOf": 2}, {"multipleOf": 3}]}"#,
+        &["2", "3", "4"],
+        &["6", "5"],
+    );
+    check(r#"{"not": {"type": "null"}}"#, &["1", "{}"], &["null"]);
+    check(
+        r#"{"if": {"type": "integer"}, "then": {"minimum": 0}, "else": {"type": "string"}}"#,
+        &["1", r#""a""#],
+        &["-1", "null"],
+    );
+    check(
+        r#"{"dependentSchemas": {"a": {"required": ["b"]}}}"#,
+        &[r#"{}"#, r#"{"a":1,"b":2}"#],
+        &[r#"{"a":1}"#],
+    );
+}
+
+#[test]
+fn references() {
+    let s = r##"{
+        "$defs": {
+            "node": {
+                "type": "object",
+                "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+                "required": ["name"]
+            },
+            "positive": {"$anchor": "positive", "exclusiveMinimum": 0}
+        },
+        "properties": {"root": {"$ref": "#/$defs/node"}, "count": {"$ref": "#positive"}}
+    }"##;
+    check(
+        s,
+        &[
+            r#"{"root":{"name":"a","children":[{"name":"b","children":[]}]}}"#,
+            r#"{"count":1}"#,
+        ],
+        &[
+            r#"{"root":{"name":"a","children":[{"children":[]}]}}"#,
+            r#"{"count":0}"#,
+        ],
+    );
+}
+
+#[test]
+fn reference_cycles_without_descent_terminate() {
+    // each of these would follow `$ref`s forever on the same instance
+    check(r##"{"$ref": "#"}"##, &["1", "{}", "[[]]"], &[]);
+    check(
+        r##"{"$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"}"##,
+        &["1"],
+        &[],
+    );
+    check(
+        r##"{"allOf": [{"$ref": "#"}, {"type": "integer"}]}"##,
+        &["1"],
+        &[r#""a""#],
+    );
+    check(
+        r##"{"anyOf": [{"$ref": "#"}], "minimum": 2}"##,
+        &["2"],
+        &["1"],
+    );
+}
+
+#[test]
+fn recursion_through_the_instance_still_applies() {
+    let s = r##"{"type": ["integer", "array"], "items": {"$ref": "#"}}"##;
+    check(s, &["1", "[1,[2,[]]]"], &[r#"[1,["a"]]"#]);
+}
+
+#[test]
+fn errors_locate_instance_and_keyword() {
+    let s = r#"{
+        "properties": {
+            "name": {"type": "string"},
+            "tags": {"items": {"minLength": 2}}
+        },
+        "required": ["id"]
+    }"#;
+    assert_eq!(
+        error_paths(s, r#"{"name":1,"tags":["ab","c","d"]}"#),
+        paths(&[
+            ("/name", "/properties/name/type"),
+            ("/tags/1", "/properties/tags/items/minLength"),
+            ("/tags/2", "/properties/tags/items/minLength"),
+            ("", "/required"),
+        ])
+    );
+}
+
+#[test]
+fn errors_through_references_name_the_target() {
+    let s = r##"{"$defs": {"n": {"maximum": 1}}, "items": {"$ref": "#/$defs/n"}}"##;
+    assert_eq!(
+        error_paths(s, "[0,2]"),
+        paths(&[("/1", "/$defs/n/maximum")])
+    );
+}
+
+#[test]
+fn error_messages() {
+    let errors = schema(r#"{"required": ["a", "b"], "type": "object"}"#)
+        .validate(&parse(r#"{"b":1}"#))
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "missing required `a`");
+    assert_eq!(
+        errors[0].to_string(),
+        "``: missing required `a` (schema `/required`)"
+    );
+
+    let errors = schema(r#"{"type": "string"}"#)
+        .validate(&parse("1.5"))
+        .unwrap_err();
+    assert_eq!(errors[0].message, "expected string, found number");
+}
+
+#[test]
+fn invalid_schemas_are_rejected() {
+    let compile = |s: &str| Schema::compile(&parse(s)).unwrap_err();
+    assert!(matches!(
+        compile(r#"{"properties": {"a": {"type": "strin"}}}"#),
+        SchemaError::InvalidKeyword { path, .. } if path == pointer("/properties/a/type")
+    ));
+    assert!(matches!(
+        compile(r#"{"minLength": -1}"#),
+        SchemaError::InvalidKeyword { path, .. } if path == pointer("/minLength")
+    ));
+    assert_eq!(
+        compile(r#"{"pattern": "("}"#),
+        SchemaError::InvalidPattern {
+            path: pointer("/pattern"),
+            pattern: "(".into()
+        }
+    );
+    assert_eq!(
+        compile(r##"{"items": {"$ref": "#/$defs/missing"}}"##),
+        SchemaError::UnresolvedRef {
+            path: pointer("/items/$ref"),
+            reference: "#/$defs/missing".into()
+        }
+    );
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::{parse_many, parse_many_with, DuplicateKeys, ParseOptions};
+use common::parse;
+
+/// A document in `Debug` form, or its error as the first line of `Display` and offset.
+type Document = Result<String, (String, usize)>;
+
+/// Every document in `input`.
+fn documents(input: &str) -> Vec<Document> {
+    parse_many(input)
+        .map(|result| result.map(|v| format!("{:?}", v)).map_err(describe))
+        .collect()
+}
+
+fn describe(e: ParseError) -> (String, usize) {
+    let message = e.to_string();
+    let message = message.split(" at line").next().unwrap().to_string();
+    (message, e.location().offset)
+}
+
+fn ok(input: &str) -> Document {
+    Ok(format!("{:?}", parse(input)))
+}
+
+fn err(message: &str, offset: usize) -> Document {
+    Err((message.to_string(), offset))
+}
+
+#[test]
+fn back_to_back_documents() {
+    assert_eq!(
+        documents(r#"{"a":1}{"b":[2]}[3,{}]"x"[]"#),
+        [
+            ok(r#"{"a":1}"#),
+            ok(r#"{"b":[2]}"#),
+            ok(r#"[3,{}]"#),
+            ok(r#""x""#),
+            ok("[]"),
+        ]
+    );
+    assert_eq!(
+        documents(" 1 2\n-3.5\ttrue null\r\n\"s\" "),
+        [
+            ok("1"),
+            ok("2"),
+            ok("-3.5"),
+            ok("true"),
+            ok("null"),
+            ok(r#""s""#)
+        ]
+    );
+    // a number only ends where something else begins
+    assert_eq!(documents("12"), [ok("12")]);
+    assert_eq!(documents("1[2]"), [ok("1"), ok("[2]")]);
+    assert_eq!(documents(""), []);
+    assert_eq!(documents(" \n "), []);
+}
+
+#[test]
+fn an_error_between_plain_documents_ends_the_iteration() {
+    assert_eq!(
+        documents(r#"{"a":1} [1,} 3"#),
+        [ok(r#"{"a":1}"#), err("unexpected token `}`", 11)]
+    );
+    assert_eq!(
+        documents("[1] {"),
+        [ok("[1]"), err("unexpected end of input", 5)]
+    );
+}
+
+#[test]
+fn rfc_7464_records() {
+    let input = "\x1e{\"a\":1}\n\x1e[1,2]\n\x1e\"x\"\n";
+    assert_eq!(
+        documents(input),
+        [ok(r#"{"a":1}"#), ok("[1,2]"), ok(r#""x""#)]
+    );
+    // empty records and whitespace between them are skipped
+    assert_eq!(documents("\x1e\x1e \n\x1e1\n\x1e\n"), [ok("1")]);
+}
+
+#[test]
+fn truncated_records_are_reported_and_skipped() {
+    // record offsets: 0, 9, 15, 20, 24
+    let input = "\x1e{\"a\":1}\n\x1e[1,2\n\x1e\"x\"\n\x1e42\n\x1e43";
+    assert_eq!(
+        documents(input),
+        [
+            ok(r#"{"a":1}"#),
+            err("unexpected end of input", 15),
+            ok(r#""x""#),
+            ok("42"),
+            // a number running up to the end may have been cut off
+            err("unexpected end of input", 27),
+        ]
+    );
+    assert_eq!(
+        documents("\x1e{\"a\":\x1etrue\n\x1e{} {}\n\x1e[]\n"),
+        [
+            err("unexpected end of input", 6),
+            ok("true"),
+            err("trailing characters after the top-level value", 16),
+            ok("[]"),
+        ]
+    );
+    // a separator inside a string cuts the record there
+    assert_eq!(
+        documents("\x1e\"ab\x1ecd\"\n\x1e1\n"),
+        [
+            err("unexpected end of input", 4),
+            err("unexpected token `c`", 5),
+            ok("1")
+        ]
+    );
+}
+
+#[test]
+fn records_after_plain_documents() {
+    assert_eq!(
+        documents("1 [2]\n\x1e{\"b\":3}\n\x1e4\n"),
+        [ok("1"), ok("[2]"), ok(r#"{"b":3}"#), ok("4")]
+    );
+}
+
+#[test]
+fn parse_options_apply_to_every_document() {
+    let opts = ParseOptions {
+        duplicate_keys: DuplicateKeys::Reject,
+        max_depth: 2,
+    };
+    let read: Vec<_> = parse_many_with("\x1e{\"a\":1,\"a\":2}\n\x1e[[[]]]\n\x1e[[]]\n", &opts)
+        .map(|r| {
+            r.map(|v| format!("{:?}", v)).map_err(|e| {
+                matches!(
+                    e,
+                    ParseError::DuplicateKey { .. } | ParseError::DepthLimitExceeded { .. }
+                )
+            })
+        })
+        .collect();
+    assert_eq!(
+        read,
+        [Err(true), Err(true), Ok(format!("{:?}", parse("[[]]")))]
+    );
+}
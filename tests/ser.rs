@@ -0,0 +1,194 @@
+mod common;
+
+use arjer::json::{JsValue, Num, PrettyConfig};
+use common::parse;
+use std::io::{self, Write};
+
+/// Checks that `written` parses back to `value`.
+fn reads_back(written: &str, value: &JsValue) {
+    assert_eq!(format!("{:?}", parse(written)), format!("{:?}", value));
+}
+
+fn string(s: &str) -> JsValue {
+    JsValue::JsString(s.into())
+}
+
+#[test]
+fn strings_are_escaped() {
+    let cases = [
+        ("a\"b", r#""a\"b""#),
+        ("back\\slash", r#""back\\slash""#),
+        ("\n\r\t\u{8}\u{c}", r#""\n\r\t\b\f""#),
+        ("\0 \u{1} \u{1f}", r#""\u0000 \u0001 \u001f""#),
+        // DEL, non-ASCII and the JavaScript line separators need no escaping in JSON
+        ("\u{7f} é 😀 \u{2028}", "\"\u{7f} é 😀 \u{2028}\""),
+        ("/", r#""/""#),
+        ("", r#""""#),
+    ];
+    for (s, expected) in cases {
+        let written = string(s).to_string();
+        assert_eq!(written, expected);
+        reads_back(&written, &string(s));
+    }
+    // every control character comes back out unchanged
+    let controls: String = (0..0x20u8).map(char::from).collect();
+    reads_back(&string(&controls).to_string(), &string(&controls));
+    // keys are escaped the same way
+    let object = parse(r#"{"k\"\u0001": "\\"}"#);
+    assert_eq!(object.to_string(), r#"{"k\"\u0001":"\\"}"#);
+}
+
+#[test]
+fn numbers() {
+    let number = |n| JsValue::JsNumber(n).to_string();
+    assert_eq!(number(Num::U32(7)), "7");
+    assert_eq!(number(Num::U64(u64::MAX)), "18446744073709551615");
+    assert_eq!(number(Num::I64(i64::MIN)), "-9223372036854775808");
+    assert_eq!(number(Num::F(2.0)), "2.0");
+    assert_eq!(number(Num::F(-0.0)), "-0.0");
+    assert_eq!(number(Num::F(1e300)), "1e300");
+    assert_eq!(number(Num::F(f64::NAN)), "null");
+    assert_eq!(number(Num::F(f64::NEG_INFINITY)), "null");
+    for written in ["2.0", "1e300", "1.5e-7", "0.1"] {
+        assert_eq!(parse(written).to_string(), written);
+    }
+}
+
+const DOC: &str = r#"{"b": [1, {"z": null, "a": []}], "a": {}, "c": "x"}"#;
+
+#[test]
+fn compact_output_and_display() {
+    let doc = parse(DOC);
+    let compact = r#"{"b":[1,{"z":null,"a":[]}],"a":{},"c":"x"}"#;
+    assert_eq!(doc.to_string(), compact);
+    assert_eq!(format!("{}", doc), compact);
+    let mut written = String::new();
+    doc.write_compact(&mut written).unwrap();
+    assert_eq!(written, compact);
+    let mut bytes = Vec::new();
+    doc.to_writer(&mut bytes).unwrap();
+    assert_eq!(bytes, compact.as_bytes());
+    // the alternate flag is the default pretty layout
+    assert_eq!(
+        format!("{:#}", doc),
+        doc.to_string_pretty(&PrettyConfig::default())
+    );
+    assert_eq!(format!("{:#}", parse("[]")), "[]");
+}
+
+#[test]
+fn pretty_layout() {
+    let doc = parse(DOC);
+    assert_eq!(
+        doc.to_string_pretty(&PrettyConfig::default()),
+        r#"{
+  "b": [
+    1,
+    {
+      "z": null,
+      "a": []
+    }
+  ],
+  "a": {},
+  "c": "x"
+}"#
+    );
+    let tabs = PrettyConfig {
+        indent: "\t".into(),
+        ..PrettyConfig::default()
+    };
+    assert_eq!(
+        parse(r#"{"a":[1]}"#).to_string_pretty(&tabs),
+        "{\n\t\"a\": [\n\t\t1\n\t]\n}"
+    );
+    let no_indent = PrettyConfig {
+        indent: String::new(),
+        ..PrettyConfig::default()
+    };
+    assert_eq!(
+        parse(r#"{"a":[1]}"#).to_string_pretty(&no_indent),
+        "{\n\"a\": [\n1\n]\n}"
+    );
+    // whatever the layout, the output parses back to the same document
+    for config in [PrettyConfig::default(), tabs, no_indent] {
+        reads_back(&doc.to_string_pretty(&config), &doc);
+    }
+}
+
+#[test]
+fn sorted_keys() {
+    let sorted = PrettyConfig {
+        indent: " ".into(),
+        sort_keys: true,
+        ..PrettyConfig::default()
+    };
+    assert_eq!(
+        parse(DOC).to_string_pretty(&sorted),
+        r#"{
+ "a": {},
+ "b": [
+  1,
+  {
+   "a": [],
+   "z": null
+  }
+ ],
+ "c": "x"
+}"#
+    );
+    // by code point, so uppercase sorts before lowercase and `é` after both
+    assert_eq!(
+        parse(r#"{"é":1,"b":2,"B":3,"":4}"#).to_string_pretty(&sorted),
+        "{\n \"\": 4,\n \"B\": 3,\n \"b\": 2,\n \"é\": 1\n}"
+    );
+}
+
+#[test]
+fn trailing_newline() {
+    let config = PrettyConfig {
+        trailing_newline: true,
+        ..PrettyConfig::default()
+    };
+    assert_eq!(parse("[1]").to_string_pretty(&config), "[\n  1\n]\n");
+    assert_eq!(parse("null").to_string_pretty(&config), "null\n");
+    assert_eq!(parse("{}").to_string_pretty(&config), "{}\n");
+    let mut bytes = Vec::new();
+    parse("[1]").to_writer_pretty(&mut bytes, &config).unwrap();
+    assert_eq!(bytes, b"[\n  1\n]\n");
+    // not without the option
+    assert_eq!(
+        parse("[1]").to_string_pretty(&PrettyConfig::default()),
+        "[\n  1\n]"
+    );
+}
+
+#[test]
+fn writer_errors_are_passed_on() {
+    /// Accepts `.0` more bytes, then fails.
+    struct Full(usize);
+
+    impl Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() > self.0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let doc = parse(DOC);
+    let e = doc.to_writer(Full(5)).unwrap_err();
+    assert_eq!(
+        (e.kind(), e.to_string()),
+        (io::ErrorKind::WriteZero, "full".into())
+    );
+    let e = doc
+        .to_writer_pretty(Full(5), &PrettyConfig::default())
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+    assert!(doc.to_writer(Full(100)).is_ok());
+}
@@ -0,0 +1,205 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::{JsValue, Num};
+use arjer::serde::Error;
+use arjer::{from_str, to_string};
+use common::parse;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// The message and offset of a `Custom` error.
+fn custom(e: Error) -> (String, Option<usize>) {
+    match e {
+        Error::Custom { message, offset } => (message, offset),
+        Error::Parse(e) => panic!("expected a custom error, got {}", e),
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Job {
+    title: String,
+    #[serde(rename = "self_employed", default)]
+    independent: bool,
+    tags: Vec<String>,
+    salary: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect(u32, u32),
+    Polygon { sides: u8, regular: bool },
+}
+
+#[test]
+fn structs_round_trip() {
+    let job = Job {
+        title: "dev \"ops\"".into(),
+        independent: true,
+        tags: vec!["a".into(), "b\n".into()],
+        salary: None,
+        note: None,
+    };
+    let text = to_string(&job).unwrap();
+    assert_eq!(
+        text,
+        r#"{"title":"dev \"ops\"","self_employed":true,"tags":["a","b\n"],"salary":null}"#
+    );
+    assert_eq!(from_str::<Job>(&text).unwrap(), job);
+    // a missing `Option` is `None`, a `default` member gets its default
+    assert_eq!(
+        from_str::<Job>(r#" {"tags":[],"title":"x","note":"n"} "#).unwrap(),
+        Job {
+            title: "x".into(),
+            independent: false,
+            tags: vec![],
+            salary: None,
+            note: Some("n".into()),
+        }
+    );
+}
+
+#[test]
+fn enums_of_every_variant_kind() {
+    let shapes = [
+        (Shape::Empty, r#""Empty""#),
+        (Shape::Circle(1.5), r#"{"Circle":1.5}"#),
+        (Shape::Rect(2, 3), r#"{"Rect":[2,3]}"#),
+        (
+            Shape::Polygon {
+                sides: 6,
+                regular: true,
+            },
+            r#"{"Polygon":{"sides":6,"regular":true}}"#,
+        ),
+    ];
+    for (shape, text) in shapes {
+        assert_eq!(to_string(&shape).unwrap(), text);
+        assert_eq!(from_str::<Shape>(text).unwrap(), shape);
+    }
+    assert!(from_str::<Shape>(r#""Circle""#).is_err());
+    assert!(from_str::<Shape>(r#"{"Empty":null,"Rect":[1,2]}"#).is_err());
+    let (message, _) = custom(from_str::<Shape>(r#""Square""#).unwrap_err());
+    assert!(
+        message.starts_with("unknown variant `Square`"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn options() {
+    assert_eq!(to_string(&Some(1)).unwrap(), "1");
+    assert_eq!(to_string(&None::<u8>).unwrap(), "null");
+    assert_eq!(from_str::<Option<u8>>("null").unwrap(), None);
+    assert_eq!(from_str::<Option<u8>>(" 7 ").unwrap(), Some(7));
+    assert_eq!(
+        from_str::<Vec<Option<bool>>>("[true,null]").unwrap(),
+        [Some(true), None]
+    );
+}
+
+#[test]
+fn maps_with_integer_keys() {
+    let map: BTreeMap<i32, &str> = [(-1, "a"), (10, "b")].into();
+    let text = to_string(&map).unwrap();
+    assert_eq!(text, r#"{"-1":"a","10":"b"}"#);
+    assert_eq!(from_str::<BTreeMap<i32, &str>>(&text).unwrap(), map);
+    let bytes: HashMap<u8, bool> = from_str(r#"{"255":true}"#).unwrap();
+    assert!(bytes[&255]);
+    assert!(from_str::<HashMap<u8, bool>>(r#"{"256":true}"#).is_err());
+    assert!(from_str::<HashMap<u8, bool>>(r#"{"x":true}"#).is_err());
+
+    // keys that aren't strings or integers can't be written
+    for e in [
+        to_string(&HashMap::from([(true, 1)])).unwrap_err(),
+        to_string(&HashMap::from([((1, 2), 1)])).unwrap_err(),
+        to_string(&BTreeMap::from([(Some('a'), 1)])).unwrap_err(),
+    ] {
+        assert_eq!(
+            custom(e),
+            ("map keys must be strings or integers".into(), None)
+        );
+    }
+}
+
+#[test]
+fn non_finite_floats_become_null() {
+    assert_eq!(to_string(&f64::NAN).unwrap(), "null");
+    assert_eq!(to_string(&[f64::INFINITY, 0.5]).unwrap(), "[null,0.5]");
+    assert_eq!(to_string(&f32::NEG_INFINITY).unwrap(), "null");
+    assert_eq!(to_string(&1.0f64).unwrap(), "1.0");
+}
+
+#[test]
+fn js_value_round_trips() {
+    let text = r#"{"a":[1,-2,18446744073709551615,2.5,"s",null,true],"b":{}}"#;
+    let value: JsValue = from_str(text).unwrap();
+    assert_eq!(value, parse(text));
+    assert_eq!(to_string(&value).unwrap(), text);
+    // integers keep the variant the parser gives them
+    assert!(matches!(
+        from_str::<JsValue>("[5000000000,-1]").unwrap(),
+        JsValue::JsArray(items) if matches!(
+            items[..],
+            [JsValue::JsNumber(Num::U64(5000000000)), JsValue::JsNumber(Num::I64(-1))]
+        )
+    ));
+    // and through another format
+    let via_serde_json: JsValue = serde_json::from_str(text).unwrap();
+    assert_eq!(via_serde_json, value);
+    assert_eq!(serde_json::to_string(&value).unwrap(), text);
+}
+
+#[test]
+fn unescaped_strings_are_borrowed() {
+    let text = r#"["plain","esc\"aped"]"#;
+    let strings: Vec<Cow<str>> = from_str(text).unwrap();
+    assert_eq!(strings, ["plain", "esc\"aped"]);
+    let borrowed: &str = from_str(r#""plain""#).unwrap();
+    assert_eq!(borrowed, "plain");
+    assert!(from_str::<&str>(r#""esc\"aped""#).is_err());
+}
+
+#[test]
+fn errors_carry_offsets() {
+    // a value that doesn't fit is reported at the start of its event
+    let text = r#"{"title":"x","tags":["a",2]}"#;
+    let (message, offset) = custom(from_str::<Job>(text).unwrap_err());
+    assert_eq!(message, "invalid type: integer `2`, expected a string");
+    assert_eq!(offset, Some(25));
+    assert_eq!(
+        from_str::<Job>(text).unwrap_err().to_string(),
+        "invalid type: integer `2`, expected a string (byte 25)"
+    );
+    assert_eq!(
+        custom(from_str::<Job>(r#"{"tags":[]}"#).unwrap_err()),
+        ("missing field `title`".into(), Some(10))
+    );
+    assert_eq!(custom(from_str::<u8>("300").unwrap_err()).1, Some(0));
+
+    // invalid JSON is a `ParseError` with its whole location
+    match from_str::<Vec<u8>>("[1,\n 2,]").unwrap_err() {
+        Error::Parse(e) => {
+            let location = e.location();
+            assert_eq!((location.offset, location.line, location.column), (7, 2, 4));
+        }
+        e => panic!("expected a parse error, got {}", e),
+    }
+    for (text, at) in [("[1] [2]", 4), ("1 2", 2)] {
+        match from_str::<JsValue>(text).unwrap_err() {
+            Error::Parse(e) => {
+                assert!(matches!(*e, ParseError::TrailingCharacters { .. }));
+                assert_eq!(e.location().offset, at);
+            }
+            e => panic!("expected a parse error, got {}", e),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::JsValue;
+use common::parse;
+
+fn string(input: &str) -> String {
+    match parse(input) {
+        JsValue::JsString(s) => s,
+        other => panic!("{} parsed as {:?}", input, other),
+    }
+}
+
+#[test]
+fn simple_escapes() {
+    assert_eq!(string(r#""a\"b""#), "a\"b");
+    assert_eq!(string(r#""a\\b""#), "a\\b");
+    assert_eq!(string(r#""a\/b""#), "a/b");
+    assert_eq!(string(r#""\b\f\n\r\t""#), "\u{8}\u{c}\n\r\t");
+    assert_eq!(string(r#""\\n""#), "\\n");
+}
+
+#[test]
+fn unicode_escapes() {
+    assert_eq!(string(r#""caf\u00e9""#), "caf\u{e9}");
+    assert_eq!(string(r#""\u00E9\u65e5""#), "\u{e9}\u{65e5}");
+    assert_eq!(string(r#""\u0000""#), "\0");
+    // characters outside the BMP are written as a surrogate pair
+    assert_eq!(string(r#""\ud83d\ude00""#), "\u{1f600}");
+    assert_eq!(string(r#""x\uD834\uDD1Ey""#), "x\u{1d11e}y");
+}
+
+#[test]
+fn raw_unicode_passes_through() {
+    assert_eq!(
+        string("\"\u{e9}\u{65e5}\u{1f600}\u{7f}\""),
+        "\u{e9}\u{65e5}\u{1f600}\u{7f}"
+    );
+}
+
+#[test]
+fn escapes_in_keys() {
+    let JsValue::JsObject(members) = parse(r#"{"a\nb": 1, "\u00e9": 2}"#) else {
+        panic!("not an object");
+    };
+    assert!(members.contains_key("a\nb"));
+    assert!(members.contains_key("\u{e9}"));
+}
+
+fn invalid_escape(input: &str) -> (String, usize) {
+    match arjer::parse(input).unwrap_err() {
+        ParseError::InvalidEscape { sequence, at } => (sequence, at.offset),
+        other => panic!("{}: expected InvalidEscape, got {:?}", input, other),
+    }
+}
+
+#[test]
+fn lone_surrogates_are_rejected() {
+    assert_eq!(invalid_escape(r#""\ud83d""#), (r"\ud83d".into(), 1));
+    assert_eq!(invalid_escape(r#""ab\ud83dx""#), (r"\ud83d".into(), 3));
+    assert_eq!(invalid_escape(r#""\ud83dA""#), (r"\ud83d".into(), 1));
+    assert_eq!(invalid_escape(r#""\ude00""#), (r"\ude00".into(), 1));
+    // the sequence is reported with lowercase hex digits, however it was written
+    assert_eq!(invalid_escape(r#"["ok", "\uDFFF"]"#), (r"\udfff".into(), 8));
+}
+
+#[test]
+fn unknown_and_malformed_escapes_are_rejected() {
+    assert_eq!(invalid_escape(r#""\x""#), (r"\x".into(), 1));
+    assert_eq!(invalid_escape(r#""\'""#), (r"\'".into(), 1));
+    assert_eq!(invalid_escape(r#""\uZZZZ""#), (r"\uZ".into(), 1));
+    let (sequence, offset) = invalid_escape(r#""\u12""#);
+    assert!(sequence.starts_with(r"\u12"), "{}", sequence);
+    assert_eq!(offset, 1);
+}
+
+#[test]
+fn raw_control_characters_are_rejected() {
+    for byte in 0x00..=0x1f_u8 {
+        let input = format!("[\"a{}b\"]", byte as char);
+        match arjer::parse(&input).unwrap_err() {
+            ParseError::ControlCharacter { value, at } => {
+                assert_eq!(value, byte);
+                assert_eq!(at.offset, 3);
+            }
+            other => panic!("{:#04x}: expected ControlCharacter, got {:?}", byte, other),
+        }
+    }
+    assert!(arjer::parse("{\"a\tb\": 1}").is_err());
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use arjer::error::ParseError;
+use arjer::json::{JsValue, Num};
+use common::parse;
+
+fn trailing_offset(input: &str) -> usize {
+    match arjer::parse(input).unwrap_err() {
+        ParseError::TrailingCharacters { at } => at.offset,
+        other => panic!("{:?}: expected TrailingCharacters, got {:?}", input, other),
+    }
+}
+
+#[test]
+fn content_after_the_value_is_rejected() {
+    assert_eq!(trailing_offset("{} garbage"), 3);
+    assert_eq!(trailing_offset("1 2"), 2);
+    assert_eq!(trailing_offset("[1]]"), 3);
+    assert_eq!(trailing_offset("\"a\"\n\n  x"), 7);
+    assert!(matches!(
+        arjer::parse_borrowed("{} {}").unwrap_err(),
+        ParseError::TrailingCharacters { .. }
+    ));
+    assert!(matches!(
+        arjer::from_reader("null null".as_bytes()).unwrap_err(),
+        ParseError::TrailingCharacters { .. }
+    ));
+}
+
+#[test]
+fn trailing_whitespace_is_fine() {
+    for input in ["{} ", "1\n", "[]\r\n\t ", "  true  "] {
+        assert!(arjer::parse(input).is_ok(), "{:?}", input);
+    }
+}
+
+#[test]
+fn parse_prefix_returns_the_rest() {
+    let (value, rest) = arjer::parse_prefix("{} {}").unwrap();
+    assert!(matches!(value, JsValue::JsObject(members) if members.is_empty()));
+    assert_eq!(rest, "{}");
+
+    let input = "[1, 2]\n  3 garbage";
+    let (value, rest) = arjer::parse_prefix(input).unwrap();
+    assert!(matches!(value, JsValue::JsArray(values) if values.len() == 2));
+    assert_eq!(rest, "3 garbage");
+    assert_eq!(input.len() - rest.len(), 9);
+
+    let (value, rest) = arjer::parse_prefix("1 2").unwrap();
+    assert!(matches!(value, JsValue::JsNumber(Num::U32(1))));
+    assert_eq!(rest, "2");
+    assert!(matches!(parse(rest), JsValue::JsNumber(Num::U32(2))));
+
+    let (_, rest) = arjer::parse_prefix("true   ").unwrap();
+    assert_eq!(rest, "");
+}
+
+#[test]
+fn parse_prefix_reports_errors_in_the_value() {
+    assert!(arjer::parse_prefix("[1, ").is_err());
+    assert!(arjer::parse_prefix("").is_err());
+}
@@ -0,0 +1,9 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+#[json(rename = "job")]
+struct Job {
+    title: String,
+}
+
+fn main() {}
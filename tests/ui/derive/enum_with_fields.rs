@@ -0,0 +1,9 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+enum Shape {
+    Circle { radius: f64 },
+    Empty,
+}
+
+fn main() {}
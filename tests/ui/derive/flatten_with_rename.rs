@@ -0,0 +1,14 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+struct Job {
+    #[json(flatten, rename = "where")]
+    address: Address,
+}
+
+#[derive(FromJson)]
+struct Address {
+    city: String,
+}
+
+fn main() {}
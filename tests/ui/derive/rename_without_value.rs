@@ -0,0 +1,9 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+struct Job {
+    #[json(rename)]
+    title: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+struct Point(#[json(default)] i32, i32);
+
+fn main() {}
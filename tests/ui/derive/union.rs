@@ -0,0 +1,9 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
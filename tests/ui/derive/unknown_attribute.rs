@@ -0,0 +1,9 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+struct Job {
+    #[json(rname = "name")]
+    title: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use arjer::FromJson;
+
+#[derive(FromJson)]
+enum Color {
+    Red,
+    #[json(default)]
+    Green,
+}
+
+fn main() {}